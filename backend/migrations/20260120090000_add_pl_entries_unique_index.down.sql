@@ -0,0 +1,2 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS uq_pl_entries_project_level;
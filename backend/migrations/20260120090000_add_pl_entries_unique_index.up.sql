@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- bulk_upsert の ON CONFLICT で使用する Project 単位（job_id IS NULL）の一意制約
+CREATE UNIQUE INDEX uq_pl_entries_project_level
+    ON pl_entries(project_id, scenario, account_item_id, date)
+    WHERE job_id IS NULL;
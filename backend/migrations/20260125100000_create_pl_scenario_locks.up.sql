@@ -0,0 +1,20 @@
+-- Add up migration script here
+
+-- 確定済みシナリオのロック（解除履歴も保持する）
+CREATE TABLE pl_scenario_locks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    project_id UUID NOT NULL REFERENCES projects(id),
+    scenario scenario_type NOT NULL,
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+
+    locked_by UUID NOT NULL REFERENCES users(id),
+    locked_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    unlocked_by UUID REFERENCES users(id),
+    unlocked_at TIMESTAMP WITH TIME ZONE,
+    unlock_reason TEXT,
+
+    CHECK (period_start <= period_end)
+);
+
+CREATE INDEX idx_pl_scenario_locks_project_scenario ON pl_scenario_locks(project_id, scenario);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS fiscal_periods;
+
+DROP TYPE IF EXISTS period_status;
@@ -0,0 +1,20 @@
+-- Add up migration script here
+
+CREATE TYPE period_status AS ENUM (
+    'Open',
+    'Closed'
+);
+
+-- 会計期間（月次）
+CREATE TABLE fiscal_periods (
+    fiscal_year INTEGER NOT NULL,
+    period INTEGER NOT NULL CHECK (period BETWEEN 1 AND 12),
+    start_date DATE NOT NULL UNIQUE,
+    end_date DATE NOT NULL,
+    status period_status NOT NULL DEFAULT 'Open',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+
+    PRIMARY KEY (fiscal_year, period),
+    CHECK (start_date <= end_date)
+);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS fiscal_period_events;
+
+ALTER TABLE fiscal_periods
+    DROP COLUMN IF EXISTS closed_by,
+    DROP COLUMN IF EXISTS closed_at;
@@ -0,0 +1,20 @@
+-- Add up migration script here
+
+ALTER TABLE fiscal_periods
+    ADD COLUMN closed_by UUID REFERENCES users(id),
+    ADD COLUMN closed_at TIMESTAMP WITH TIME ZONE;
+
+-- 月次締め・締め解除の履歴
+CREATE TABLE fiscal_period_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    fiscal_year INTEGER NOT NULL,
+    period INTEGER NOT NULL,
+    status period_status NOT NULL,
+    reason TEXT,
+    acted_by UUID NOT NULL REFERENCES users(id),
+    acted_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+
+    FOREIGN KEY (fiscal_year, period) REFERENCES fiscal_periods(fiscal_year, period)
+);
+
+CREATE INDEX idx_fiscal_period_events_period ON fiscal_period_events(fiscal_year, period);
@@ -0,0 +1,15 @@
+-- Add down migration script here
+
+-- enum の値は削除できないため型を作り直す（追加した区分の科目が残っている場合は失敗する）
+ALTER TYPE account_type RENAME TO account_type_old;
+
+CREATE TYPE account_type AS ENUM (
+    'Revenue',
+    'CostOfGoodsSold',
+    'SellingGeneralAdmin'
+);
+
+ALTER TABLE account_items
+    ALTER COLUMN account_type TYPE account_type USING account_type::text::account_type;
+
+DROP TYPE account_type_old;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- 経常利益・当期純利益を算出するための区分を追加する
+ALTER TYPE account_type ADD VALUE IF NOT EXISTS 'NonOperatingIncome';
+ALTER TYPE account_type ADD VALUE IF NOT EXISTS 'NonOperatingExpense';
+ALTER TYPE account_type ADD VALUE IF NOT EXISTS 'Tax';
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DELETE FROM account_items
+WHERE account_type IN ('NonOperatingIncome', 'NonOperatingExpense', 'Tax')
+  AND name IN ('営業外収益', '営業外費用', '法人税等');
@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+-- enum に追加した値は同じトランザクション内で使えないため、初期データは別のマイグレーションで登録する
+INSERT INTO account_items (name, account_type, description, display_order, is_active) VALUES
+('営業外収益', 'NonOperatingIncome', '受取利息や為替差益など、本業以外から生じる収益', 40, true),
+('営業外費用', 'NonOperatingExpense', '支払利息や為替差損など、本業以外から生じる費用', 50, true),
+('法人税等', 'Tax', '法人税、住民税及び事業税', 60, true);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_account_items_parent_id;
+
+ALTER TABLE account_items
+    DROP COLUMN IF EXISTS parent_id;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+-- 勘定科目の親子関係（販管費 > 人件費・外注費・地代家賃 など）
+ALTER TABLE account_items
+    ADD COLUMN parent_id UUID REFERENCES account_items(id);
+
+CREATE INDEX idx_account_items_parent_id ON account_items(parent_id);
@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- Job 単位（job_id IS NOT NULL）の bulk_upsert の ON CONFLICT で使用する一意制約
+CREATE UNIQUE INDEX uq_pl_entries_job_level
+    ON pl_entries(job_id, scenario, account_item_id, date)
+    WHERE job_id IS NOT NULL;
@@ -0,0 +1,25 @@
+-- Add up migration script here
+
+-- シナリオ保存時点のPL明細（Project単位）のスナップショット
+CREATE TABLE pl_snapshots (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    project_id UUID NOT NULL REFERENCES projects(id),
+    scenario scenario_type NOT NULL,
+    version INTEGER NOT NULL,
+    comment TEXT,
+
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+
+    UNIQUE (project_id, scenario, version)
+);
+
+CREATE TABLE pl_snapshot_entries (
+    snapshot_id UUID NOT NULL REFERENCES pl_snapshots(id) ON DELETE CASCADE,
+    account_item_id UUID NOT NULL REFERENCES account_items(id),
+    date DATE NOT NULL,
+    amount DECIMAL(19, 4) NOT NULL,
+    description TEXT,
+
+    PRIMARY KEY (snapshot_id, account_item_id, date)
+);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS pl_sandbox_entries;
+DROP TABLE IF EXISTS pl_sandboxes;
@@ -0,0 +1,29 @@
+-- Add up migration script here
+
+-- 正式なシナリオに影響しない試算用のシナリオ（作成者のみ参照・編集できる）
+CREATE TABLE pl_sandboxes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(200) NOT NULL,
+    description TEXT,
+    project_id UUID REFERENCES projects(id) ON DELETE CASCADE,
+    theme_id UUID REFERENCES themes(id) ON DELETE CASCADE,
+    source_scenario scenario_type NOT NULL,
+
+    owner_id UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+
+    CHECK ((project_id IS NULL) <> (theme_id IS NULL))
+);
+
+CREATE INDEX idx_pl_sandboxes_owner ON pl_sandboxes(owner_id);
+
+-- 試算用シナリオの勘定科目・月ごとの金額
+CREATE TABLE pl_sandbox_entries (
+    sandbox_id UUID NOT NULL REFERENCES pl_sandboxes(id) ON DELETE CASCADE,
+    account_item_id UUID NOT NULL REFERENCES account_items(id),
+    month DATE NOT NULL,
+    amount DECIMAL(19, 4) NOT NULL,
+
+    PRIMARY KEY (sandbox_id, account_item_id, month)
+);
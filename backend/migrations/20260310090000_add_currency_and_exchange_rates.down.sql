@@ -0,0 +1,7 @@
+-- Add down migration script here
+DROP FUNCTION IF EXISTS exchange_rate(scenario_type, CHAR(3), CHAR(3), DATE);
+DROP TABLE IF EXISTS scenario_rate_types;
+DROP TABLE IF EXISTS exchange_rates;
+DROP TYPE IF EXISTS exchange_rate_type;
+ALTER TABLE pl_snapshot_entries DROP COLUMN IF EXISTS currency;
+ALTER TABLE pl_entries DROP COLUMN IF EXISTS currency;
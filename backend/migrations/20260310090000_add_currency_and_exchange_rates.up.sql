@@ -0,0 +1,64 @@
+-- Add up migration script here
+
+-- 明細の通貨（既存の明細は円建て）
+ALTER TABLE pl_entries ADD COLUMN currency CHAR(3) DEFAULT 'JPY' NOT NULL;
+ALTER TABLE pl_snapshot_entries ADD COLUMN currency CHAR(3) DEFAULT 'JPY' NOT NULL;
+
+CREATE TYPE exchange_rate_type AS ENUM ('Budget', 'Actual');
+
+-- 為替レート（1通貨単位あたりの報告通貨額）。指定した月から次のレートの月まで適用する
+CREATE TABLE exchange_rates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    rate_type exchange_rate_type NOT NULL,
+    currency CHAR(3) NOT NULL,
+    month DATE NOT NULL,
+    rate DECIMAL(19, 8) NOT NULL CHECK (rate > 0),
+
+    created_by UUID NOT NULL REFERENCES users(id),
+    updated_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+
+    UNIQUE (rate_type, currency, month)
+);
+
+-- シナリオごとに使うレートの種類
+CREATE TABLE scenario_rate_types (
+    scenario scenario_type PRIMARY KEY,
+    rate_type exchange_rate_type NOT NULL,
+
+    updated_by UUID REFERENCES users(id),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+INSERT INTO scenario_rate_types (scenario, rate_type) VALUES
+    ('MasterPlan', 'Budget'),
+    ('RevisedPlan', 'Budget'),
+    ('InitialPlan', 'Budget'),
+    ('ExecPlanAdjust', 'Budget'),
+    ('JobPlan', 'Budget'),
+    ('Actual', 'Actual');
+
+-- シナリオの明細を報告通貨に換算するレート。レートが登録されていなければ NULL を返す
+CREATE FUNCTION exchange_rate(
+    p_scenario scenario_type,
+    p_currency CHAR(3),
+    p_reporting_currency CHAR(3),
+    p_date DATE
+) RETURNS NUMERIC
+LANGUAGE sql STABLE
+AS $$
+    SELECT CASE
+        WHEN p_currency = p_reporting_currency THEN 1
+        ELSE (
+            SELECT r.rate
+            FROM exchange_rates r
+            JOIN scenario_rate_types s ON s.rate_type = r.rate_type
+            WHERE s.scenario = p_scenario
+              AND r.currency = p_currency
+              AND r.month <= p_date
+            ORDER BY r.month DESC
+            LIMIT 1
+        )
+    END
+$$;
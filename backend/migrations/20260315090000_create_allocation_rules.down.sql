@@ -0,0 +1,12 @@
+-- Add down migration script here
+DELETE FROM pl_entries WHERE allocation_run_id IS NOT NULL;
+DROP INDEX IF EXISTS uq_pl_entries_project_level;
+CREATE UNIQUE INDEX uq_pl_entries_project_level
+    ON pl_entries(project_id, scenario, account_item_id, date)
+    WHERE job_id IS NULL;
+DROP INDEX IF EXISTS idx_pl_entries_allocation_run;
+ALTER TABLE pl_entries DROP COLUMN IF EXISTS allocation_run_id;
+DROP TABLE IF EXISTS allocation_runs;
+DROP TABLE IF EXISTS allocation_rule_targets;
+DROP TABLE IF EXISTS allocation_rules;
+DROP TYPE IF EXISTS allocation_driver;
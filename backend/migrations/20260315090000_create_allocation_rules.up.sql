@@ -0,0 +1,68 @@
+-- Add up migration script here
+
+CREATE TYPE allocation_driver AS ENUM (
+    'FixedRatio',   -- 配賦先ごとに指定した比率
+    'RevenueShare', -- 配賦先の同じ月の売上高の比率
+    'JobCount'      -- 配賦先で同じ月に明細があるJobの数の比率
+);
+
+-- 共通費（配賦元Projectの勘定科目に計上した金額）を各Projectに配賦するルール
+CREATE TABLE allocation_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(200) NOT NULL,
+    description TEXT,
+    source_project_id UUID NOT NULL REFERENCES projects(id),
+    source_account_item_id UUID NOT NULL REFERENCES account_items(id),
+    -- 配賦先に計上する勘定科目
+    target_account_item_id UUID NOT NULL REFERENCES account_items(id),
+    scenario scenario_type NOT NULL,
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    driver allocation_driver NOT NULL,
+
+    created_by UUID NOT NULL REFERENCES users(id),
+    updated_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+
+    CHECK (period_start <= period_end)
+);
+
+-- 配賦先のProject（FixedRatio 以外で指定がなければ配賦元以外の全Project）
+CREATE TABLE allocation_rule_targets (
+    rule_id UUID NOT NULL REFERENCES allocation_rules(id) ON DELETE CASCADE,
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    -- FixedRatio の比率
+    ratio DECIMAL(19, 8) CHECK (ratio > 0),
+
+    PRIMARY KEY (rule_id, project_id)
+);
+
+-- 配賦の実行履歴（取り消すと生成した明細を削除する）
+CREATE TABLE allocation_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    rule_id UUID NOT NULL REFERENCES allocation_rules(id) ON DELETE CASCADE,
+    total_amount DECIMAL(19, 4) NOT NULL,
+
+    run_by UUID NOT NULL REFERENCES users(id),
+    run_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    reversed_by UUID REFERENCES users(id),
+    reversed_at TIMESTAMP WITH TIME ZONE
+);
+
+-- ルールごとに有効な実行は1つまで
+CREATE UNIQUE INDEX uq_allocation_runs_active
+    ON allocation_runs(rule_id)
+    WHERE reversed_at IS NULL;
+
+-- 配賦で生成した明細は手入力の明細とは別の行として持つ
+ALTER TABLE pl_entries ADD COLUMN allocation_run_id UUID REFERENCES allocation_runs(id);
+
+CREATE INDEX idx_pl_entries_allocation_run
+    ON pl_entries(allocation_run_id)
+    WHERE allocation_run_id IS NOT NULL;
+
+DROP INDEX uq_pl_entries_project_level;
+CREATE UNIQUE INDEX uq_pl_entries_project_level
+    ON pl_entries(project_id, scenario, account_item_id, date)
+    WHERE job_id IS NULL AND allocation_run_id IS NULL;
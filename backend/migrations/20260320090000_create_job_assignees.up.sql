@@ -0,0 +1,14 @@
+-- Add up migration script here
+
+-- Jobの担当者（所有者以外に Job と Job単位のPL明細を編集できるユーザー）
+CREATE TABLE job_assignees (
+    job_id UUID NOT NULL REFERENCES jobs(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id),
+
+    assigned_by UUID NOT NULL REFERENCES users(id),
+    assigned_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+
+    PRIMARY KEY (job_id, user_id)
+);
+
+CREATE INDEX idx_job_assignees_user ON job_assignees(user_id);
@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+-- 招待（管理者が発行し、招待されたメールアドレスで一度だけユーザー登録に使える）
+CREATE TABLE user_invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    email VARCHAR(255) NOT NULL,
+    role VARCHAR(50) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+
+    invited_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    accepted_by UUID REFERENCES users(id),
+    accepted_at TIMESTAMP WITH TIME ZONE,
+    revoked_by UUID REFERENCES users(id),
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS login_failures;
+DROP TABLE IF EXISTS login_events;
+DROP TYPE IF EXISTS login_outcome;
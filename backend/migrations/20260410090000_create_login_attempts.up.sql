@@ -0,0 +1,34 @@
+-- Add up migration script here
+
+CREATE TYPE login_outcome AS ENUM (
+    'Succeeded',
+    'UnknownEmail',    -- 登録されていないメールアドレス
+    'InvalidPassword', -- パスワードの誤り
+    'Inactive',        -- 無効化されたユーザー
+    'Locked'           -- 失敗が続いたためロック中
+);
+
+-- ログインの試行履歴（セキュリティ監査用）
+CREATE TABLE login_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    email VARCHAR(255) NOT NULL,
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    outcome login_outcome NOT NULL,
+    ip_address VARCHAR(45) NOT NULL,
+    user_agent TEXT,
+
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_login_events_created ON login_events(created_at DESC);
+CREATE INDEX idx_login_events_user ON login_events(user_id, created_at DESC);
+CREATE INDEX idx_login_events_email ON login_events(email, created_at DESC);
+
+-- アカウントごとの連続失敗回数とロック
+-- 登録の有無を推測されないよう、未登録のメールアドレスも同じように数える
+CREATE TABLE login_failures (
+    email VARCHAR(255) PRIMARY KEY,
+    failed_count INTEGER NOT NULL,
+    last_failed_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    locked_until TIMESTAMP WITH TIME ZONE
+);
@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE pl_scenario_locks DROP CONSTRAINT IF EXISTS pl_scenario_locks_no_overlap;
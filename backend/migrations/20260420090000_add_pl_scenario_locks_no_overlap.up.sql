@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+CREATE EXTENSION IF NOT EXISTS btree_gist;
+
+-- 同じプロジェクト・シナリオで期間が重なる有効なロックを作らせない
+ALTER TABLE pl_scenario_locks
+    ADD CONSTRAINT pl_scenario_locks_no_overlap
+    EXCLUDE USING gist (
+        project_id WITH =,
+        scenario WITH =,
+        daterange(period_start, period_end, '[]') WITH &&
+    ) WHERE (unlocked_at IS NULL);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DELETE FROM pl_snapshot_entries WHERE job_id IS NOT NULL;
+ALTER TABLE pl_snapshot_entries DROP CONSTRAINT pl_snapshot_entries_entry_key;
+ALTER TABLE pl_snapshot_entries DROP COLUMN job_id;
+ALTER TABLE pl_snapshot_entries ADD PRIMARY KEY (snapshot_id, account_item_id, date);
@@ -0,0 +1,8 @@
+-- Add up migration script here
+
+-- Job単位のシナリオの版はProject配下の全JobのPL明細を記録する
+ALTER TABLE pl_snapshot_entries DROP CONSTRAINT pl_snapshot_entries_pkey;
+ALTER TABLE pl_snapshot_entries ADD COLUMN job_id UUID REFERENCES jobs(id);
+ALTER TABLE pl_snapshot_entries
+    ADD CONSTRAINT pl_snapshot_entries_entry_key
+    UNIQUE NULLS NOT DISTINCT (snapshot_id, job_id, account_item_id, date);
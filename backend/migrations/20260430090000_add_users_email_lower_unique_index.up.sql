@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+-- 大文字・小文字だけが異なるメールアドレスを登録させない（ログインは小文字にして照合する）
+CREATE UNIQUE INDEX users_email_lower_key ON users (lower(email));
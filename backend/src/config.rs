@@ -5,6 +5,31 @@ pub struct Config {
     pub host: String,
     pub port: u16,
     pub jwt_secret: String,
+    pub access_token_ttl_minutes: i64,
+    pub refresh_token_ttl_days: i64,
+    pub auth_cache_ttl_seconds: u64,
+    pub invitation_ttl_days: i64,
+    pub password_reset_ttl_minutes: i64,
+    pub app_url: String,
+    pub mailer: String,
+    pub mail_from: String,
+    pub mail_dir: Option<String>,
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub login_max_failures: i32,
+    pub login_lockout_minutes: i64,
+    pub auth_rate_limit_per_minute: u32,
+    pub trust_proxy_headers: bool,
+    pub fiscal_year_start_month: u32,
+    pub job_budget_policy: String,
+    pub reporting_currency: String,
+    pub initial_admin_email: Option<String>,
+    pub initial_admin_password: Option<String>,
+    pub initial_admin_employee_id: String,
+    pub initial_admin_username: String,
+    pub initial_admin_name: String,
 }
 
 impl Config {
@@ -17,6 +42,73 @@ impl Config {
                 .and_then(|p| p.parse().ok())
                 .unwrap_or(3000),
             jwt_secret: env::var("JWT_SECRET")?,
+            access_token_ttl_minutes: env::var("ACCESS_TOKEN_TTL_MINUTES")
+                .ok()
+                .and_then(|m| m.parse().ok())
+                .unwrap_or(15),
+            refresh_token_ttl_days: env::var("REFRESH_TOKEN_TTL_DAYS")
+                .ok()
+                .and_then(|d| d.parse().ok())
+                .unwrap_or(14),
+            auth_cache_ttl_seconds: env::var("AUTH_CACHE_TTL_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(30),
+            invitation_ttl_days: env::var("INVITATION_TTL_DAYS")
+                .ok()
+                .and_then(|d| d.parse().ok())
+                .unwrap_or(7),
+            password_reset_ttl_minutes: env::var("PASSWORD_RESET_TTL_MINUTES")
+                .ok()
+                .and_then(|m| m.parse().ok())
+                .unwrap_or(60),
+            app_url: env::var("APP_URL").unwrap_or_else(|_| "http://localhost:5173".to_string()),
+            mailer: env::var("MAILER")?,
+            mail_from: env::var("MAIL_FROM")
+                .unwrap_or_else(|_| "Ghost <no-reply@localhost>".to_string()),
+            mail_dir: env::var("MAIL_DIR").ok().filter(|v| !v.is_empty()),
+            smtp_host: env::var("SMTP_HOST").ok().filter(|v| !v.is_empty()),
+            smtp_port: env::var("SMTP_PORT")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(587),
+            smtp_username: env::var("SMTP_USERNAME").ok().filter(|v| !v.is_empty()),
+            smtp_password: env::var("SMTP_PASSWORD").ok().filter(|v| !v.is_empty()),
+            login_max_failures: env::var("LOGIN_MAX_FAILURES")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(5),
+            login_lockout_minutes: env::var("LOGIN_LOCKOUT_MINUTES")
+                .ok()
+                .and_then(|m| m.parse().ok())
+                .unwrap_or(15),
+            auth_rate_limit_per_minute: env::var("AUTH_RATE_LIMIT_PER_MINUTE")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(20),
+            trust_proxy_headers: env::var("TRUST_PROXY_HEADERS")
+                .ok()
+                .and_then(|b| b.parse().ok())
+                .unwrap_or(false),
+            fiscal_year_start_month: env::var("FISCAL_YEAR_START_MONTH")
+                .ok()
+                .and_then(|m| m.parse().ok())
+                .unwrap_or(4),
+            job_budget_policy: env::var("JOB_BUDGET_POLICY").unwrap_or_else(|_| "warn".to_string()),
+            reporting_currency: env::var("REPORTING_CURRENCY")
+                .unwrap_or_else(|_| "JPY".to_string()),
+            initial_admin_email: env::var("INITIAL_ADMIN_EMAIL")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            initial_admin_password: env::var("INITIAL_ADMIN_PASSWORD")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            initial_admin_employee_id: env::var("INITIAL_ADMIN_EMPLOYEE_ID")
+                .unwrap_or_else(|_| "00000".to_string()),
+            initial_admin_username: env::var("INITIAL_ADMIN_USERNAME")
+                .unwrap_or_else(|_| "admin".to_string()),
+            initial_admin_name: env::var("INITIAL_ADMIN_NAME")
+                .unwrap_or_else(|_| "Administrator".to_string()),
         })
     }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Type};
@@ -5,14 +7,29 @@ use uuid::Uuid;
 
 use crate::error::AppError;
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Type)]
-#[sqlx(type_name = "account_category", rename_all = "snake_case")]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash, Type)]
+#[sqlx(type_name = "account_type", rename_all = "PascalCase")]
 pub enum AccountType {
-    Revenue,
-    CostOfGoodsSold,
-    SellingGeneralAdmin,
-    NonOperationg,
-    Tax,
+    Revenue,             // 売上高
+    CostOfGoodsSold,     // 売上原価
+    SellingGeneralAdmin, // 販管費
+    NonOperatingIncome,  // 営業外収益
+    NonOperatingExpense, // 営業外費用
+    Tax,                 // 法人税等
+}
+
+impl AccountType {
+    /// 帳票に表示する区分名
+    pub fn label(&self) -> &'static str {
+        match self {
+            AccountType::Revenue => "売上高",
+            AccountType::CostOfGoodsSold => "売上原価",
+            AccountType::SellingGeneralAdmin => "販管費",
+            AccountType::NonOperatingIncome => "営業外収益",
+            AccountType::NonOperatingExpense => "営業外費用",
+            AccountType::Tax => "法人税等",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -21,6 +38,8 @@ pub struct AccountItem {
     pub name: String,
     #[sqlx(rename = "account_type")]
     pub account_type: AccountType,
+    /// 親科目（親子は2階層まで、同じ区分に限る）
+    pub parent_id: Option<Uuid>,
     pub display_order: i32,
     pub description: Option<String>,
     pub is_active: bool,
@@ -28,7 +47,62 @@ pub struct AccountItem {
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone)]
+pub struct CreateAccountItemParam {
+    pub name: String,
+    pub account_type: AccountType,
+    pub parent_id: Option<Uuid>,
+    /// 指定がなければ末尾に追加する
+    pub display_order: Option<i32>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateAccountItemParam {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Some(None) で親科目を外す
+    pub parent_id: Option<Option<Uuid>>,
+    pub display_order: Option<i32>,
+    pub is_active: Option<bool>,
+}
+
+/// 親科目の直後に子科目が続くように並べる
+///
+/// 親が含まれない子科目は最上位の科目として扱う
+pub fn in_hierarchy_order<'a>(
+    items: impl IntoIterator<Item = &'a AccountItem>,
+) -> Vec<&'a AccountItem> {
+    let mut items: Vec<&AccountItem> = items.into_iter().collect();
+    items.sort_by_key(|item| item.display_order);
+
+    let ids: HashSet<Uuid> = items.iter().map(|item| item.id).collect();
+    let is_root = |item: &AccountItem| item.parent_id.is_none_or(|p| !ids.contains(&p));
+
+    let mut ordered = Vec::with_capacity(items.len());
+    for root in items.iter().filter(|item| is_root(item)) {
+        ordered.push(*root);
+        ordered.extend(
+            items
+                .iter()
+                .filter(|item| !is_root(item) && item.parent_id == Some(root.id)),
+        );
+    }
+    ordered
+}
+
 #[async_trait::async_trait]
 pub trait AccountItemRepository: Send + Sync {
     async fn find_all(&self) -> Result<Vec<AccountItem>, AppError>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<AccountItem>, AppError>;
+    async fn create(&self, params: CreateAccountItemParam) -> Result<AccountItem, AppError>;
+    async fn update(
+        &self,
+        id: Uuid,
+        params: UpdateAccountItemParam,
+    ) -> Result<AccountItem, AppError>;
+    /// 指定された順に表示順を振り直す
+    async fn reorder(&self, ids: Vec<Uuid>) -> Result<Vec<AccountItem>, AppError>;
+    /// PL明細や子科目がある科目は削除できない（無効化で対応する）
+    async fn delete(&self, id: Uuid) -> Result<(), AppError>;
 }
@@ -0,0 +1,365 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Type, prelude::FromRow};
+use uuid::Uuid;
+
+use crate::{
+    domains::{fiscal::PeriodRange, pl_entry::Scenario},
+    error::AppError,
+};
+
+/// 配賦基準
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Type)]
+#[sqlx(type_name = "allocation_driver", rename_all = "PascalCase")]
+pub enum AllocationDriver {
+    FixedRatio,   // 配賦先ごとに指定した比率
+    RevenueShare, // 配賦先の同じ月の売上高の比率
+    JobCount,     // 配賦先で同じ月に JobPlan か Actual の明細があるJobの数の比率
+}
+
+/// 共通費を各Projectに配賦するルール
+///
+/// 配賦元Projectの勘定科目に計上した金額を月ごとに配賦先へ振り替え、配賦元には同額のマイナスを計上する
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AllocationRule {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub source_project_id: Uuid,
+    pub source_account_item_id: Uuid,
+    /// 配賦先に計上する勘定科目
+    pub target_account_item_id: Uuid,
+    pub scenario: Scenario,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub driver: AllocationDriver,
+
+    pub created_by: Uuid,
+    pub updated_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl AllocationRule {
+    pub fn period(&self) -> PeriodRange {
+        PeriodRange {
+            start: self.period_start,
+            end: self.period_end,
+        }
+    }
+}
+
+/// 配賦先のProject
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct AllocationTarget {
+    pub project_id: Uuid,
+    /// FixedRatio の比率（合計が1でなくてもよい）
+    pub ratio: Option<Decimal>,
+}
+
+/// 配賦の実行履歴
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AllocationRun {
+    pub id: Uuid,
+    pub rule_id: Uuid,
+    /// 配賦先に振り替えた金額の合計
+    pub total_amount: Decimal,
+
+    pub run_by: Uuid,
+    pub run_at: DateTime<Utc>,
+    pub reversed_by: Option<Uuid>,
+    pub reversed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AllocationRuleParam {
+    pub name: String,
+    pub description: Option<String>,
+    pub source_project_id: Uuid,
+    pub source_account_item_id: Uuid,
+    pub target_account_item_id: Uuid,
+    pub scenario: Scenario,
+    pub period: PeriodRange,
+    pub driver: AllocationDriver,
+    pub targets: Vec<AllocationTarget>,
+    pub user_id: Uuid,
+}
+
+/// 配賦元の月ごとの金額（報告通貨）
+#[derive(Debug, Clone, FromRow)]
+pub struct SourceAmount {
+    pub month: NaiveDate,
+    pub amount: Decimal,
+}
+
+/// 配賦先の月ごとの配賦基準の値
+#[derive(Debug, Clone, FromRow)]
+pub struct DriverWeight {
+    pub project_id: Uuid,
+    pub month: NaiveDate,
+    pub weight: Decimal,
+}
+
+/// 配賦で生成する明細
+#[derive(Debug, Clone, Serialize)]
+pub struct AllocationLine {
+    pub project_id: Uuid,
+    pub account_item_id: Uuid,
+    pub month: NaiveDate,
+    pub amount: Decimal,
+}
+
+/// 配賦元の金額を月ごとに配賦基準の比率で按分し、配賦元の振替（マイナス）とあわせた明細を作る
+///
+/// 端数は比率が最も大きい配賦先に寄せ、配賦元の金額と配賦額の合計を一致させる
+pub fn allocate(
+    rule: &AllocationRule,
+    sources: &[SourceAmount],
+    weights: &[DriverWeight],
+) -> Result<Vec<AllocationLine>, AppError> {
+    let mut by_month: BTreeMap<NaiveDate, Vec<&DriverWeight>> = BTreeMap::new();
+    for weight in weights.iter().filter(|w| w.weight > Decimal::ZERO) {
+        by_month.entry(weight.month).or_default().push(weight);
+    }
+
+    let mut lines = Vec::new();
+    for source in sources.iter().filter(|s| !s.amount.is_zero()) {
+        let Some(month_weights) = by_month.get(&source.month) else {
+            return Err(AppError::Validation(format!(
+                "No {:?} driver to allocate {} in {}",
+                rule.driver,
+                source.amount,
+                source.month.format("%Y-%m")
+            )));
+        };
+
+        let total_weight: Decimal = month_weights.iter().map(|w| w.weight).sum();
+        let mut shares: Vec<AllocationLine> = month_weights
+            .iter()
+            .map(|w| AllocationLine {
+                project_id: w.project_id,
+                account_item_id: rule.target_account_item_id,
+                month: source.month,
+                amount: (source.amount * w.weight / total_weight).round_dp(4),
+            })
+            .collect();
+
+        let remainder = source.amount - shares.iter().map(|s| s.amount).sum::<Decimal>();
+        if let Some(largest) = month_weights
+            .iter()
+            .enumerate()
+            .max_by_key(|(i, w)| (w.weight, std::cmp::Reverse(*i)))
+            .map(|(i, _)| i)
+        {
+            shares[largest].amount += remainder;
+        }
+
+        lines.extend(shares);
+        lines.push(AllocationLine {
+            project_id: rule.source_project_id,
+            account_item_id: rule.source_account_item_id,
+            month: source.month,
+            amount: -source.amount,
+        });
+    }
+
+    Ok(lines)
+}
+
+#[async_trait::async_trait]
+pub trait AllocationRepository: Send + Sync {
+    async fn find_rules(&self) -> Result<Vec<AllocationRule>, AppError>;
+    async fn find_rule(&self, id: Uuid) -> Result<Option<AllocationRule>, AppError>;
+    async fn find_targets(&self, rule_id: Uuid) -> Result<Vec<AllocationTarget>, AppError>;
+    async fn create_rule(&self, params: AllocationRuleParam) -> Result<AllocationRule, AppError>;
+    async fn update_rule(
+        &self,
+        id: Uuid,
+        params: AllocationRuleParam,
+    ) -> Result<AllocationRule, AppError>;
+    async fn delete_rule(&self, id: Uuid) -> Result<(), AppError>;
+
+    async fn find_runs(&self, rule_id: Uuid) -> Result<Vec<AllocationRun>, AppError>;
+    async fn find_run(&self, id: Uuid) -> Result<Option<AllocationRun>, AppError>;
+    /// 有効な実行があれば取り消したうえで、ルールをロックした同じトランザクション内で
+    /// 配賦元の金額と配賦基準を読んで明細を生成する
+    async fn run(
+        &self,
+        rule_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(AllocationRun, Vec<AllocationLine>), AppError>;
+    /// 実行で生成した明細を削除し、取り消し済みにする
+    async fn reverse(&self, run_id: Uuid, user_id: Uuid) -> Result<AllocationRun, AppError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule() -> AllocationRule {
+        let user = Uuid::new_v4();
+        AllocationRule {
+            id: Uuid::new_v4(),
+            name: "共通費".to_string(),
+            description: None,
+            source_project_id: Uuid::new_v4(),
+            source_account_item_id: Uuid::new_v4(),
+            target_account_item_id: Uuid::new_v4(),
+            scenario: Scenario::MasterPlan,
+            period_start: month(1),
+            period_end: month(12),
+            driver: AllocationDriver::FixedRatio,
+            created_by: user,
+            updated_by: user,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn month(m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, m, 1).unwrap()
+    }
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn source(m: u32, amount: &str) -> SourceAmount {
+        SourceAmount {
+            month: month(m),
+            amount: dec(amount),
+        }
+    }
+
+    fn weight(project_id: Uuid, m: u32, weight: &str) -> DriverWeight {
+        DriverWeight {
+            project_id,
+            month: month(m),
+            weight: dec(weight),
+        }
+    }
+
+    fn amount_of(lines: &[AllocationLine], project_id: Uuid) -> Decimal {
+        lines
+            .iter()
+            .filter(|line| line.project_id == project_id)
+            .map(|line| line.amount)
+            .sum()
+    }
+
+    #[test]
+    fn splits_by_weight_and_books_the_source_transfer() {
+        let rule = rule();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let lines = allocate(
+            &rule,
+            &[source(4, "1000")],
+            &[weight(a, 4, "1"), weight(b, 4, "3")],
+        )
+        .unwrap();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(amount_of(&lines, a), dec("250"));
+        assert_eq!(amount_of(&lines, b), dec("750"));
+        assert_eq!(amount_of(&lines, rule.source_project_id), dec("-1000"));
+        assert!(
+            lines
+                .iter()
+                .filter(|line| line.project_id != rule.source_project_id)
+                .all(|line| line.account_item_id == rule.target_account_item_id)
+        );
+        assert!(lines.iter().all(|line| line.month == month(4)));
+    }
+
+    #[test]
+    fn remainder_goes_to_the_largest_weight() {
+        let rule = rule();
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let lines = allocate(
+            &rule,
+            &[source(4, "10")],
+            &[weight(a, 4, "1"), weight(b, 4, "1"), weight(c, 4, "4")],
+        )
+        .unwrap();
+
+        // 1.6667 + 1.6667 + 6.6667 = 10.0001 のため、最も比率の大きい c から 0.0001 を差し引く
+        assert_eq!(amount_of(&lines, a), dec("1.6667"));
+        assert_eq!(amount_of(&lines, b), dec("1.6667"));
+        assert_eq!(amount_of(&lines, c), dec("6.6666"));
+        assert_eq!(
+            amount_of(&lines, a) + amount_of(&lines, b) + amount_of(&lines, c),
+            dec("10")
+        );
+    }
+
+    #[test]
+    fn remainder_goes_to_the_first_target_on_equal_weights() {
+        let rule = rule();
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let lines = allocate(
+            &rule,
+            &[source(4, "10")],
+            &[weight(a, 4, "2"), weight(b, 4, "2"), weight(c, 4, "2")],
+        )
+        .unwrap();
+
+        assert_eq!(amount_of(&lines, a), dec("3.3334"));
+        assert_eq!(amount_of(&lines, b), dec("3.3333"));
+        assert_eq!(amount_of(&lines, c), dec("3.3333"));
+    }
+
+    #[test]
+    fn allocates_each_month_by_its_own_weights() {
+        let rule = rule();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let lines = allocate(
+            &rule,
+            &[source(4, "100"), source(5, "100"), source(6, "0")],
+            &[
+                weight(a, 4, "1"),
+                weight(b, 4, "1"),
+                weight(a, 5, "1"),
+                weight(b, 5, "0"),
+            ],
+        )
+        .unwrap();
+
+        let amount = |project_id: Uuid, m: u32| -> Decimal {
+            lines
+                .iter()
+                .filter(|line| line.project_id == project_id && line.month == month(m))
+                .map(|line| line.amount)
+                .sum()
+        };
+        assert_eq!(amount(a, 4), dec("50"));
+        assert_eq!(amount(b, 4), dec("50"));
+        assert_eq!(amount(a, 5), dec("100"));
+        assert!(
+            !lines
+                .iter()
+                .any(|line| line.project_id == b && line.month == month(5))
+        );
+        assert!(!lines.iter().any(|line| line.month == month(6)));
+    }
+
+    #[test]
+    fn rejects_months_without_a_driver() {
+        let rule = rule();
+        let a = Uuid::new_v4();
+
+        let result = allocate(
+            &rule,
+            &[source(4, "100"), source(5, "100")],
+            &[weight(a, 4, "1"), weight(a, 5, "0")],
+        );
+
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+}
@@ -0,0 +1,328 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    domains::{account_item::AccountItem, pl_entry::UpsertPlEntryParam, report::MonthlyAmount},
+    error::AppError,
+};
+
+/// JobPlanがProjectの予算残を超えたときの扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetPolicy {
+    /// 登録した上で超過した明細を返す
+    Warn,
+    /// 登録せずにエラーとする
+    Reject,
+}
+
+impl FromStr for BudgetPolicy {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "warn" => Ok(BudgetPolicy::Warn),
+            "reject" => Ok(BudgetPolicy::Reject),
+            _ => Err(AppError::Validation(format!(
+                "Invalid budget policy: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// 予算消化の明細行
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetLine {
+    pub account_item_id: Uuid,
+    pub account_item_name: String,
+    pub month: NaiveDate,
+    /// Projectの予算（InitialPlan または MasterPlan）
+    pub budget: Decimal,
+    /// 配下のJobのJobPlanの合計
+    pub consumed: Decimal,
+    /// budget - consumed
+    pub remaining: Decimal,
+}
+
+/// Projectの予算と配下のJobPlanを勘定科目・月で突き合わせて予算残を算出する
+pub fn build_consumption(budget: &[MonthlyAmount], consumed: &[MonthlyAmount]) -> Vec<BudgetLine> {
+    let mut lines: BTreeMap<(i32, Uuid, NaiveDate), BudgetLine> = BTreeMap::new();
+
+    for row in budget {
+        budget_line(&mut lines, row).budget += row.amount;
+    }
+    for row in consumed {
+        budget_line(&mut lines, row).consumed += row.amount;
+    }
+
+    lines
+        .into_values()
+        .map(|mut line| {
+            line.remaining = line.budget - line.consumed;
+            line
+        })
+        .collect()
+}
+
+/// 予算残を ExecPlanAdjust の明細に変換する（差額が 0 の明細は作らない）
+///
+/// ExecPlanAdjust = Projectの計画 - 配下のJobのJobPlanの合計 となり、
+/// JobPlanと合算するとProjectの計画に一致する
+pub fn exec_plan_adjustments(lines: &[BudgetLine]) -> Vec<UpsertPlEntryParam> {
+    lines
+        .iter()
+        .filter(|line| !line.remaining.is_zero())
+        .map(|line| UpsertPlEntryParam {
+            account_item_id: line.account_item_id,
+            date: line.month,
+            amount: line.remaining,
+            currency: None,
+            description: None,
+        })
+        .collect()
+}
+
+/// JobのJobPlanを書き込んだ場合に予算残を超える明細を求める
+///
+/// `consumed` は配下の全JobのJobPlan、`current` は書き込み対象Jobの登録済みJobPlan。
+/// 書き込む勘定科目・月について、他のJobの消化額に新しい金額を足して予算と比較する
+pub fn find_overruns(
+    budget: &[MonthlyAmount],
+    consumed: &[MonthlyAmount],
+    current: &[MonthlyAmount],
+    entries: &[UpsertPlEntryParam],
+    items: &[AccountItem],
+) -> Vec<BudgetLine> {
+    let sum = |rows: &[MonthlyAmount]| {
+        let mut amounts: HashMap<(Uuid, NaiveDate), Decimal> = HashMap::new();
+        for row in rows {
+            *amounts.entry((row.account_item_id, row.month)).or_default() += row.amount;
+        }
+        amounts
+    };
+    let budget = sum(budget);
+    let consumed = sum(consumed);
+    let current = sum(current);
+    let items: HashMap<Uuid, &AccountItem> = items.iter().map(|item| (item.id, item)).collect();
+
+    let mut overruns: BTreeMap<(i32, Uuid, NaiveDate), BudgetLine> = BTreeMap::new();
+    for entry in entries {
+        let key = (entry.account_item_id, entry.date);
+        let budget = budget.get(&key).copied().unwrap_or_default();
+        let consumed = consumed.get(&key).copied().unwrap_or_default()
+            - current.get(&key).copied().unwrap_or_default()
+            + entry.amount;
+
+        if consumed <= budget {
+            continue;
+        }
+
+        let (name, display_order) = items
+            .get(&entry.account_item_id)
+            .map(|item| (item.name.clone(), item.display_order))
+            .unwrap_or_default();
+        overruns.insert(
+            (display_order, entry.account_item_id, entry.date),
+            BudgetLine {
+                account_item_id: entry.account_item_id,
+                account_item_name: name,
+                month: entry.date,
+                budget,
+                consumed,
+                remaining: budget - consumed,
+            },
+        );
+    }
+
+    overruns.into_values().collect()
+}
+
+fn budget_line<'a>(
+    lines: &'a mut BTreeMap<(i32, Uuid, NaiveDate), BudgetLine>,
+    row: &MonthlyAmount,
+) -> &'a mut BudgetLine {
+    lines
+        .entry((row.display_order, row.account_item_id, row.month))
+        .or_insert_with(|| BudgetLine {
+            account_item_id: row.account_item_id,
+            account_item_name: row.account_item_name.clone(),
+            month: row.month,
+            budget: Decimal::ZERO,
+            consumed: Decimal::ZERO,
+            remaining: Decimal::ZERO,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::domains::account_item::AccountType;
+
+    fn item(name: &str, display_order: i32) -> AccountItem {
+        AccountItem {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            account_type: AccountType::SellingGeneralAdmin,
+            parent_id: None,
+            display_order,
+            description: None,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn month(m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, m, 1).unwrap()
+    }
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn amount(item: &AccountItem, m: u32, amount: &str) -> MonthlyAmount {
+        MonthlyAmount {
+            account_item_id: item.id,
+            account_item_name: item.name.clone(),
+            display_order: item.display_order,
+            month: month(m),
+            amount: dec(amount),
+        }
+    }
+
+    fn entry(item: &AccountItem, m: u32, amount: &str) -> UpsertPlEntryParam {
+        UpsertPlEntryParam {
+            account_item_id: item.id,
+            date: month(m),
+            amount: dec(amount),
+            currency: None,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn parses_budget_policies() {
+        assert_eq!("warn".parse::<BudgetPolicy>().unwrap(), BudgetPolicy::Warn);
+        assert_eq!(
+            "Reject".parse::<BudgetPolicy>().unwrap(),
+            BudgetPolicy::Reject
+        );
+        assert!(matches!(
+            "ignore".parse::<BudgetPolicy>(),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn consumption_compares_budget_with_job_plans() {
+        let travel = item("旅費", 1);
+        let outsourcing = item("外注費", 2);
+
+        let lines = build_consumption(
+            &[amount(&outsourcing, 4, "100"), amount(&travel, 4, "50")],
+            &[
+                amount(&travel, 4, "20"),
+                amount(&travel, 4, "10"),
+                amount(&travel, 5, "5"),
+            ],
+        );
+
+        let found: Vec<(Uuid, NaiveDate, Decimal, Decimal, Decimal)> = lines
+            .iter()
+            .map(|l| {
+                (
+                    l.account_item_id,
+                    l.month,
+                    l.budget,
+                    l.consumed,
+                    l.remaining,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (travel.id, month(4), dec("50"), dec("30"), dec("20")),
+                (travel.id, month(5), dec("0"), dec("5"), dec("-5")),
+                (outsourcing.id, month(4), dec("100"), dec("0"), dec("100")),
+            ]
+        );
+    }
+
+    #[test]
+    fn adjustments_skip_fully_consumed_lines() {
+        let travel = item("旅費", 1);
+
+        let adjustments = exec_plan_adjustments(&build_consumption(
+            &[amount(&travel, 4, "50"), amount(&travel, 5, "50")],
+            &[amount(&travel, 4, "50"), amount(&travel, 5, "80")],
+        ));
+
+        assert_eq!(adjustments.len(), 1);
+        assert_eq!(adjustments[0].date, month(5));
+        assert_eq!(adjustments[0].amount, dec("-30"));
+    }
+
+    #[test]
+    fn overruns_replace_the_jobs_current_plan() {
+        let travel = item("旅費", 1);
+        let outsourcing = item("外注費", 2);
+        let items = [travel.clone(), outsourcing.clone()];
+        let budget = [amount(&travel, 4, "100"), amount(&outsourcing, 4, "100")];
+        // 配下の全Jobで 90、そのうち書き込み対象のJobが 40
+        let consumed = [amount(&travel, 4, "90")];
+        let current = [amount(&travel, 4, "40")];
+
+        let within = find_overruns(
+            &budget,
+            &consumed,
+            &current,
+            &[entry(&travel, 4, "50")],
+            &items,
+        );
+        assert!(within.is_empty());
+
+        let overruns = find_overruns(
+            &budget,
+            &consumed,
+            &current,
+            &[
+                entry(&outsourcing, 4, "120"),
+                entry(&travel, 4, "60"),
+                entry(&travel, 5, "1"),
+            ],
+            &items,
+        );
+
+        let found: Vec<(Uuid, NaiveDate, Decimal, Decimal, Decimal)> = overruns
+            .iter()
+            .map(|l| {
+                (
+                    l.account_item_id,
+                    l.month,
+                    l.budget,
+                    l.consumed,
+                    l.remaining,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (travel.id, month(4), dec("100"), dec("110"), dec("-10")),
+                (travel.id, month(5), dec("0"), dec("1"), dec("-1")),
+                (outsourcing.id, month(4), dec("100"), dec("120"), dec("-20")),
+            ]
+        );
+        assert_eq!(overruns[0].account_item_name, "旅費");
+    }
+}
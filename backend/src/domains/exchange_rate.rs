@@ -0,0 +1,90 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Type, prelude::FromRow};
+use uuid::Uuid;
+
+use crate::{domains::pl_entry::Scenario, error::AppError};
+
+/// 為替レートの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Type)]
+#[sqlx(type_name = "exchange_rate_type", rename_all = "PascalCase")]
+pub enum RateType {
+    Budget, // 予算レート（計画シナリオの換算に使う）
+    Actual, // 月次の実績レート
+}
+
+/// 為替レート（1通貨単位あたりの報告通貨額）
+///
+/// 指定した月から、同じ種類・通貨の次のレートの月の前月まで適用する
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ExchangeRate {
+    pub id: Uuid,
+    pub rate_type: RateType,
+    pub currency: String,
+    pub month: NaiveDate,
+    pub rate: Decimal,
+
+    pub created_by: Uuid,
+    pub updated_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// シナリオの換算に使うレートの種類
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ScenarioRateType {
+    pub scenario: Scenario,
+    pub rate_type: RateType,
+
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UpsertExchangeRateParam {
+    pub rate_type: RateType,
+    pub currency: String,
+    pub month: NaiveDate,
+    pub rate: Decimal,
+    pub user_id: Uuid,
+}
+
+/// ISO 4217 形式（英字3文字）の通貨コードを大文字に揃える
+pub fn normalize_currency(code: &str) -> Result<String, AppError> {
+    let code = code.trim();
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(AppError::Validation(format!(
+            "Invalid currency code: {}",
+            code
+        )));
+    }
+
+    Ok(code.to_ascii_uppercase())
+}
+
+#[async_trait::async_trait]
+pub trait ExchangeRateRepository: Send + Sync {
+    async fn find_all(
+        &self,
+        rate_type: Option<RateType>,
+        currency: Option<String>,
+    ) -> Result<Vec<ExchangeRate>, AppError>;
+    /// 同じ種類・通貨・月のレートがあれば置き換える
+    async fn upsert(&self, params: UpsertExchangeRateParam) -> Result<ExchangeRate, AppError>;
+    async fn delete(&self, id: Uuid) -> Result<(), AppError>;
+    /// シナリオの明細を報告通貨に換算するレート（報告通貨なら 1）
+    async fn find_rate(
+        &self,
+        scenario: Scenario,
+        currency: &str,
+        date: NaiveDate,
+    ) -> Result<Option<Decimal>, AppError>;
+    async fn find_scenario_rate_types(&self) -> Result<Vec<ScenarioRateType>, AppError>;
+    async fn update_scenario_rate_type(
+        &self,
+        scenario: Scenario,
+        rate_type: RateType,
+        user_id: Uuid,
+    ) -> Result<ScenarioRateType, AppError>;
+}
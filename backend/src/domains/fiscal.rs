@@ -0,0 +1,539 @@
+use std::{fmt, ops::RangeInclusive, str::FromStr};
+
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Type, prelude::FromRow};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// 会計期間のステータス
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Type)]
+#[sqlx(type_name = "period_status", rename_all = "PascalCase")]
+pub enum PeriodStatus {
+    Open,
+    Closed,
+}
+
+/// 月次期間を扱える年度の範囲
+pub const FISCAL_YEARS: RangeInclusive<i32> = 2000..=2999;
+
+/// 会計カレンダー（期首月を設定可能）
+#[derive(Debug, Clone, Copy)]
+pub struct FiscalCalendar {
+    start_month: u32,
+}
+
+/// 日付の範囲（両端を含む）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PeriodRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+/// 月次の会計期間
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FiscalMonth {
+    pub fiscal_year: i32,
+    /// 期首月を1とした月番号 (1-12)
+    pub period: u32,
+    pub quarter: u32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+impl FiscalCalendar {
+    pub fn new(start_month: u32) -> Result<Self, AppError> {
+        if !(1..=12).contains(&start_month) {
+            return Err(AppError::Validation(format!(
+                "Invalid fiscal year start month: {}",
+                start_month
+            )));
+        }
+        Ok(Self { start_month })
+    }
+
+    /// 日付が属する年度（期首月の暦年で表す。4月始まりなら2026/4〜2027/3がFY2026）
+    pub fn fiscal_year(&self, date: NaiveDate) -> i32 {
+        if date.month() >= self.start_month {
+            date.year()
+        } else {
+            date.year() - 1
+        }
+    }
+
+    /// 日付が属する月次の会計期間
+    pub fn month_of(&self, date: NaiveDate) -> FiscalMonth {
+        let fiscal_year = self.fiscal_year(date);
+        let period = (date.month() + 12 - self.start_month) % 12 + 1;
+        FiscalMonth::new(
+            fiscal_year,
+            period,
+            date - Days::new(u64::from(date.day0())),
+        )
+    }
+
+    /// 年度と月番号から月次の会計期間を求める
+    pub fn month(&self, fiscal_year: i32, period: u32) -> Result<FiscalMonth, AppError> {
+        let start_date = self
+            .year_start(fiscal_year)?
+            .checked_add_months(Months::new(period - 1))
+            .ok_or_else(|| out_of_range(fiscal_year))?;
+        Ok(FiscalMonth::new(fiscal_year, period, start_date))
+    }
+
+    /// 年度内の全ての月次期間
+    pub fn months(&self, fiscal_year: i32) -> Result<Vec<FiscalMonth>, AppError> {
+        (1..=12).map(|p| self.month(fiscal_year, p)).collect()
+    }
+
+    /// 2つの日付を含む年度全体の範囲
+    pub fn years_covering(
+        &self,
+        first: NaiveDate,
+        last: NaiveDate,
+    ) -> Result<PeriodRange, AppError> {
+        Ok(PeriodRange {
+            start: self
+                .range(FiscalPeriodSpec::Year(self.fiscal_year(first)))?
+                .start,
+            end: self
+                .range(FiscalPeriodSpec::Year(self.fiscal_year(last)))?
+                .end,
+        })
+    }
+
+    /// 帳票の対象範囲。指定がなければ明細の日付を含む年度全体（明細がなければ今年度）とする
+    pub fn report_range(
+        &self,
+        range: Option<PeriodRange>,
+        dates: impl Iterator<Item = NaiveDate> + Clone,
+    ) -> Result<PeriodRange, AppError> {
+        match range {
+            Some(range) => Ok(range),
+            None => {
+                let today = Utc::now().date_naive();
+                let first = dates.clone().min().unwrap_or(today);
+                let last = dates.max().unwrap_or(today);
+                self.years_covering(first, last)
+            }
+        }
+    }
+
+    /// 範囲に含まれる月次期間の初日の一覧
+    pub fn month_starts(&self, range: PeriodRange) -> Vec<NaiveDate> {
+        let mut months = Vec::new();
+        let mut month = Some(self.normalize(range.start));
+        while let Some(start) = month.filter(|m| *m <= range.end) {
+            months.push(start);
+            month = start.checked_add_months(Months::new(1));
+        }
+        months
+    }
+
+    /// PL明細の日付を月次期間の初日に正規化する
+    pub fn normalize(&self, date: NaiveDate) -> NaiveDate {
+        self.month_of(date).start_date
+    }
+
+    /// 期間指定を日付の範囲に変換する
+    pub fn range(&self, spec: FiscalPeriodSpec) -> Result<PeriodRange, AppError> {
+        let (first, last) = match spec {
+            FiscalPeriodSpec::Year(fy) => (self.month(fy, 1)?, self.month(fy, 12)?),
+            FiscalPeriodSpec::Quarter(fy, q) => {
+                (self.month(fy, (q - 1) * 3 + 1)?, self.month(fy, q * 3)?)
+            }
+            FiscalPeriodSpec::Month(fy, p) => (self.month(fy, p)?, self.month(fy, p)?),
+        };
+        Ok(PeriodRange {
+            start: first.start_date,
+            end: last.end_date,
+        })
+    }
+
+    fn year_start(&self, fiscal_year: i32) -> Result<NaiveDate, AppError> {
+        NaiveDate::from_ymd_opt(fiscal_year, self.start_month, 1)
+            .ok_or_else(|| out_of_range(fiscal_year))
+    }
+}
+
+impl FiscalMonth {
+    fn new(fiscal_year: i32, period: u32, start_date: NaiveDate) -> Self {
+        FiscalMonth {
+            fiscal_year,
+            period,
+            quarter: (period - 1) / 3 + 1,
+            start_date,
+            end_date: start_date + Days::new(u64::from(start_date.num_days_in_month()) - 1),
+        }
+    }
+}
+
+fn out_of_range(fiscal_year: i32) -> AppError {
+    AppError::BadRequest(format!("Fiscal year {} is out of range", fiscal_year))
+}
+
+/// "FY2026", "FY2026 Q2", "FY2026 M05" 形式の期間指定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum FiscalPeriodSpec {
+    Year(i32),
+    Quarter(i32, u32),
+    Month(i32, u32),
+}
+
+impl FromStr for FiscalPeriodSpec {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || AppError::BadRequest(format!("Invalid fiscal period: {}", s));
+
+        let normalized: String = s
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+            .collect::<String>()
+            .to_uppercase();
+        let rest = normalized.strip_prefix("FY").ok_or_else(invalid)?;
+
+        let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits != 4 {
+            return Err(invalid());
+        }
+        let (year, suffix) = rest.split_at(digits);
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+
+        if suffix.is_empty() {
+            return Ok(FiscalPeriodSpec::Year(year));
+        }
+
+        let (kind, number) = suffix.split_at(1);
+        let number: u32 = number.parse().map_err(|_| invalid())?;
+        match kind {
+            "Q" if (1..=4).contains(&number) => Ok(FiscalPeriodSpec::Quarter(year, number)),
+            "M" if (1..=12).contains(&number) => Ok(FiscalPeriodSpec::Month(year, number)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl TryFrom<String> for FiscalPeriodSpec {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for FiscalPeriodSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FiscalPeriodSpec::Year(fy) => write!(f, "FY{}", fy),
+            FiscalPeriodSpec::Quarter(fy, q) => write!(f, "FY{} Q{}", fy, q),
+            FiscalPeriodSpec::Month(fy, p) => write!(f, "FY{} M{:02}", fy, p),
+        }
+    }
+}
+
+/// 登録済みの会計期間（月次）
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct FiscalPeriod {
+    pub fiscal_year: i32,
+    pub period: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub status: PeriodStatus,
+    pub closed_by: Option<Uuid>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 月次期間と締め状況
+///
+/// 月次期間は締めるときに登録するため、未登録の月は締めたことのない Open の期間として扱う
+#[derive(Debug, Clone, Serialize)]
+pub struct FiscalMonthStatus {
+    #[serde(flatten)]
+    pub month: FiscalMonth,
+    pub status: PeriodStatus,
+    pub closed_by: Option<Uuid>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl FiscalMonthStatus {
+    pub fn new(month: FiscalMonth, registered: Option<&FiscalPeriod>) -> Self {
+        match registered {
+            Some(period) => FiscalMonthStatus {
+                month,
+                status: period.status,
+                closed_by: period.closed_by,
+                closed_at: period.closed_at,
+            },
+            None => FiscalMonthStatus {
+                month,
+                status: PeriodStatus::Open,
+                closed_by: None,
+                closed_at: None,
+            },
+        }
+    }
+}
+
+/// 月次締め・締め解除の履歴
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct FiscalPeriodEvent {
+    pub id: Uuid,
+    pub fiscal_year: i32,
+    pub period: i32,
+    /// 操作後のステータス
+    pub status: PeriodStatus,
+    pub reason: Option<String>,
+    pub acted_by: Uuid,
+    pub acted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChangePeriodStatusParam {
+    pub month: FiscalMonth,
+    pub status: PeriodStatus,
+    pub reason: Option<String>,
+    pub acted_by: Uuid,
+}
+
+#[async_trait::async_trait]
+pub trait FiscalPeriodRepository: Send + Sync {
+    /// 年度の月次期間を未登録のものだけ登録する
+    async fn ensure_months(&self, months: Vec<FiscalMonth>) -> Result<(), AppError>;
+    async fn find_by_year(&self, fiscal_year: i32) -> Result<Vec<FiscalPeriod>, AppError>;
+    async fn find_by_period(
+        &self,
+        fiscal_year: i32,
+        period: i32,
+    ) -> Result<Option<FiscalPeriod>, AppError>;
+    async fn find_events(
+        &self,
+        fiscal_year: i32,
+        period: i32,
+    ) -> Result<Vec<FiscalPeriodEvent>, AppError>;
+    /// 月次締め・締め解除を行い、履歴を残す（月次期間が未登録であれば先に登録する）
+    async fn change_status(
+        &self,
+        params: ChangePeriodStatusParam,
+    ) -> Result<FiscalPeriod, AppError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn april() -> FiscalCalendar {
+        FiscalCalendar::new(4).unwrap()
+    }
+
+    #[test]
+    fn rejects_invalid_start_months() {
+        assert!(FiscalCalendar::new(0).is_err());
+        assert!(FiscalCalendar::new(13).is_err());
+        assert!(FiscalCalendar::new(1).is_ok());
+        assert!(FiscalCalendar::new(12).is_ok());
+    }
+
+    #[test]
+    fn fiscal_year_turns_over_at_the_start_month() {
+        let calendar = april();
+
+        assert_eq!(calendar.fiscal_year(date(2026, 3, 31)), 2025);
+        assert_eq!(calendar.fiscal_year(date(2026, 4, 1)), 2026);
+        assert_eq!(calendar.fiscal_year(date(2026, 12, 31)), 2026);
+        assert_eq!(calendar.fiscal_year(date(2027, 1, 1)), 2026);
+        assert_eq!(calendar.fiscal_year(date(2027, 3, 31)), 2026);
+
+        let january = FiscalCalendar::new(1).unwrap();
+        assert_eq!(january.fiscal_year(date(2026, 1, 1)), 2026);
+        assert_eq!(january.fiscal_year(date(2025, 12, 31)), 2025);
+    }
+
+    #[test]
+    fn month_of_numbers_periods_from_the_start_month() {
+        let calendar = april();
+
+        let first = calendar.month_of(date(2026, 4, 15));
+        assert_eq!(
+            (first.fiscal_year, first.period, first.quarter),
+            (2026, 1, 1)
+        );
+
+        let january = calendar.month_of(date(2027, 1, 20));
+        assert_eq!(january.fiscal_year, 2026);
+        assert_eq!(january.period, 10);
+        assert_eq!(january.quarter, 4);
+        assert_eq!(january.start_date, date(2027, 1, 1));
+        assert_eq!(january.end_date, date(2027, 1, 31));
+
+        let last = calendar.month_of(date(2027, 3, 31));
+        assert_eq!((last.fiscal_year, last.period, last.quarter), (2026, 12, 4));
+    }
+
+    #[test]
+    fn month_handles_calendar_year_and_leap_year_boundaries() {
+        let calendar = april();
+
+        let december = calendar.month(2026, 9).unwrap();
+        assert_eq!(december.start_date, date(2026, 12, 1));
+        assert_eq!(december.end_date, date(2026, 12, 31));
+
+        let february = calendar.month(2026, 11).unwrap();
+        assert_eq!(february.start_date, date(2027, 2, 1));
+        assert_eq!(february.end_date, date(2027, 2, 28));
+
+        let leap_february = calendar.month(2027, 11).unwrap();
+        assert_eq!(leap_february.end_date, date(2028, 2, 29));
+
+        assert_eq!(calendar.month_of(date(2027, 2, 14)), february);
+    }
+
+    #[test]
+    fn months_cover_the_fiscal_year_without_gaps() {
+        let months = april().months(2026).unwrap();
+
+        assert_eq!(months.len(), 12);
+        assert_eq!(months[0].start_date, date(2026, 4, 1));
+        assert_eq!(months[11].end_date, date(2027, 3, 31));
+        for pair in months.windows(2) {
+            assert_eq!(pair[0].end_date + Days::new(1), pair[1].start_date);
+        }
+    }
+
+    #[test]
+    fn range_converts_year_quarter_and_month_specs() {
+        let calendar = april();
+
+        assert_eq!(
+            calendar.range(FiscalPeriodSpec::Year(2026)).unwrap(),
+            PeriodRange {
+                start: date(2026, 4, 1),
+                end: date(2027, 3, 31),
+            }
+        );
+        assert_eq!(
+            calendar.range(FiscalPeriodSpec::Quarter(2026, 4)).unwrap(),
+            PeriodRange {
+                start: date(2027, 1, 1),
+                end: date(2027, 3, 31),
+            }
+        );
+        assert_eq!(
+            calendar.range(FiscalPeriodSpec::Month(2026, 11)).unwrap(),
+            PeriodRange {
+                start: date(2027, 2, 1),
+                end: date(2027, 2, 28),
+            }
+        );
+    }
+
+    #[test]
+    fn years_covering_spans_whole_fiscal_years() {
+        let range = april()
+            .years_covering(date(2026, 2, 10), date(2026, 5, 20))
+            .unwrap();
+
+        assert_eq!(range.start, date(2025, 4, 1));
+        assert_eq!(range.end, date(2027, 3, 31));
+    }
+
+    #[test]
+    fn month_starts_normalizes_the_first_month() {
+        let calendar = april();
+        let months = calendar.month_starts(PeriodRange {
+            start: date(2026, 11, 15),
+            end: date(2027, 2, 1),
+        });
+
+        assert_eq!(
+            months,
+            vec![
+                date(2026, 11, 1),
+                date(2026, 12, 1),
+                date(2027, 1, 1),
+                date(2027, 2, 1),
+            ]
+        );
+        assert_eq!(calendar.normalize(date(2027, 2, 28)), date(2027, 2, 1));
+    }
+
+    #[test]
+    fn out_of_range_years_are_bad_requests() {
+        let calendar = april();
+
+        assert!(matches!(
+            calendar.month(300_000, 1),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            calendar.range(FiscalPeriodSpec::Year(-300_000)),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn parses_period_specs() {
+        assert_eq!(
+            "FY2026".parse::<FiscalPeriodSpec>().unwrap(),
+            FiscalPeriodSpec::Year(2026)
+        );
+        assert_eq!(
+            "fy 2026 q2".parse::<FiscalPeriodSpec>().unwrap(),
+            FiscalPeriodSpec::Quarter(2026, 2)
+        );
+        assert_eq!(
+            "FY-2026-M05".parse::<FiscalPeriodSpec>().unwrap(),
+            FiscalPeriodSpec::Month(2026, 5)
+        );
+        assert_eq!(
+            "FY2026 M12".parse::<FiscalPeriodSpec>().unwrap(),
+            FiscalPeriodSpec::Month(2026, 12)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_period_specs() {
+        for spec in [
+            "",
+            "2026",
+            "FY",
+            "FY26",
+            "FY20260",
+            "FY2026 Q",
+            "FY2026 Q0",
+            "FY2026 Q5",
+            "FY2026 M0",
+            "FY2026 M13",
+            "FY2026 H1",
+            "FY2026 Qx",
+        ] {
+            assert!(
+                matches!(
+                    spec.parse::<FiscalPeriodSpec>(),
+                    Err(AppError::BadRequest(_))
+                ),
+                "{:?} should be rejected",
+                spec
+            );
+        }
+    }
+
+    #[test]
+    fn display_round_trips_through_the_parser() {
+        for spec in [
+            FiscalPeriodSpec::Year(2026),
+            FiscalPeriodSpec::Quarter(2026, 3),
+            FiscalPeriodSpec::Month(2026, 7),
+        ] {
+            assert_eq!(spec.to_string().parse::<FiscalPeriodSpec>().unwrap(), spec);
+        }
+    }
+}
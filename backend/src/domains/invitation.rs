@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::{
+    domains::user::{CreateUserParam, User, UserRole},
+    error::AppError,
+};
+
+/// ユーザー登録の招待
+///
+/// 招待トークンは招待されたメールアドレスと権限に紐づき、期限内に一度だけ使える
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct Invitation {
+    pub id: Uuid,
+    pub email: String,
+    pub role: String,
+    pub expires_at: DateTime<Utc>,
+
+    pub invited_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub accepted_by: Option<Uuid>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub revoked_by: Option<Uuid>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateInvitationParam {
+    pub email: String,
+    pub role: UserRole,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub invited_by: Uuid,
+}
+
+#[async_trait::async_trait]
+pub trait InvitationRepository: Send + Sync {
+    async fn create(&self, params: CreateInvitationParam) -> Result<Invitation, AppError>;
+    async fn find_all(&self) -> Result<Vec<Invitation>, AppError>;
+    /// 未使用の招待を取り消す
+    async fn revoke(&self, id: Uuid, revoked_by: Uuid) -> Result<Invitation, AppError>;
+    /// 招待トークンでユーザーを登録し、招待を使用済みにする
+    ///
+    /// 権限は招待で指定したものを使い、メールアドレスが招待と異なる場合は登録しない
+    async fn accept(&self, token_hash: &str, params: CreateUserParam) -> Result<User, AppError>;
+}
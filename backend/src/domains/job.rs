@@ -23,6 +23,15 @@ pub struct Job {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Jobの担当者
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct JobAssignee {
+    pub user_id: Uuid,
+    pub name: String,
+    pub assigned_by: Uuid,
+    pub assigned_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone)]
 pub struct CreateJobParam {
     pub service_id: Uuid,
@@ -52,8 +61,18 @@ pub struct UpdateJobParam {
 #[async_trait::async_trait]
 pub trait JobRepository: Send + Sync {
     async fn create(&self, params: CreateJobParam) -> Result<Job, AppError>;
-    async fn find_all(&self) -> Result<Vec<Job>, AppError>;
+    /// member_id を指定すると、そのユーザーが所有者か担当者のJobに絞り込む
+    async fn find_all(&self, member_id: Option<Uuid>) -> Result<Vec<Job>, AppError>;
     async fn find_by_id(&self, id: Uuid) -> Result<Option<Job>, AppError>;
     async fn update(&self, id: Uuid, params: UpdateJobParam) -> Result<Job, AppError>;
     async fn delete(&self, id: Uuid) -> Result<(), AppError>;
+
+    async fn find_assignees(&self, job_id: Uuid) -> Result<Vec<JobAssignee>, AppError>;
+    /// 担当者を置き換える（引き続き担当するユーザーの割り当て日時は変えない）
+    async fn replace_assignees(
+        &self,
+        job_id: Uuid,
+        user_ids: Vec<Uuid>,
+        assigned_by: Uuid,
+    ) -> Result<Vec<JobAssignee>, AppError>;
 }
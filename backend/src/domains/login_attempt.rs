@@ -0,0 +1,74 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Type, prelude::FromRow};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// ログインの結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Type)]
+#[sqlx(type_name = "login_outcome", rename_all = "PascalCase")]
+pub enum LoginOutcome {
+    Succeeded,
+    UnknownEmail,
+    InvalidPassword,
+    Inactive,
+    Locked,
+}
+
+/// ログインの試行履歴
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct LoginEvent {
+    pub id: Uuid,
+    pub email: String,
+    pub user_id: Option<Uuid>,
+    pub outcome: LoginOutcome,
+    pub ip_address: String,
+    pub user_agent: Option<String>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateLoginEventParam {
+    pub email: String,
+    pub user_id: Option<Uuid>,
+    pub outcome: LoginOutcome,
+    pub ip_address: String,
+    pub user_agent: Option<String>,
+}
+
+/// 試行履歴の絞り込み条件
+#[derive(Debug, Clone)]
+pub struct LoginEventFilter {
+    pub user_id: Option<Uuid>,
+    pub email: Option<String>,
+    pub outcome: Option<LoginOutcome>,
+    pub limit: i64,
+}
+
+/// アカウントロックの設定
+///
+/// lockout の間に max_failures 回続けて失敗すると、lockout の間ログインできなくなる
+#[derive(Debug, Clone, Copy)]
+pub struct LockoutPolicy {
+    pub max_failures: i32,
+    pub lockout: Duration,
+}
+
+#[async_trait::async_trait]
+pub trait LoginAttemptRepository: Send + Sync {
+    async fn record_event(&self, params: CreateLoginEventParam) -> Result<(), AppError>;
+    async fn find_events(&self, filter: LoginEventFilter) -> Result<Vec<LoginEvent>, AppError>;
+
+    /// ロック中であればロックの解除日時を返す
+    async fn locked_until(&self, email: &str) -> Result<Option<DateTime<Utc>>, AppError>;
+    /// 失敗を数え、この失敗でロックした場合はロックの解除日時を返す
+    async fn record_failure(
+        &self,
+        email: &str,
+        policy: LockoutPolicy,
+    ) -> Result<Option<DateTime<Utc>>, AppError>;
+    /// 失敗回数とロックを解除する
+    async fn clear_failures(&self, email: &str) -> Result<(), AppError>;
+}
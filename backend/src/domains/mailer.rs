@@ -0,0 +1,37 @@
+use std::str::FromStr;
+
+use crate::error::AppError;
+
+/// メールの送信方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MailerKind {
+    /// SMTPサーバーから送信する
+    Smtp,
+    /// 送信せずに宛先と件名をログに、本文をファイルに書き出す（ローカル開発・テスト用）
+    File,
+}
+
+impl FromStr for MailerKind {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "smtp" => Ok(MailerKind::Smtp),
+            "file" => Ok(MailerKind::File),
+            _ => Err(AppError::Validation(format!("Invalid mailer: {}", s))),
+        }
+    }
+}
+
+/// 送信するメール（本文はプレーンテキスト）
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait::async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: Mail) -> Result<(), AppError>;
+}
@@ -1,8 +1,25 @@
 pub mod account_item;
+pub mod allocation;
+pub mod budget;
+pub mod exchange_rate;
+pub mod fiscal;
+pub mod invitation;
 pub mod job;
+pub mod login_attempt;
+pub mod mailer;
+pub mod password_reset;
+pub mod permission;
+pub mod pl_csv;
 pub mod pl_entry;
+pub mod pl_sandbox;
+pub mod pl_snapshot;
+pub mod pl_statement;
+pub mod pl_xlsx;
 pub mod project;
+pub mod report;
+pub mod scenario_lock;
 pub mod segment;
 pub mod service;
+pub mod session;
 pub mod theme;
 pub mod user;
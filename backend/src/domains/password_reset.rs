@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+#[async_trait::async_trait]
+pub trait PasswordResetRepository: Send + Sync {
+    /// 再設定トークンを登録する（同じユーザーの未使用のトークンは無効にする）
+    async fn create(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AppError>;
+    /// トークンを使用済みにしてパスワードを更新し、対象のユーザーIDを返す
+    async fn consume(&self, token_hash: &str, password_hash: &str) -> Result<Uuid, AppError>;
+}
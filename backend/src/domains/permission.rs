@@ -0,0 +1,248 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::domains::user::UserRole;
+
+/// 権限の対象となるリソース
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Resource {
+    Theme,
+    Project,
+    Job,
+    Segment,
+    Service,
+    AccountItem,
+    PlEntry,
+    Sandbox,
+    Report,
+    ScenarioLock,
+    FiscalPeriod,
+    ExchangeRate,
+    Allocation,
+    User,
+    /// ログインの試行履歴
+    LoginEvent,
+}
+
+/// リソースに対する操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Action {
+    Read,
+    Create,
+    Update,
+    Delete,
+    /// 計画のロック・月次締め
+    Lock,
+    /// ロック解除・締め解除
+    Unlock,
+    /// 配賦の実行・取り消し
+    Run,
+}
+
+/// 操作するユーザーとリソースの関係
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ownership {
+    Owner,
+    Assignee,
+    Other,
+}
+
+impl Ownership {
+    pub fn of(user_id: Uuid, owner_id: Option<Uuid>, assignee_ids: &[Uuid]) -> Self {
+        if owner_id == Some(user_id) {
+            Ownership::Owner
+        } else if assignee_ids.contains(&user_id) {
+            Ownership::Assignee
+        } else {
+            Ownership::Other
+        }
+    }
+}
+
+/// 操作に必要な最低限の権限（権限は General < Manager < Admin の順に強い）
+pub fn required_role(resource: Resource, action: Action) -> UserRole {
+    use Action::*;
+    use Resource::*;
+
+    match (resource, action) {
+        // ユーザー・招待・ログイン履歴は参照も含めて管理者のみ（汎用の参照より先に判定する）
+        (User | LoginEvent, _) => UserRole::Admin,
+
+        (_, Read) => UserRole::General,
+
+        (Theme | Project | Job, Create) => UserRole::General,
+        (Theme, Update) => UserRole::General,
+        (Sandbox, Create | Update | Delete) => UserRole::General,
+
+        (Theme, Delete) => UserRole::Manager,
+        (Project | Job | Service | PlEntry, Update | Delete) => UserRole::Manager,
+        (Segment | Service, Create) => UserRole::Manager,
+        (Segment, Update) => UserRole::Manager,
+        (ScenarioLock | FiscalPeriod, Lock) => UserRole::Manager,
+
+        (Segment, Delete) => UserRole::Admin,
+        (ScenarioLock | FiscalPeriod, Unlock) => UserRole::Admin,
+        (AccountItem | ExchangeRate | Allocation, _) => UserRole::Admin,
+
+        // 表にない操作は管理者のみ許可する
+        _ => UserRole::Admin,
+    }
+}
+
+/// 権限が足りなくても、リソースの所有者・担当者であれば許可する操作
+///
+/// PL明細は Project単位なら Project の、Job単位なら Job の所有者・担当者を見る
+pub fn allowed_by_ownership(resource: Resource, action: Action, ownership: Ownership) -> bool {
+    use Action::*;
+    use Resource::*;
+
+    matches!(
+        (resource, action, ownership),
+        (Project | Job | Service, Update | Delete, Ownership::Owner)
+            | (Job, Update, Ownership::Assignee)
+            | (PlEntry, Update, Ownership::Owner | Ownership::Assignee)
+    )
+}
+
+impl UserRole {
+    fn rank(&self) -> u8 {
+        match self {
+            UserRole::General => 0,
+            UserRole::Manager => 1,
+            UserRole::Admin => 2,
+        }
+    }
+
+    /// リソースに対する操作が許可されているかどうか
+    pub fn can(&self, resource: Resource, action: Action) -> bool {
+        self.rank() >= required_role(resource, action).rank()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn general_users_can_read_plans_but_not_users() {
+        let general = UserRole::General;
+
+        assert!(general.can(Resource::Project, Action::Read));
+        assert!(general.can(Resource::PlEntry, Action::Read));
+        assert!(general.can(Resource::AccountItem, Action::Read));
+        assert!(!general.can(Resource::User, Action::Read));
+        assert!(!general.can(Resource::LoginEvent, Action::Read));
+    }
+
+    #[test]
+    fn managers_cannot_manage_users() {
+        let manager = UserRole::Manager;
+
+        assert!(!manager.can(Resource::User, Action::Read));
+        assert!(!manager.can(Resource::User, Action::Create));
+        assert!(!manager.can(Resource::User, Action::Update));
+        assert!(!manager.can(Resource::LoginEvent, Action::Read));
+    }
+
+    #[test]
+    fn managers_lock_and_admins_unlock() {
+        assert!(UserRole::Manager.can(Resource::ScenarioLock, Action::Lock));
+        assert!(UserRole::Manager.can(Resource::FiscalPeriod, Action::Lock));
+        assert!(!UserRole::Manager.can(Resource::ScenarioLock, Action::Unlock));
+        assert!(!UserRole::Manager.can(Resource::FiscalPeriod, Action::Unlock));
+        assert!(!UserRole::General.can(Resource::ScenarioLock, Action::Lock));
+        assert!(UserRole::Admin.can(Resource::ScenarioLock, Action::Unlock));
+    }
+
+    #[test]
+    fn role_hierarchy_applies_to_writes() {
+        assert!(UserRole::General.can(Resource::Project, Action::Create));
+        assert!(!UserRole::General.can(Resource::Project, Action::Update));
+        assert!(UserRole::Manager.can(Resource::Project, Action::Update));
+        assert!(!UserRole::Manager.can(Resource::Segment, Action::Delete));
+        assert!(UserRole::Admin.can(Resource::Segment, Action::Delete));
+        assert!(!UserRole::Manager.can(Resource::AccountItem, Action::Create));
+    }
+
+    #[test]
+    fn unlisted_operations_require_admin() {
+        assert_eq!(
+            required_role(Resource::Report, Action::Run),
+            UserRole::Admin
+        );
+        assert_eq!(
+            required_role(Resource::Allocation, Action::Run),
+            UserRole::Admin
+        );
+    }
+
+    #[test]
+    fn every_role_can_do_what_a_weaker_role_can() {
+        let resources = [
+            Resource::Theme,
+            Resource::Project,
+            Resource::Job,
+            Resource::Segment,
+            Resource::Service,
+            Resource::AccountItem,
+            Resource::PlEntry,
+            Resource::Sandbox,
+            Resource::Report,
+            Resource::ScenarioLock,
+            Resource::FiscalPeriod,
+            Resource::ExchangeRate,
+            Resource::Allocation,
+            Resource::User,
+            Resource::LoginEvent,
+        ];
+        let actions = [
+            Action::Read,
+            Action::Create,
+            Action::Update,
+            Action::Delete,
+            Action::Lock,
+            Action::Unlock,
+            Action::Run,
+        ];
+
+        for resource in resources {
+            for action in actions {
+                if UserRole::General.can(resource, action) {
+                    assert!(UserRole::Manager.can(resource, action));
+                }
+                if UserRole::Manager.can(resource, action) {
+                    assert!(UserRole::Admin.can(resource, action));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn owners_and_assignees_can_update_their_resources() {
+        assert!(allowed_by_ownership(
+            Resource::Project,
+            Action::Update,
+            Ownership::Owner
+        ));
+        assert!(allowed_by_ownership(
+            Resource::Job,
+            Action::Update,
+            Ownership::Assignee
+        ));
+        assert!(allowed_by_ownership(
+            Resource::PlEntry,
+            Action::Update,
+            Ownership::Assignee
+        ));
+        assert!(!allowed_by_ownership(
+            Resource::Job,
+            Action::Delete,
+            Ownership::Assignee
+        ));
+        assert!(!allowed_by_ownership(
+            Resource::Project,
+            Action::Update,
+            Ownership::Other
+        ));
+    }
+}
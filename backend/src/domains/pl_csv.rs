@@ -0,0 +1,377 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::domains::{account_item::AccountItem, pl_entry::UpsertPlEntryParam};
+
+const ID_COLUMN: &str = "account_item_id";
+const NAME_COLUMN: &str = "account_item";
+const UTF8_BOM: &str = "\u{feff}";
+
+/// CSV取り込み時のエラー（行番号はヘッダー行を1とする）
+#[derive(Debug, Clone, Serialize)]
+pub struct PlCsvError {
+    pub row: usize,
+    pub column: Option<String>,
+    pub message: String,
+}
+
+impl PlCsvError {
+    fn new(row: usize, column: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            row,
+            column: column.map(str::to_string),
+            message: message.into(),
+        }
+    }
+}
+
+/// 勘定科目ごとに1行、月ごとに1列の横持ちCSVを書き出す
+///
+/// `amounts` は (勘定科目ID, 月初日) ごとの金額
+pub fn write_plan_csv(
+    months: &[NaiveDate],
+    items: &[AccountItem],
+    amounts: &HashMap<(Uuid, NaiveDate), Decimal>,
+) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    let mut header = vec![ID_COLUMN.to_string(), NAME_COLUMN.to_string()];
+    header.extend(months.iter().map(|m| m.format("%Y-%m").to_string()));
+    writer.write_record(&header)?;
+
+    for item in items {
+        let mut record = vec![item.id.to_string(), item.name.clone()];
+        record.extend(months.iter().map(|m| {
+            amounts
+                .get(&(item.id, *m))
+                .map(|a| a.normalize().to_string())
+                .unwrap_or_default()
+        }));
+        writer.write_record(&record)?;
+    }
+
+    let body = String::from_utf8(writer.into_inner()?)?;
+
+    // Excelで文字化けしないようBOMを付ける
+    Ok(format!("{}{}", UTF8_BOM, body))
+}
+
+/// 横持ちCSVを読み込み、PL明細に変換する
+///
+/// 勘定科目はID列を優先し、空ならば科目名で照合する。
+/// 1件でもエラーがあれば全ての行のエラーをまとめて返す
+pub fn parse_plan_csv(
+    body: &str,
+    items: &[AccountItem],
+) -> Result<Vec<UpsertPlEntryParam>, Vec<PlCsvError>> {
+    let body = body.strip_prefix(UTF8_BOM).unwrap_or(body);
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(body.as_bytes());
+
+    let mut records = reader.records();
+    let header = match records.next() {
+        Some(Ok(header)) => header,
+        Some(Err(e)) => return Err(vec![PlCsvError::new(1, None, e.to_string())]),
+        None => return Err(vec![PlCsvError::new(1, None, "CSV is empty")]),
+    };
+
+    let mut errors = Vec::new();
+
+    if header.get(0) != Some(ID_COLUMN) || header.get(1) != Some(NAME_COLUMN) {
+        errors.push(PlCsvError::new(
+            1,
+            None,
+            format!(
+                "The first two columns must be '{}' and '{}'",
+                ID_COLUMN, NAME_COLUMN
+            ),
+        ));
+    }
+
+    let mut months: Vec<(String, NaiveDate)> = Vec::new();
+    for column in header.iter().skip(2) {
+        match parse_month(column) {
+            Some(month) if months.iter().any(|(_, m)| *m == month) => {
+                errors.push(PlCsvError::new(1, Some(column), "Duplicate month column"))
+            }
+            Some(month) => months.push((column.to_string(), month)),
+            None => errors.push(PlCsvError::new(
+                1,
+                Some(column),
+                "Month column must be in YYYY-MM format",
+            )),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let by_id: HashMap<Uuid, &AccountItem> = items.iter().map(|i| (i.id, i)).collect();
+    let mut by_name: HashMap<&str, Vec<&AccountItem>> = HashMap::new();
+    for item in items {
+        by_name.entry(item.name.as_str()).or_default().push(item);
+    }
+
+    let mut seen_items = HashSet::new();
+    let mut entries = BTreeMap::new();
+
+    for (index, record) in records.enumerate() {
+        let row = index + 2;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(PlCsvError::new(row, None, e.to_string()));
+                continue;
+            }
+        };
+
+        if record.iter().all(|field| field.is_empty()) {
+            continue;
+        }
+
+        if record.len() > months.len() + 2 {
+            errors.push(PlCsvError::new(row, None, "Too many columns"));
+            continue;
+        }
+
+        let id = record.get(0).unwrap_or_default();
+        let name = record.get(1).unwrap_or_default();
+
+        let item = if !id.is_empty() {
+            match Uuid::parse_str(id).ok().and_then(|id| by_id.get(&id)) {
+                Some(item) => *item,
+                None => {
+                    errors.push(PlCsvError::new(
+                        row,
+                        Some(ID_COLUMN),
+                        format!("Unknown account item id '{}'", id),
+                    ));
+                    continue;
+                }
+            }
+        } else {
+            match by_name.get(name).map(Vec::as_slice) {
+                Some([item]) => *item,
+                Some(_) => {
+                    errors.push(PlCsvError::new(
+                        row,
+                        Some(NAME_COLUMN),
+                        format!("Account item name '{}' is ambiguous, use the id", name),
+                    ));
+                    continue;
+                }
+                None => {
+                    errors.push(PlCsvError::new(
+                        row,
+                        Some(NAME_COLUMN),
+                        format!("Unknown account item '{}'", name),
+                    ));
+                    continue;
+                }
+            }
+        };
+
+        if !seen_items.insert(item.id) {
+            errors.push(PlCsvError::new(
+                row,
+                None,
+                format!("Account item '{}' appears more than once", item.name),
+            ));
+            continue;
+        }
+
+        for ((column, month), cell) in months.iter().zip(record.iter().skip(2)) {
+            if cell.is_empty() {
+                continue;
+            }
+            match cell.replace(',', "").parse::<Decimal>() {
+                Ok(amount) => {
+                    entries.insert(
+                        (item.display_order, item.id, *month),
+                        UpsertPlEntryParam {
+                            account_item_id: item.id,
+                            date: *month,
+                            amount,
+                            currency: None,
+                            description: None,
+                        },
+                    );
+                }
+                Err(_) => errors.push(PlCsvError::new(
+                    row,
+                    Some(column),
+                    format!("Invalid amount '{}'", cell),
+                )),
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(entries.into_values().collect())
+}
+
+fn parse_month(column: &str) -> Option<NaiveDate> {
+    let column = column.replace('/', "-");
+    NaiveDate::parse_from_str(&format!("{}-01", column), "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(&column, "%Y-%m-%d"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::domains::account_item::AccountType;
+
+    fn item(name: &str, display_order: i32) -> AccountItem {
+        AccountItem {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            account_type: AccountType::Revenue,
+            parent_id: None,
+            display_order,
+            description: None,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn month(m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, m, 1).unwrap()
+    }
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn errors(body: &str, items: &[AccountItem]) -> Vec<PlCsvError> {
+        parse_plan_csv(body, items).expect_err("CSV should be rejected")
+    }
+
+    #[test]
+    fn parses_rows_by_id_or_name() {
+        let sales = item("売上", 2);
+        let cost = item("原価", 1);
+        let body = format!(
+            "{}account_item_id,account_item,2026-04,2026/05\n\
+             {},,\"1,200\",\n\
+             ,原価, -300 ,50.5\n\
+             ,,,\n",
+            UTF8_BOM, sales.id
+        );
+
+        let entries = parse_plan_csv(&body, &[sales.clone(), cost.clone()]).unwrap();
+
+        let found: Vec<(Uuid, NaiveDate, Decimal)> = entries
+            .iter()
+            .map(|e| (e.account_item_id, e.date, e.amount))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (cost.id, month(4), dec("-300")),
+                (cost.id, month(5), dec("50.5")),
+                (sales.id, month(4), dec("1200")),
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trips_written_csv() {
+        let sales = item("売上", 1);
+        let months = [month(4), month(5)];
+        let amounts = HashMap::from([
+            ((sales.id, month(4)), dec("100.50")),
+            ((sales.id, month(5)), dec("-20")),
+        ]);
+
+        let body = write_plan_csv(&months, std::slice::from_ref(&sales), &amounts).unwrap();
+        let entries = parse_plan_csv(&body, &[sales]).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].amount, dec("100.5"));
+        assert_eq!(entries[1].amount, dec("-20"));
+    }
+
+    #[test]
+    fn rejects_empty_csv() {
+        let errors = errors("", &[]);
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].row, 1);
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        let errors = errors("id,name,2026-04,April,2026-04\n", &[]);
+
+        assert_eq!(errors.len(), 3);
+        assert!(errors.iter().all(|e| e.row == 1));
+        assert_eq!(errors[0].column, None);
+        assert_eq!(errors[1].column.as_deref(), Some("April"));
+        assert_eq!(errors[2].column.as_deref(), Some("2026-04"));
+    }
+
+    #[test]
+    fn rejects_unknown_and_ambiguous_items() {
+        let sales = item("売上", 1);
+        let first = item("雑費", 2);
+        let second = item("雑費", 3);
+        let body = format!(
+            "account_item_id,account_item,2026-04\n\
+             {},,1\n\
+             not-a-uuid,,1\n\
+             ,雑費,1\n\
+             ,交際費,1\n",
+            Uuid::new_v4()
+        );
+
+        let errors = errors(&body, &[sales, first, second]);
+
+        let found: Vec<(usize, Option<&str>)> = errors
+            .iter()
+            .map(|e| (e.row, e.column.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (2, Some(ID_COLUMN)),
+                (3, Some(ID_COLUMN)),
+                (4, Some(NAME_COLUMN)),
+                (5, Some(NAME_COLUMN)),
+            ]
+        );
+    }
+
+    #[test]
+    fn collects_row_errors_across_the_file() {
+        let sales = item("売上", 1);
+        let body = "account_item_id,account_item,2026-04,2026-05\n\
+                    ,売上,abc,1\n\
+                    ,売上,1,2\n\
+                    ,売上,1,2,3\n";
+
+        let errors = errors(body, &[sales]);
+
+        let found: Vec<(usize, Option<&str>)> = errors
+            .iter()
+            .map(|e| (e.row, e.column.as_deref()))
+            .collect();
+        assert_eq!(found, vec![(2, Some("2026-04")), (3, None), (4, None)]);
+        assert!(errors[1].message.contains("more than once"));
+        assert_eq!(errors[2].message, "Too many columns");
+    }
+}
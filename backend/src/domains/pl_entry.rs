@@ -1,12 +1,20 @@
 use chrono::{DateTime, NaiveDate, Utc};
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sqlx::{Type, prelude::FromRow};
 use uuid::Uuid;
 
-use crate::error::AppError;
+use crate::{
+    domains::{
+        account_item::AccountItem,
+        budget::{BudgetLine, BudgetPolicy},
+        fiscal::PeriodRange,
+        report::{MonthlyAmount, PlScope},
+    },
+    error::AppError,
+};
 
-#[derive(Debug, Clone, PartialEq, Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Type)]
 #[sqlx(type_name = "scenario_type", rename_all = "PascalCase")]
 pub enum Scenario {
     MasterPlan,     // 期初計画（設定後は変えられない）
@@ -17,7 +25,44 @@ pub enum Scenario {
     Actual,         // Jobの実績
 }
 
-#[derive(Debug, Clone, FromRow)]
+impl Scenario {
+    pub const ALL: [Scenario; 6] = [
+        Scenario::MasterPlan,
+        Scenario::RevisedPlan,
+        Scenario::InitialPlan,
+        Scenario::ExecPlanAdjust,
+        Scenario::JobPlan,
+        Scenario::Actual,
+    ];
+
+    /// 設定後にロック（確定）できるシナリオかどうか
+    pub fn is_lockable(&self) -> bool {
+        matches!(
+            self,
+            Scenario::MasterPlan | Scenario::RevisedPlan | Scenario::InitialPlan
+        )
+    }
+
+    /// 自動計算されるため直接登録できないシナリオかどうか
+    pub fn is_computed(&self) -> bool {
+        matches!(self, Scenario::ExecPlanAdjust)
+    }
+
+    /// 変更されたときに ExecPlanAdjust の再計算が必要なシナリオかどうか
+    pub fn affects_exec_plan_adjust(&self) -> bool {
+        matches!(
+            self,
+            Scenario::MasterPlan | Scenario::InitialPlan | Scenario::JobPlan
+        )
+    }
+
+    /// Job単位で登録するシナリオかどうか
+    pub fn is_job_level(&self) -> bool {
+        matches!(self, Scenario::JobPlan | Scenario::Actual)
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
 pub struct PlEntry {
     pub id: Uuid,
 
@@ -28,6 +73,31 @@ pub struct PlEntry {
     pub date: NaiveDate,
     pub account_item_id: Uuid,
     pub amount: Decimal,
+    pub currency: String,
+    pub description: Option<String>,
+
+    pub created_by: Uuid,
+    pub updated_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 勘定科目名を結合したPL明細
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PlEntryDetail {
+    pub id: Uuid,
+
+    pub project_id: Uuid,
+    pub job_id: Option<Uuid>,
+
+    pub scenario: Scenario,
+    pub date: NaiveDate,
+    pub account_item_id: Uuid,
+    pub account_item_name: String,
+    pub amount: Decimal,
+    pub currency: String,
+    /// 報告通貨に換算した金額（換算レートが未登録なら None）
+    pub reporting_amount: Option<Decimal>,
     pub description: Option<String>,
 
     pub created_by: Uuid,
@@ -36,14 +106,51 @@ pub struct PlEntry {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct UpsertPlEntryParam {
     pub account_item_id: Uuid,
     pub date: NaiveDate,
     pub amount: Decimal,
+    /// 省略時は報告通貨
+    pub currency: Option<String>,
     pub description: Option<String>,
 }
 
+/// Project単位の明細の保存（保存後の明細を版として記録する）
+#[derive(Debug, Clone)]
+pub struct SaveProjectEntriesParam {
+    pub project_id: Uuid,
+    pub scenario: Scenario,
+    pub entries: Vec<UpsertPlEntryParam>,
+    /// 版に残すコメント
+    pub comment: Option<String>,
+    pub user_id: Uuid,
+}
+
+/// Job単位の明細の保存
+#[derive(Debug, Clone)]
+pub struct SaveJobEntriesParam {
+    /// Jobが紐づくProject
+    pub project_id: Uuid,
+    pub job_id: Uuid,
+    pub scenario: Scenario,
+    pub entries: Vec<UpsertPlEntryParam>,
+    /// 書き込むJobPlanをProjectの予算残と比較する場合に指定する
+    pub budget_check: Option<JobBudgetCheck>,
+    /// 版に残すコメント
+    pub comment: Option<String>,
+    pub user_id: Uuid,
+}
+
+/// JobPlanの予算残との比較に使う内容
+#[derive(Debug, Clone)]
+pub struct JobBudgetCheck {
+    /// 報告通貨に換算した書き込む明細
+    pub converted: Vec<UpsertPlEntryParam>,
+    pub items: Vec<AccountItem>,
+    pub policy: BudgetPolicy,
+}
+
 #[async_trait::async_trait]
 pub trait PlEntryRepository: Send + Sync {
     async fn find_by_project(
@@ -52,11 +159,56 @@ pub trait PlEntryRepository: Send + Sync {
         scenario: Scenario,
     ) -> Result<Vec<PlEntry>, AppError>;
 
-    async fn bulk_upsert(
+    async fn find_details_by_project(
         &self,
         project_id: Uuid,
         scenario: Scenario,
-        entries: Vec<UpsertPlEntryParam>,
-        user_id: Uuid,
-    ) -> Result<(), AppError>;
+        range: Option<PeriodRange>,
+    ) -> Result<Vec<PlEntryDetail>, AppError>;
+
+    async fn find_details_by_job(
+        &self,
+        job_id: Uuid,
+        scenario: Scenario,
+        range: Option<PeriodRange>,
+    ) -> Result<Vec<PlEntryDetail>, AppError>;
+
+    /// 範囲内のPL明細を報告通貨に換算し、勘定科目・月ごとに合計する
+    ///
+    /// 換算レートが未登録の通貨の明細があればエラーとする
+    async fn find_monthly_totals(
+        &self,
+        scope: PlScope,
+        scenario: Scenario,
+        range: Option<PeriodRange>,
+    ) -> Result<Vec<MonthlyAmount>, AppError>;
+
+    /// 締め済みの月は Actual、それ以外の月は最新の計画を勘定科目・月ごとに合計する
+    ///
+    /// 計画は Project ごとに RevisedPlan があればそれを、なければ MasterPlan を使う。
+    /// Job・Service 単位の集計では JobPlan を計画とする
+    async fn find_forecast_totals(
+        &self,
+        scope: PlScope,
+        range: Option<PeriodRange>,
+    ) -> Result<Vec<MonthlyAmount>, AppError>;
+
+    /// Project単位の明細を一括登録・更新し、保存後の明細を版として記録して版番号を返す
+    ///
+    /// ExecPlanAdjust に影響するシナリオであれば、同じトランザクション内で再計算する
+    async fn save_project_entries(&self, params: SaveProjectEntriesParam) -> Result<i32, AppError>;
+
+    /// ExecPlanAdjust をProjectの計画と配下のJobのJobPlanの差額で置き換える
+    async fn sync_exec_plan_adjust(&self, project_id: Uuid, user_id: Uuid) -> Result<(), AppError>;
+
+    /// Job単位の明細を一括登録・更新し、予算残を超えた明細を返す
+    ///
+    /// 予算残との比較はProjectをロックした同じトランザクション内で行い、
+    /// BudgetPolicy::Reject であれば書き込まずにエラーとする。
+    /// 保存後のProject配下のJobの明細を版として記録し、
+    /// ExecPlanAdjust に影響するシナリオであれば、同じトランザクション内で再計算する
+    async fn bulk_upsert_job(
+        &self,
+        params: SaveJobEntriesParam,
+    ) -> Result<Vec<BudgetLine>, AppError>;
 }
@@ -0,0 +1,209 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::{
+    domains::{
+        account_item::{AccountItem, AccountType},
+        fiscal::{FiscalCalendar, PeriodRange},
+        pl_entry::Scenario,
+        report::{MonthlyAmount, PlScope},
+    },
+    error::AppError,
+};
+
+/// 正式なシナリオに影響しない試算用のシナリオ（作成者のみ参照・編集できる）
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PlSandbox {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// コピー元の範囲（Project か Theme のどちらか一方）
+    pub project_id: Option<Uuid>,
+    pub theme_id: Option<Uuid>,
+    pub source_scenario: Scenario,
+
+    pub owner_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PlSandbox {
+    /// コピー元の集計範囲
+    pub fn scope(&self) -> PlScope {
+        match (self.project_id, self.theme_id) {
+            (Some(project_id), _) => PlScope::Project(project_id),
+            (None, theme_id) => PlScope::Theme(theme_id.expect("sandbox has a project or theme")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreatePlSandboxParam {
+    pub name: String,
+    pub description: Option<String>,
+    pub project_id: Option<Uuid>,
+    pub theme_id: Option<Uuid>,
+    pub source_scenario: Scenario,
+    pub owner_id: Uuid,
+    /// コピー元シナリオの勘定科目・月ごとの合計
+    pub entries: Vec<MonthlyAmount>,
+}
+
+/// 試算用シナリオへの一括変換
+///
+/// account_item_ids・account_type を省略した場合は全ての科目が対象になる
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum SandboxOperation {
+    /// 金額を指定した月数だけ後ろにずらす（負の値で前倒し）
+    ShiftMonths {
+        months: i32,
+        account_item_ids: Option<Vec<Uuid>>,
+        account_type: Option<AccountType>,
+    },
+    /// 金額を指定した割合（%）だけ増減させる
+    Scale {
+        percent: Decimal,
+        account_item_ids: Option<Vec<Uuid>>,
+        account_type: Option<AccountType>,
+    },
+    /// 勘定科目・月の金額を指定した値に置き換える
+    Override {
+        account_item_id: Uuid,
+        month: NaiveDate,
+        amount: Decimal,
+    },
+}
+
+/// 変換を順に適用する。同じ勘定科目・月に重なった金額は合算する
+pub fn apply_operations(
+    lines: Vec<MonthlyAmount>,
+    operations: &[SandboxOperation],
+    items: &[AccountItem],
+    calendar: &FiscalCalendar,
+) -> Result<Vec<MonthlyAmount>, AppError> {
+    let items: HashMap<Uuid, &AccountItem> = items.iter().map(|item| (item.id, item)).collect();
+    let is_target =
+        |line: &MonthlyAmount, ids: &Option<Vec<Uuid>>, account_type: &Option<AccountType>| {
+            ids.as_ref()
+                .is_none_or(|ids| ids.contains(&line.account_item_id))
+                && account_type.as_ref().is_none_or(|account_type| {
+                    items
+                        .get(&line.account_item_id)
+                        .is_some_and(|item| item.account_type == *account_type)
+                })
+        };
+
+    let mut lines = lines;
+    for operation in operations {
+        lines = match operation {
+            SandboxOperation::ShiftMonths {
+                months,
+                account_item_ids,
+                account_type,
+            } => lines
+                .into_iter()
+                .map(|mut line| {
+                    if is_target(&line, account_item_ids, account_type) {
+                        line.month = shift_month(line.month, *months)?;
+                    }
+                    Ok(line)
+                })
+                .collect::<Result<_, AppError>>()?,
+            SandboxOperation::Scale {
+                percent,
+                account_item_ids,
+                account_type,
+            } => {
+                if *percent < -Decimal::ONE_HUNDRED {
+                    return Err(AppError::Validation(format!(
+                        "Cannot scale by {}%",
+                        percent
+                    )));
+                }
+                lines
+                    .into_iter()
+                    .map(|mut line| {
+                        if is_target(&line, account_item_ids, account_type) {
+                            line.amount = (line.amount * (Decimal::ONE_HUNDRED + percent)
+                                / Decimal::ONE_HUNDRED)
+                                .round_dp(4);
+                        }
+                        line
+                    })
+                    .collect()
+            }
+            SandboxOperation::Override {
+                account_item_id,
+                month,
+                amount,
+            } => {
+                let item = items
+                    .get(account_item_id)
+                    .ok_or(AppError::Validation(format!(
+                        "Account item {} not found",
+                        account_item_id
+                    )))?;
+                let month = calendar.normalize(*month);
+
+                lines.retain(|line| !(line.account_item_id == item.id && line.month == month));
+                lines.push(MonthlyAmount {
+                    account_item_id: item.id,
+                    account_item_name: item.name.clone(),
+                    display_order: item.display_order,
+                    month,
+                    amount: *amount,
+                });
+                lines
+            }
+        };
+    }
+
+    let mut merged: BTreeMap<(i32, Uuid, NaiveDate), MonthlyAmount> = BTreeMap::new();
+    for line in lines {
+        merged
+            .entry((line.display_order, line.account_item_id, line.month))
+            .and_modify(|merged| merged.amount += line.amount)
+            .or_insert(line);
+    }
+
+    Ok(merged.into_values().collect())
+}
+
+fn shift_month(month: NaiveDate, months: i32) -> Result<NaiveDate, AppError> {
+    let shifted = if months >= 0 {
+        month.checked_add_months(Months::new(months.unsigned_abs()))
+    } else {
+        month.checked_sub_months(Months::new(months.unsigned_abs()))
+    };
+
+    shifted.ok_or(AppError::Validation(format!(
+        "Cannot shift {} by {} months",
+        month, months
+    )))
+}
+
+#[async_trait::async_trait]
+pub trait PlSandboxRepository: Send + Sync {
+    async fn create(&self, params: CreatePlSandboxParam) -> Result<PlSandbox, AppError>;
+    async fn find_by_owner(&self, owner_id: Uuid) -> Result<Vec<PlSandbox>, AppError>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<PlSandbox>, AppError>;
+    /// 範囲内の金額を勘定科目・月ごとに返す
+    async fn find_totals(
+        &self,
+        id: Uuid,
+        range: Option<PeriodRange>,
+    ) -> Result<Vec<MonthlyAmount>, AppError>;
+    /// 金額を指定された内容に置き換える
+    async fn replace_entries(
+        &self,
+        id: Uuid,
+        entries: Vec<MonthlyAmount>,
+    ) -> Result<PlSandbox, AppError>;
+    async fn delete(&self, id: Uuid) -> Result<(), AppError>;
+}
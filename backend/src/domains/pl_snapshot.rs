@@ -0,0 +1,103 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::{
+    domains::{
+        pl_entry::Scenario,
+        report::{MonthlyAmount, VarianceLine, build_variance},
+    },
+    error::AppError,
+};
+
+/// シナリオ保存時点のPL明細の版
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PlSnapshot {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub scenario: Scenario,
+    /// Project・シナリオごとに1から振る版番号
+    pub version: i32,
+    pub comment: Option<String>,
+
+    pub created_by: Uuid,
+    pub created_by_name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 版に含まれるPL明細
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PlSnapshotEntry {
+    /// Job単位のシナリオの版のみ
+    pub job_id: Option<Uuid>,
+    pub account_item_id: Uuid,
+    pub account_item_name: String,
+    #[serde(skip)]
+    pub display_order: i32,
+    pub date: NaiveDate,
+    pub amount: Decimal,
+    pub currency: String,
+    /// 報告通貨に換算した金額（換算レートが未登録なら None）
+    pub reporting_amount: Option<Decimal>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreatePlSnapshotParam {
+    pub project_id: Uuid,
+    pub scenario: Scenario,
+    pub comment: Option<String>,
+    pub created_by: Uuid,
+}
+
+/// 2つの版を報告通貨に換算して勘定科目・月で突き合わせ、金額が変わった明細だけを返す
+pub fn diff_snapshots(
+    from: &[PlSnapshotEntry],
+    to: &[PlSnapshotEntry],
+) -> Result<Vec<VarianceLine>, AppError> {
+    let monthly = |entries: &[PlSnapshotEntry]| -> Result<Vec<MonthlyAmount>, AppError> {
+        entries
+            .iter()
+            .map(|entry| {
+                let amount = entry.reporting_amount.ok_or_else(|| {
+                    AppError::MissingExchangeRate(format!(
+                        "No exchange rate for {} in {}",
+                        entry.currency,
+                        entry.date.format("%Y-%m")
+                    ))
+                })?;
+                Ok(MonthlyAmount {
+                    account_item_id: entry.account_item_id,
+                    account_item_name: entry.account_item_name.clone(),
+                    display_order: entry.display_order,
+                    month: entry.date,
+                    amount,
+                })
+            })
+            .collect()
+    };
+
+    Ok(build_variance(&monthly(from)?, &monthly(to)?)
+        .into_iter()
+        .filter(|line| !line.delta.is_zero())
+        .collect())
+}
+
+#[async_trait::async_trait]
+pub trait PlSnapshotRepository: Send + Sync {
+    /// 新しい版から順に返す
+    async fn find_by_project(
+        &self,
+        project_id: Uuid,
+        scenario: Scenario,
+    ) -> Result<Vec<PlSnapshot>, AppError>;
+    async fn find_by_version(
+        &self,
+        project_id: Uuid,
+        scenario: Scenario,
+        version: i32,
+    ) -> Result<Option<PlSnapshot>, AppError>;
+    async fn find_entries(&self, snapshot_id: Uuid) -> Result<Vec<PlSnapshotEntry>, AppError>;
+}
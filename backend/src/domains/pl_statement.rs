@@ -0,0 +1,556 @@
+use std::collections::HashMap;
+
+use chrono::{Months, NaiveDate};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::domains::{
+    account_item::{AccountItem, AccountType, in_hierarchy_order},
+    pl_entry::{PlEntry, PlEntryDetail},
+    pl_snapshot::PlSnapshotEntry,
+    report::MonthlyAmount,
+};
+
+/// 損益計算書に表示する区分の順序
+const SECTION_ORDER: [AccountType; 6] = [
+    AccountType::Revenue,
+    AccountType::CostOfGoodsSold,
+    AccountType::SellingGeneralAdmin,
+    AccountType::NonOperatingIncome,
+    AccountType::NonOperatingExpense,
+    AccountType::Tax,
+];
+
+/// 損益計算書に集計できる金額
+pub trait PlAmount {
+    fn account_item_id(&self) -> Uuid;
+    fn date(&self) -> NaiveDate;
+    fn amount(&self) -> Decimal;
+}
+
+impl PlAmount for PlEntry {
+    fn account_item_id(&self) -> Uuid {
+        self.account_item_id
+    }
+
+    fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    fn amount(&self) -> Decimal {
+        self.amount
+    }
+}
+
+/// 換算レートが未登録の明細は集計しない
+impl PlAmount for PlEntryDetail {
+    fn account_item_id(&self) -> Uuid {
+        self.account_item_id
+    }
+
+    fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    fn amount(&self) -> Decimal {
+        self.reporting_amount.unwrap_or_default()
+    }
+}
+
+/// 換算レートが未登録の明細は集計しない
+impl PlAmount for PlSnapshotEntry {
+    fn account_item_id(&self) -> Uuid {
+        self.account_item_id
+    }
+
+    fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    fn amount(&self) -> Decimal {
+        self.reporting_amount.unwrap_or_default()
+    }
+}
+
+impl PlAmount for MonthlyAmount {
+    fn account_item_id(&self) -> Uuid {
+        self.account_item_id
+    }
+
+    fn date(&self) -> NaiveDate {
+        self.month
+    }
+
+    fn amount(&self) -> Decimal {
+        self.amount
+    }
+}
+
+/// 期間ごとの金額と期間合計
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodAmounts {
+    pub amounts: Vec<Decimal>,
+    pub total: Decimal,
+}
+
+impl PeriodAmounts {
+    fn new(amounts: Vec<Decimal>) -> Self {
+        let total = amounts.iter().sum();
+        Self { amounts, total }
+    }
+
+    fn zip_with(&self, other: &PeriodAmounts, f: impl Fn(Decimal, Decimal) -> Decimal) -> Self {
+        Self::new(
+            self.amounts
+                .iter()
+                .zip(&other.amounts)
+                .map(|(a, b)| f(*a, *b))
+                .collect(),
+        )
+    }
+}
+
+/// 勘定科目の行（子科目は親科目の直後に並ぶ）
+#[derive(Debug, Clone, Serialize)]
+pub struct StatementLine {
+    pub account_item_id: Uuid,
+    pub account_item_name: String,
+    pub parent_id: Option<Uuid>,
+    #[serde(flatten)]
+    pub values: PeriodAmounts,
+}
+
+/// 区分ごとの科目と小計
+#[derive(Debug, Clone, Serialize)]
+pub struct StatementSection {
+    pub account_type: AccountType,
+    pub label: &'static str,
+    pub lines: Vec<StatementLine>,
+    pub subtotal: PeriodAmounts,
+}
+
+/// 区分の小計から算出する利益
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ProfitKind {
+    GrossProfit,
+    OperatingProfit,
+    OrdinaryProfit,
+    NetProfit,
+}
+
+impl ProfitKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProfitKind::GrossProfit => "売上総利益",
+            ProfitKind::OperatingProfit => "営業利益",
+            ProfitKind::OrdinaryProfit => "経常利益",
+            ProfitKind::NetProfit => "当期純利益",
+        }
+    }
+
+    /// この利益を表示する直前の区分
+    pub fn after(&self) -> AccountType {
+        match self {
+            ProfitKind::GrossProfit => AccountType::CostOfGoodsSold,
+            ProfitKind::OperatingProfit => AccountType::SellingGeneralAdmin,
+            ProfitKind::OrdinaryProfit => AccountType::NonOperatingExpense,
+            ProfitKind::NetProfit => AccountType::Tax,
+        }
+    }
+}
+
+/// 利益の行と売上高に対する利益率
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfitLine {
+    pub kind: ProfitKind,
+    pub label: &'static str,
+    #[serde(flatten)]
+    pub values: PeriodAmounts,
+    /// 期間ごとの利益率（%）。売上高が 0 の期間は算出しない
+    pub margins: Vec<Option<Decimal>>,
+    pub total_margin: Option<Decimal>,
+}
+
+/// 損益計算書（期間は月次期間の初日で表す）
+#[derive(Debug, Clone, Serialize)]
+pub struct PlStatement {
+    pub periods: Vec<NaiveDate>,
+    pub sections: Vec<StatementSection>,
+    pub profits: Vec<ProfitLine>,
+}
+
+/// 帳票に出力する行の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementRowKind {
+    Item,
+    Subtotal,
+    Profit,
+}
+
+/// 表示順に並べた損益計算書の行
+#[derive(Debug, Clone)]
+pub struct StatementRow<'a> {
+    pub kind: StatementRowKind,
+    /// 子科目の場合は 1
+    pub level: u8,
+    pub label: String,
+    pub values: &'a PeriodAmounts,
+}
+
+impl PlStatement {
+    /// 明細を期間・勘定科目ごとに集計し、区分の小計と利益を算出する
+    ///
+    /// 期間外の明細は集計しない。無効な科目は期間内に明細がある場合のみ含める
+    pub fn build<T: PlAmount>(periods: &[NaiveDate], items: &[AccountItem], rows: &[T]) -> Self {
+        let mut amounts: HashMap<Uuid, Vec<Decimal>> = HashMap::new();
+        for row in rows {
+            if let Some(index) = period_index(periods, row.date()) {
+                amounts
+                    .entry(row.account_item_id())
+                    .or_insert_with(|| vec![Decimal::ZERO; periods.len()])[index] += row.amount();
+            }
+        }
+
+        let sections: Vec<StatementSection> = SECTION_ORDER
+            .iter()
+            .map(|account_type| {
+                let type_items = in_hierarchy_order(
+                    items
+                        .iter()
+                        .filter(|item| item.account_type == *account_type)
+                        .filter(|item| item.is_active || amounts.contains_key(&item.id)),
+                );
+
+                let lines: Vec<StatementLine> = type_items
+                    .into_iter()
+                    .map(|item| StatementLine {
+                        account_item_id: item.id,
+                        account_item_name: item.name.clone(),
+                        parent_id: item.parent_id,
+                        values: PeriodAmounts::new(
+                            amounts
+                                .get(&item.id)
+                                .cloned()
+                                .unwrap_or_else(|| vec![Decimal::ZERO; periods.len()]),
+                        ),
+                    })
+                    .collect();
+
+                let subtotal = PeriodAmounts::new(
+                    (0..periods.len())
+                        .map(|i| lines.iter().map(|l| l.values.amounts[i]).sum())
+                        .collect(),
+                );
+
+                StatementSection {
+                    account_type: account_type.clone(),
+                    label: account_type.label(),
+                    lines,
+                    subtotal,
+                }
+            })
+            .collect();
+
+        let mut statement = Self {
+            periods: periods.to_vec(),
+            sections,
+            profits: Vec::new(),
+        };
+
+        // 費用の区分は正の金額で登録されている前提で差し引く
+        // 売上総利益 = 売上高 - 売上原価、営業利益 = 売上総利益 - 販管費
+        // 経常利益 = 営業利益 + 営業外収益 - 営業外費用、当期純利益 = 経常利益 - 法人税等
+        let sub = |a: Decimal, b: Decimal| a - b;
+        let add = |a: Decimal, b: Decimal| a + b;
+        let gross = statement
+            .subtotal(AccountType::Revenue)
+            .zip_with(statement.subtotal(AccountType::CostOfGoodsSold), sub);
+        let operating = gross.zip_with(statement.subtotal(AccountType::SellingGeneralAdmin), sub);
+        let ordinary = operating
+            .zip_with(statement.subtotal(AccountType::NonOperatingIncome), add)
+            .zip_with(statement.subtotal(AccountType::NonOperatingExpense), sub);
+        let net = ordinary.zip_with(statement.subtotal(AccountType::Tax), sub);
+
+        statement.profits = vec![
+            statement.profit_line(ProfitKind::GrossProfit, gross),
+            statement.profit_line(ProfitKind::OperatingProfit, operating),
+            statement.profit_line(ProfitKind::OrdinaryProfit, ordinary),
+            statement.profit_line(ProfitKind::NetProfit, net),
+        ];
+
+        statement
+    }
+
+    pub fn section(&self, account_type: AccountType) -> Option<&StatementSection> {
+        self.sections
+            .iter()
+            .find(|section| section.account_type == account_type)
+    }
+
+    pub fn profit(&self, kind: ProfitKind) -> Option<&ProfitLine> {
+        self.profits.iter().find(|profit| profit.kind == kind)
+    }
+
+    /// 科目・区分小計・利益を表示順に並べる。科目のない区分は小計を省く
+    pub fn rows(&self) -> Vec<StatementRow<'_>> {
+        let mut rows = Vec::new();
+
+        for section in &self.sections {
+            for line in &section.lines {
+                let is_child = line
+                    .parent_id
+                    .is_some_and(|p| section.lines.iter().any(|l| l.account_item_id == p));
+                rows.push(StatementRow {
+                    kind: StatementRowKind::Item,
+                    level: u8::from(is_child),
+                    label: line.account_item_name.clone(),
+                    values: &line.values,
+                });
+            }
+            if !section.lines.is_empty() {
+                rows.push(StatementRow {
+                    kind: StatementRowKind::Subtotal,
+                    level: 0,
+                    label: format!("{} 計", section.label),
+                    values: &section.subtotal,
+                });
+            }
+            for profit in self
+                .profits
+                .iter()
+                .filter(|profit| profit.kind.after() == section.account_type)
+            {
+                rows.push(StatementRow {
+                    kind: StatementRowKind::Profit,
+                    level: 0,
+                    label: profit.label.to_string(),
+                    values: &profit.values,
+                });
+            }
+        }
+
+        rows
+    }
+
+    fn subtotal(&self, account_type: AccountType) -> &PeriodAmounts {
+        &self
+            .section(account_type)
+            .expect("every account type has a section")
+            .subtotal
+    }
+
+    fn profit_line(&self, kind: ProfitKind, values: PeriodAmounts) -> ProfitLine {
+        let revenue = self.subtotal(AccountType::Revenue);
+        ProfitLine {
+            kind,
+            label: kind.label(),
+            margins: values
+                .amounts
+                .iter()
+                .zip(&revenue.amounts)
+                .map(|(profit, revenue)| margin(*profit, *revenue))
+                .collect(),
+            total_margin: margin(values.total, revenue.total),
+            values,
+        }
+    }
+}
+
+/// 日付が属する期間の位置（期間は昇順の月初日）
+fn period_index(periods: &[NaiveDate], date: NaiveDate) -> Option<usize> {
+    let last = *periods.last()?;
+    if date >= last + Months::new(1) {
+        return None;
+    }
+    periods
+        .partition_point(|start| *start <= date)
+        .checked_sub(1)
+}
+
+fn margin(profit: Decimal, revenue: Decimal) -> Option<Decimal> {
+    if revenue.is_zero() {
+        None
+    } else {
+        Some((profit / revenue * Decimal::ONE_HUNDRED).round_dp(2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn item(name: &str, account_type: AccountType, display_order: i32) -> AccountItem {
+        AccountItem {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            account_type,
+            parent_id: None,
+            display_order,
+            description: None,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn date(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, m, d).unwrap()
+    }
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn row(item: &AccountItem, month: NaiveDate, amount: &str) -> MonthlyAmount {
+        MonthlyAmount {
+            account_item_id: item.id,
+            account_item_name: item.name.clone(),
+            display_order: item.display_order,
+            month,
+            amount: dec(amount),
+        }
+    }
+
+    fn amounts(values: &PeriodAmounts) -> Vec<Decimal> {
+        values.amounts.clone()
+    }
+
+    #[test]
+    fn sums_rows_into_periods_and_skips_rows_outside() {
+        let sales = item("売上", AccountType::Revenue, 1);
+        let periods = [date(4, 1), date(5, 1)];
+
+        let statement = PlStatement::build(
+            &periods,
+            std::slice::from_ref(&sales),
+            &[
+                row(&sales, date(4, 1), "100"),
+                row(&sales, date(4, 30), "50"),
+                row(&sales, date(5, 31), "30"),
+                row(&sales, date(3, 31), "999"),
+                row(&sales, date(6, 1), "999"),
+            ],
+        );
+
+        let revenue = statement.section(AccountType::Revenue).unwrap();
+        assert_eq!(
+            amounts(&revenue.lines[0].values),
+            vec![dec("150"), dec("30")]
+        );
+        assert_eq!(revenue.subtotal.total, dec("180"));
+    }
+
+    #[test]
+    fn computes_profits_and_margins() {
+        let sales = item("売上", AccountType::Revenue, 1);
+        let cogs = item("原価", AccountType::CostOfGoodsSold, 2);
+        let sga = item("販管費", AccountType::SellingGeneralAdmin, 3);
+        let income = item("受取利息", AccountType::NonOperatingIncome, 4);
+        let expense = item("支払利息", AccountType::NonOperatingExpense, 5);
+        let tax = item("法人税", AccountType::Tax, 6);
+        let periods = [date(4, 1), date(5, 1)];
+
+        let statement = PlStatement::build(
+            &periods,
+            &[
+                sales.clone(),
+                cogs.clone(),
+                sga.clone(),
+                income.clone(),
+                expense.clone(),
+                tax.clone(),
+            ],
+            &[
+                row(&sales, date(4, 1), "1000"),
+                row(&cogs, date(4, 1), "600"),
+                row(&sga, date(4, 1), "200"),
+                row(&income, date(4, 1), "10"),
+                row(&expense, date(4, 1), "30"),
+                row(&tax, date(4, 1), "45"),
+                row(&sga, date(5, 1), "50"),
+            ],
+        );
+
+        let profit = |kind| statement.profit(kind).unwrap();
+        assert_eq!(
+            amounts(&profit(ProfitKind::GrossProfit).values),
+            vec![dec("400"), dec("0")]
+        );
+        assert_eq!(
+            amounts(&profit(ProfitKind::OperatingProfit).values),
+            vec![dec("200"), dec("-50")]
+        );
+        assert_eq!(
+            amounts(&profit(ProfitKind::OrdinaryProfit).values),
+            vec![dec("180"), dec("-50")]
+        );
+        assert_eq!(
+            amounts(&profit(ProfitKind::NetProfit).values),
+            vec![dec("135"), dec("-50")]
+        );
+
+        let gross = profit(ProfitKind::GrossProfit);
+        assert_eq!(gross.margins, vec![Some(dec("40")), None]);
+        assert_eq!(gross.total_margin, Some(dec("40")));
+        assert_eq!(profit(ProfitKind::NetProfit).total_margin, Some(dec("8.5")));
+    }
+
+    #[test]
+    fn includes_inactive_items_only_when_they_have_rows() {
+        let mut unused = item("旧科目", AccountType::SellingGeneralAdmin, 1);
+        unused.is_active = false;
+        let mut used = item("廃止科目", AccountType::SellingGeneralAdmin, 2);
+        used.is_active = false;
+        let periods = [date(4, 1)];
+
+        let statement = PlStatement::build(
+            &periods,
+            &[unused, used.clone()],
+            &[row(&used, date(4, 1), "10")],
+        );
+
+        let section = statement.section(AccountType::SellingGeneralAdmin).unwrap();
+        assert_eq!(section.lines.len(), 1);
+        assert_eq!(section.lines[0].account_item_id, used.id);
+    }
+
+    #[test]
+    fn rows_follow_the_statement_layout() {
+        let sales = item("売上", AccountType::Revenue, 1);
+        let mut sub = item("保守売上", AccountType::Revenue, 2);
+        sub.parent_id = Some(sales.id);
+        let periods = [date(4, 1)];
+
+        let statement = PlStatement::build(&periods, &[sales, sub], &[] as &[MonthlyAmount]);
+        let rows = statement.rows();
+
+        let layout: Vec<(StatementRowKind, u8)> =
+            rows.iter().map(|row| (row.kind, row.level)).collect();
+        assert_eq!(
+            layout,
+            vec![
+                (StatementRowKind::Item, 0),
+                (StatementRowKind::Item, 1),
+                (StatementRowKind::Subtotal, 0),
+                (StatementRowKind::Profit, 0),
+                (StatementRowKind::Profit, 0),
+                (StatementRowKind::Profit, 0),
+                (StatementRowKind::Profit, 0),
+            ]
+        );
+        let profits: Vec<&str> = rows[3..].iter().map(|row| row.label.as_str()).collect();
+        assert_eq!(
+            profits,
+            [
+                ProfitKind::GrossProfit,
+                ProfitKind::OperatingProfit,
+                ProfitKind::OrdinaryProfit,
+                ProfitKind::NetProfit,
+            ]
+            .map(|kind| kind.label())
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+
+use crate::domains::{
+    pl_statement::{PlStatement, StatementRowKind},
+    report::ReportScenario,
+};
+
+const AMOUNT_FORMAT: &str = "#,##0;[Red]-#,##0";
+
+/// シナリオごとに1シートのPLワークブックを作成する
+///
+/// 損益計算書の科目・区分小計・利益を表示順に出力する
+pub fn write_pl_workbook(
+    title: &str,
+    sheets: &[(ReportScenario, PlStatement)],
+) -> anyhow::Result<Vec<u8>> {
+    let mut workbook = Workbook::new();
+
+    for (scenario, statement) in sheets {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(scenario.to_string())?;
+        write_sheet(worksheet, title, *scenario, statement)?;
+    }
+
+    Ok(workbook.save_to_buffer()?)
+}
+
+fn write_sheet(
+    worksheet: &mut Worksheet,
+    title: &str,
+    scenario: ReportScenario,
+    statement: &PlStatement,
+) -> anyhow::Result<()> {
+    let bold = Format::new().set_bold();
+    let amount = Format::new().set_num_format(AMOUNT_FORMAT);
+    let child = Format::new().set_num_format(AMOUNT_FORMAT).set_indent(1);
+    let subtotal = Format::new().set_bold().set_num_format(AMOUNT_FORMAT);
+
+    worksheet.write_string_with_format(0, 0, format!("{} ({})", title, scenario), &bold)?;
+
+    let header_row = 2;
+    let total_col = statement.periods.len() as u16 + 1;
+    worksheet.write_string_with_format(header_row, 0, "勘定科目", &bold)?;
+    for (i, month) in statement.periods.iter().enumerate() {
+        worksheet.write_string_with_format(
+            header_row,
+            i as u16 + 1,
+            month.format("%Y-%m").to_string(),
+            &bold,
+        )?;
+    }
+    worksheet.write_string_with_format(header_row, total_col, "合計", &bold)?;
+    worksheet.set_column_width(0, 24)?;
+    worksheet.set_freeze_panes(header_row + 1, 1)?;
+
+    for (i, line) in statement.rows().iter().enumerate() {
+        let row = header_row + 1 + i as u32;
+        let format = match line.kind {
+            StatementRowKind::Item if line.level > 0 => &child,
+            StatementRowKind::Item => &amount,
+            StatementRowKind::Subtotal | StatementRowKind::Profit => &subtotal,
+        };
+
+        worksheet.write_string_with_format(row, 0, &line.label, format)?;
+        for (col, value) in line.values.amounts.iter().enumerate() {
+            worksheet.write_number_with_format(row, col as u16 + 1, to_f64(*value), format)?;
+        }
+        worksheet.write_number_with_format(row, total_col, to_f64(line.values.total), format)?;
+    }
+
+    Ok(())
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or_default()
+}
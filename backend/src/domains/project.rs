@@ -7,8 +7,9 @@ use uuid::Uuid;
 
 use crate::error::AppError;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub enum ProjectType {
+    #[default]
     Normal,
     Agile,
     Maintenance,
@@ -24,12 +25,6 @@ impl fmt::Display for ProjectType {
     }
 }
 
-impl Default for ProjectType {
-    fn default() -> Self {
-        ProjectType::Normal
-    }
-}
-
 #[derive(Debug, Clone, Deserialize, Serialize, FromRow)]
 pub struct Project {
     pub id: Uuid,
@@ -98,7 +93,8 @@ pub struct UpdateProjectParam {
 #[async_trait::async_trait]
 pub trait ProjectRepository: Send + Sync {
     async fn create(&self, params: CreateProjectParam) -> Result<Project, AppError>;
-    async fn find_all(&self) -> Result<Vec<Project>, AppError>;
+    /// owner_id を指定すると、そのユーザーが所有するProjectに絞り込む
+    async fn find_all(&self, owner_id: Option<Uuid>) -> Result<Vec<Project>, AppError>;
     async fn find_by_id(&self, id: Uuid) -> Result<Option<Project>, AppError>;
     async fn update(&self, id: Uuid, params: UpdateProjectParam) -> Result<Project, AppError>;
     async fn delete(&self, id: Uuid) -> Result<(), AppError>;
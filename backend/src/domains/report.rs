@@ -0,0 +1,320 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{
+    Deserialize, Serialize,
+    de::{IntoDeserializer, value::Error as DeError},
+};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::{domains::pl_entry::Scenario, error::AppError};
+
+/// レポートで指定できるシナリオ（登録済みのシナリオと、明細から算出する仮想シナリオ）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ReportScenario {
+    Stored(Scenario),
+    /// 締め済みの月は Actual、それ以外の月は最新の計画（RevisedPlan がなければ MasterPlan）
+    Forecast,
+    /// 試算用のシナリオ（"Sandbox:{id}" で指定する）
+    Sandbox(Uuid),
+}
+
+impl ReportScenario {
+    /// 登録済みの全シナリオと仮想シナリオ（試算用のシナリオは含まない）
+    pub fn all() -> Vec<ReportScenario> {
+        Scenario::ALL
+            .into_iter()
+            .map(ReportScenario::Stored)
+            .chain([ReportScenario::Forecast])
+            .collect()
+    }
+}
+
+impl FromStr for ReportScenario {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || AppError::BadRequest(format!("Invalid scenario: {}", s));
+
+        if s == "Forecast" {
+            return Ok(ReportScenario::Forecast);
+        }
+        if let Some(id) = s.strip_prefix("Sandbox:") {
+            return Uuid::parse_str(id)
+                .map(ReportScenario::Sandbox)
+                .map_err(|_| invalid());
+        }
+        Scenario::deserialize(s.into_deserializer())
+            .map(ReportScenario::Stored)
+            .map_err(|_: DeError| invalid())
+    }
+}
+
+impl TryFrom<String> for ReportScenario {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ReportScenario> for String {
+    fn from(value: ReportScenario) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for ReportScenario {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReportScenario::Stored(scenario) => write!(f, "{:?}", scenario),
+            ReportScenario::Forecast => write!(f, "Forecast"),
+            ReportScenario::Sandbox(id) => write!(f, "Sandbox:{}", id),
+        }
+    }
+}
+
+/// 集計対象の範囲
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlScope {
+    /// Projectに紐づく全ての明細（Job単位の明細と配賦の明細を含み、Theme・Segmentの集計と一致する）
+    Project(Uuid),
+    Job(Uuid),
+    Theme(Uuid),
+    Segment(Uuid),
+    Service(Uuid),
+    /// Project配下のJobの明細のみ（Project単位の明細は含まない）
+    JobsOfProject(Uuid),
+}
+
+impl PlScope {
+    pub fn project_id(&self) -> Option<Uuid> {
+        match self {
+            PlScope::Project(id) => Some(*id),
+            _ => None,
+        }
+    }
+
+    pub fn job_id(&self) -> Option<Uuid> {
+        match self {
+            PlScope::Job(id) => Some(*id),
+            _ => None,
+        }
+    }
+
+    pub fn theme_id(&self) -> Option<Uuid> {
+        match self {
+            PlScope::Theme(id) => Some(*id),
+            _ => None,
+        }
+    }
+
+    pub fn segment_id(&self) -> Option<Uuid> {
+        match self {
+            PlScope::Segment(id) => Some(*id),
+            _ => None,
+        }
+    }
+
+    pub fn jobs_of_project_id(&self) -> Option<Uuid> {
+        match self {
+            PlScope::JobsOfProject(id) => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// Job単位の明細のみが集計対象になるかどうか
+    pub fn is_job_based(&self) -> bool {
+        matches!(
+            self,
+            PlScope::Job(_) | PlScope::Service(_) | PlScope::JobsOfProject(_)
+        )
+    }
+
+    /// Serviceへの紐付けはJob経由のため、Project単位の明細は含まれない
+    pub fn service_id(&self) -> Option<Uuid> {
+        match self {
+            PlScope::Service(id) => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+/// 勘定科目・月ごとの合計額
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct MonthlyAmount {
+    pub account_item_id: Uuid,
+    pub account_item_name: String,
+    pub display_order: i32,
+    pub month: NaiveDate,
+    pub amount: Decimal,
+}
+
+/// 予実差異の明細行
+#[derive(Debug, Clone, Serialize)]
+pub struct VarianceLine {
+    pub account_item_id: Uuid,
+    pub account_item_name: String,
+    pub month: NaiveDate,
+    pub base_amount: Decimal,
+    pub compare_amount: Decimal,
+    /// compare - base
+    pub delta: Decimal,
+    /// base に対する差異率（%）。base が 0 の場合は算出しない
+    pub delta_pct: Option<Decimal>,
+}
+
+/// 2つの月次合計を勘定科目・月で突き合わせて差異を算出する
+pub fn build_variance(base: &[MonthlyAmount], compare: &[MonthlyAmount]) -> Vec<VarianceLine> {
+    let mut lines: BTreeMap<(i32, Uuid, NaiveDate), VarianceLine> = BTreeMap::new();
+
+    for row in base {
+        variance_line(&mut lines, row).base_amount += row.amount;
+    }
+    for row in compare {
+        variance_line(&mut lines, row).compare_amount += row.amount;
+    }
+
+    lines
+        .into_values()
+        .map(|mut line| {
+            line.delta = line.compare_amount - line.base_amount;
+            line.delta_pct = if line.base_amount.is_zero() {
+                None
+            } else {
+                Some((line.delta / line.base_amount.abs() * Decimal::ONE_HUNDRED).round_dp(2))
+            };
+            line
+        })
+        .collect()
+}
+
+fn variance_line<'a>(
+    lines: &'a mut BTreeMap<(i32, Uuid, NaiveDate), VarianceLine>,
+    row: &MonthlyAmount,
+) -> &'a mut VarianceLine {
+    lines
+        .entry((row.display_order, row.account_item_id, row.month))
+        .or_insert_with(|| VarianceLine {
+            account_item_id: row.account_item_id,
+            account_item_name: row.account_item_name.clone(),
+            month: row.month,
+            base_amount: Decimal::ZERO,
+            compare_amount: Decimal::ZERO,
+            delta: Decimal::ZERO,
+            delta_pct: None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn month(m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, m, 1).unwrap()
+    }
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn amount(id: Uuid, display_order: i32, m: u32, amount: &str) -> MonthlyAmount {
+        MonthlyAmount {
+            account_item_id: id,
+            account_item_name: format!("科目{}", display_order),
+            display_order,
+            month: month(m),
+            amount: dec(amount),
+        }
+    }
+
+    #[test]
+    fn matches_rows_by_item_and_month() {
+        let (sales, cost) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let lines = build_variance(
+            &[
+                amount(cost, 2, 4, "50"),
+                amount(sales, 1, 4, "100"),
+                amount(sales, 1, 4, "100"),
+            ],
+            &[amount(sales, 1, 4, "250"), amount(sales, 1, 5, "30")],
+        );
+
+        let found: Vec<(Uuid, NaiveDate, Decimal, Decimal, Decimal)> = lines
+            .iter()
+            .map(|l| {
+                (
+                    l.account_item_id,
+                    l.month,
+                    l.base_amount,
+                    l.compare_amount,
+                    l.delta,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (sales, month(4), dec("200"), dec("250"), dec("50")),
+                (sales, month(5), dec("0"), dec("30"), dec("30")),
+                (cost, month(4), dec("50"), dec("0"), dec("-50")),
+            ]
+        );
+    }
+
+    #[test]
+    fn delta_pct_is_relative_to_the_absolute_base() {
+        let id = Uuid::new_v4();
+
+        let lines = build_variance(
+            &[
+                amount(id, 1, 4, "200"),
+                amount(id, 1, 5, "-200"),
+                amount(id, 1, 6, "0"),
+                amount(id, 1, 7, "3"),
+            ],
+            &[
+                amount(id, 1, 4, "150"),
+                amount(id, 1, 5, "-150"),
+                amount(id, 1, 6, "10"),
+                amount(id, 1, 7, "4"),
+            ],
+        );
+
+        let pcts: Vec<Option<Decimal>> = lines.iter().map(|l| l.delta_pct).collect();
+        assert_eq!(
+            pcts,
+            vec![Some(dec("-25")), Some(dec("25")), None, Some(dec("33.33"))]
+        );
+    }
+
+    #[test]
+    fn parses_report_scenarios() {
+        let id = Uuid::new_v4();
+
+        assert_eq!(
+            "Actual".parse::<ReportScenario>().unwrap(),
+            ReportScenario::Stored(Scenario::Actual)
+        );
+        assert_eq!(
+            "Forecast".parse::<ReportScenario>().unwrap(),
+            ReportScenario::Forecast
+        );
+        assert_eq!(
+            format!("Sandbox:{}", id).parse::<ReportScenario>().unwrap(),
+            ReportScenario::Sandbox(id)
+        );
+        assert!("Sandbox:1".parse::<ReportScenario>().is_err());
+        assert!("Budget".parse::<ReportScenario>().is_err());
+        for scenario in ReportScenario::all() {
+            assert_eq!(
+                scenario.to_string().parse::<ReportScenario>().unwrap(),
+                scenario
+            );
+        }
+    }
+}
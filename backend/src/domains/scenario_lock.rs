@@ -0,0 +1,49 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::{domains::pl_entry::Scenario, error::AppError};
+
+/// 確定済みシナリオのロック
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ScenarioLock {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub scenario: Scenario,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+
+    pub locked_by: Uuid,
+    pub locked_at: DateTime<Utc>,
+    pub unlocked_by: Option<Uuid>,
+    pub unlocked_at: Option<DateTime<Utc>>,
+    pub unlock_reason: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateScenarioLockParam {
+    pub project_id: Uuid,
+    pub scenario: Scenario,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub locked_by: Uuid,
+}
+
+#[derive(Debug, Clone)]
+pub struct UnlockScenarioParam {
+    pub unlocked_by: Uuid,
+    pub reason: String,
+}
+
+#[async_trait::async_trait]
+pub trait ScenarioLockRepository: Send + Sync {
+    async fn create(&self, params: CreateScenarioLockParam) -> Result<ScenarioLock, AppError>;
+    async fn find_by_project(&self, project_id: Uuid) -> Result<Vec<ScenarioLock>, AppError>;
+    async fn unlock(
+        &self,
+        project_id: Uuid,
+        id: Uuid,
+        params: UnlockScenarioParam,
+    ) -> Result<ScenarioLock, AppError>;
+}
@@ -38,4 +38,5 @@ pub struct CreateSegmentParam {
 pub trait SegmentRepository: Send + Sync {
     async fn create(&self, params: CreateSegmentParam) -> Result<Segment, AppError>;
     async fn find_all(&self) -> Result<Vec<Segment>, AppError>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Segment>, AppError>;
 }
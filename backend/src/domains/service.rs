@@ -40,7 +40,8 @@ pub struct UpdateServiceParam {
 #[async_trait::async_trait]
 pub trait ServiceRepository: Send + Sync {
     async fn create(&self, params: CreateServiceParam) -> Result<Service, AppError>;
-    async fn find_all(&self) -> Result<Vec<Service>, AppError>;
+    /// owner_id を指定すると、そのユーザーが所有するServiceに絞り込む
+    async fn find_all(&self, owner_id: Option<Uuid>) -> Result<Vec<Service>, AppError>;
     async fn find_by_id(&self, id: Uuid) -> Result<Option<Service>, AppError>;
     async fn find_by_slug(&self, slug: &str) -> Result<Option<Service>, AppError>;
     async fn update(&self, id: Uuid, params: UpdateServiceParam) -> Result<Service, AppError>;
@@ -0,0 +1,69 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// ログインセッション
+///
+/// アクセストークンはセッションIDを持ち、取り消されたセッションのトークンは有効期限内でも拒否する
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct Session {
+    pub id: Uuid,
+    pub user_id: Uuid,
+
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// リフレッシュトークン・招待トークンなどの推測できないトークン（平文は利用者に返すだけで、DBにはハッシュのみ保存する）
+#[derive(Debug, Clone)]
+pub struct SecretToken {
+    pub token: String,
+    pub hash: String,
+}
+
+impl SecretToken {
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let token = hex::encode(bytes);
+        let hash = Self::hash(&token);
+
+        Self { token, hash }
+    }
+
+    pub fn hash(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+}
+
+#[async_trait::async_trait]
+pub trait SessionRepository: Send + Sync {
+    /// セッションを開始し、最初のリフレッシュトークンを登録する
+    async fn create(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Session, AppError>;
+    /// リフレッシュトークンを使用済みにして新しいトークンに置き換える
+    ///
+    /// 使用済みのトークンが再び使われた場合は漏洩とみなし、セッションごと取り消す
+    async fn rotate(
+        &self,
+        token_hash: &str,
+        new_token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Session, AppError>;
+    /// セッションが取り消されておらず、ユーザーも有効かどうか
+    async fn is_active(&self, id: Uuid) -> Result<bool, AppError>;
+    async fn revoke(&self, id: Uuid) -> Result<(), AppError>;
+    /// ユーザーの全セッションを取り消し、取り消した件数を返す
+    async fn revoke_all(&self, user_id: Uuid) -> Result<u64, AppError>;
+    /// 指定したセッション以外のユーザーのセッションを取り消し、取り消した件数を返す
+    async fn revoke_others(&self, user_id: Uuid, session_id: Uuid) -> Result<u64, AppError>;
+}
@@ -1,7 +1,9 @@
 use argon2::{
-    Argon2,
+    Argon2, PasswordHash, PasswordVerifier,
     password_hash::{PasswordHasher, SaltString, rand_core::OsRng},
 };
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -27,8 +29,95 @@ pub struct User {
     pub updated_at: DateTime<Utc>,
 }
 
+impl User {
+    /// パスワードが登録済みのハッシュと一致するかどうか
+    pub fn verify_password(&self, password: &str) -> anyhow::Result<bool> {
+        let parsed_hash = PasswordHash::new(&self.password_hash)
+            .map_err(|e| anyhow::anyhow!("Invalid password hash in DB: {}", e))?;
+
+        Ok(Argon2::default()
+            .verify_password(password.as_bytes(), &parsed_hash)
+            .is_ok())
+    }
+}
+
+/// パスワードをハッシュ化する
+pub fn hash_password(password: &str) -> anyhow::Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let argon2 = Argon2::default();
+    Ok(argon2
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| anyhow::anyhow!("Password hashing failed: {}", e))?
+        .to_string())
+}
+
+/// 登録されていないメールアドレスのログインで照合に使うハッシュ
+///
+/// 破棄したランダムな値を hash_password と同じ設定でハッシュ化したもので、どのパスワードとも一致しない
+const DUMMY_PASSWORD_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$fSS8FagaWOnrRA1ouoyhcA$oFw20xcZ83YKAoTsoKSJ5ruWjFnENOw691BCmWgwnak";
+
+/// 登録済みのユーザーと同じだけ照合に時間をかけ、応答時間からメールアドレスの登録有無を推測させない
+pub fn verify_dummy_password(password: &str) {
+    if let Ok(parsed_hash) = PasswordHash::new(DUMMY_PASSWORD_HASH) {
+        let _ = Argon2::default().verify_password(password.as_bytes(), &parsed_hash);
+    }
+}
+
+/// パスワードの最小文字数
+pub const PASSWORD_MIN_LENGTH: usize = 8;
+
+/// 新しく設定するパスワードを検証する（登録・変更・再設定で共通）
+pub fn validate_new_password(password: &str) -> Result<(), AppError> {
+    if password.chars().count() < PASSWORD_MIN_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Password must be at least {} characters",
+            PASSWORD_MIN_LENGTH
+        )));
+    }
+
+    Ok(())
+}
+
+/// メールアドレスの最大長（users.email の桁数）
+pub const EMAIL_MAX_LENGTH: usize = 255;
+
+/// メールアドレスの形式を検証し、照合用に正規化する（前後の空白を除き小文字にする）
+pub fn normalize_email(email: &str) -> Result<String, AppError> {
+    let email = email.trim().to_lowercase();
+    let invalid = || AppError::Validation(format!("Invalid email '{}'", email));
+
+    if email.len() > EMAIL_MAX_LENGTH {
+        return Err(invalid());
+    }
+    let (local, domain) = email.split_once('@').ok_or_else(invalid)?;
+
+    let valid_local = !local.is_empty()
+        && local.len() <= 64
+        && !local.starts_with('.')
+        && !local.ends_with('.')
+        && !local.contains("..")
+        && local
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~.".contains(c));
+    let labels: Vec<&str> = domain.split('.').collect();
+    let valid_domain = labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+
+    if !valid_local || !valid_domain {
+        return Err(invalid());
+    }
+
+    Ok(email)
+}
+
 /// 権限
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum UserRole {
     General,
@@ -47,6 +136,20 @@ impl UserRole {
     }
 }
 
+impl FromStr for UserRole {
+    type Err = AppError;
+
+    /// トークンに含まれる権限の文字列から変換する（不明な権限は何も許可しない）
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "general" => Ok(UserRole::General),
+            "admin" => Ok(UserRole::Admin),
+            "manager" => Ok(UserRole::Manager),
+            _ => Err(AppError::Forbidden),
+        }
+    }
+}
+
 /// UserRoleのデフォルト値を設定する関数
 impl Default for UserRole {
     fn default() -> Self {
@@ -62,23 +165,107 @@ pub struct CreateUserParam {
     pub name: String,
     pub email: String,
     pub password: String,
-    pub role: Option<UserRole>,
 }
 
 impl CreateUserParam {
     pub fn hash_password(&self) -> anyhow::Result<String> {
-        let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
-        Ok(argon2
-            .hash_password(self.password.as_bytes(), &salt)
-            .map_err(|e| anyhow::anyhow!("Password hashing failed: {}", e))?
-            .to_string())
+        hash_password(&self.password)
     }
 }
 
 #[async_trait::async_trait]
 pub trait UserRepository: Send + Sync {
-    async fn create(&self, params: CreateUserParam) -> Result<User, AppError>;
+    async fn create(&self, params: CreateUserParam, role: UserRole) -> Result<User, AppError>;
+    /// ユーザーが1人もいない場合に限り、管理者として登録する（登録しなかった場合は None）
+    async fn create_initial_admin(&self, params: CreateUserParam)
+    -> Result<Option<User>, AppError>;
     async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, AppError>;
+    /// 大文字・小文字を区別せずにメールアドレスで探す
     async fn find_by_email(&self, email: &str) -> Result<Option<User>, AppError>;
+    async fn set_active(
+        &self,
+        id: Uuid,
+        is_active: bool,
+        updated_by: Uuid,
+    ) -> Result<User, AppError>;
+    async fn update_password(
+        &self,
+        id: Uuid,
+        password_hash: &str,
+        updated_by: Uuid,
+    ) -> Result<(), AppError>;
+    async fn update_role(
+        &self,
+        id: Uuid,
+        role: UserRole,
+        updated_by: Uuid,
+    ) -> Result<User, AppError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_passwords_need_the_minimum_length() {
+        assert!(validate_new_password("").is_err());
+        assert!(validate_new_password("1234567").is_err());
+        assert!(validate_new_password("12345678").is_ok());
+        assert!(validate_new_password("パスワードです。").is_ok());
+    }
+
+    #[test]
+    fn dummy_password_hash_is_parsed_like_stored_hashes() {
+        let parsed_hash = PasswordHash::new(DUMMY_PASSWORD_HASH).unwrap();
+        let stored = hash_password("password1").unwrap();
+
+        assert_eq!(
+            parsed_hash.params,
+            PasswordHash::new(&stored).unwrap().params
+        );
+        assert!(
+            Argon2::default()
+                .verify_password(b"password1", &parsed_hash)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn normalizes_emails() {
+        assert_eq!(
+            normalize_email("  Taro.Yamada+pl@Example.CO.JP ").unwrap(),
+            "taro.yamada+pl@example.co.jp"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_emails() {
+        let long_local = format!("{}@example.com", "a".repeat(65));
+        let long_email = format!("a@{}.com", vec!["b".repeat(63); 4].join("."));
+
+        for email in [
+            "",
+            "taro",
+            "@example.com",
+            "taro@",
+            "taro@localhost",
+            "taro@@example.com",
+            "taro@example..com",
+            "taro@-example.com",
+            "taro@exa_mple.com",
+            ".taro@example.com",
+            "ta..ro@example.com",
+            "ta ro@example.com",
+            "taro@exam\nple.com",
+            "山田@example.com",
+            long_local.as_str(),
+            long_email.as_str(),
+        ] {
+            assert!(
+                matches!(normalize_email(email), Err(AppError::Validation(_))),
+                "{:?} should be rejected",
+                email
+            );
+        }
+    }
 }
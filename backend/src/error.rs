@@ -6,6 +6,8 @@ use axum::{
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::domains::{budget::BudgetLine, pl_csv::PlCsvError};
+
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Database error: {0}")]
@@ -23,6 +25,27 @@ pub enum AppError {
     #[error("Authentication failed")]
     AuthError,
 
+    #[error("Forbidden")]
+    Forbidden,
+
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+
+    #[error("Scenario locked: {0}")]
+    ScenarioLocked(String),
+
+    #[error("Period closed: {0}")]
+    PeriodClosed(String),
+
+    #[error("Missing exchange rate: {0}")]
+    MissingExchangeRate(String),
+
+    #[error("Import failed with {} error(s)", .0.len())]
+    ImportFailed(Vec<PlCsvError>),
+
+    #[error("Budget exceeded in {} line(s)", .0.len())]
+    BudgetExceeded(Vec<BudgetLine>),
+
     #[error("Internal server error")]
     Internal(#[from] anyhow::Error),
 }
@@ -30,11 +53,27 @@ pub enum AppError {
 #[derive(Serialize)]
 struct ErrorResponse {
     error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<serde_json::Value>,
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
+            AppError::ImportFailed(errors) => {
+                let body = ErrorResponse {
+                    error: "Import failed".to_string(),
+                    details: serde_json::to_value(errors).ok(),
+                };
+                return (StatusCode::BAD_REQUEST, Json(body)).into_response();
+            }
+            AppError::BudgetExceeded(overruns) => {
+                let body = ErrorResponse {
+                    error: "Job plan exceeds the remaining project budget".to_string(),
+                    details: serde_json::to_value(overruns).ok(),
+                };
+                return (StatusCode::CONFLICT, Json(body)).into_response();
+            }
             AppError::Database(ref e) => {
                 tracing::error!("Database error: {:?}", e);
                 (
@@ -46,6 +85,11 @@ impl IntoResponse for AppError {
             AppError::Validation(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::AuthError => (StatusCode::UNAUTHORIZED, "Unauthorized".to_string()),
+            AppError::Forbidden => (StatusCode::FORBIDDEN, "Forbidden".to_string()),
+            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg),
+            AppError::ScenarioLocked(msg) => (StatusCode::CONFLICT, msg),
+            AppError::PeriodClosed(msg) => (StatusCode::CONFLICT, msg),
+            AppError::MissingExchangeRate(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg),
             AppError::Internal(err) => {
                 tracing::error!("Internal error: {:?}", err);
                 (
@@ -55,7 +99,14 @@ impl IntoResponse for AppError {
             }
         };
 
-        (status, Json(ErrorResponse { error: message })).into_response()
+        (
+            status,
+            Json(ErrorResponse {
+                error: message,
+                details: None,
+            }),
+        )
+            .into_response()
     }
 }
 
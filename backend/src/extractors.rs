@@ -1,12 +1,65 @@
-use axum::{extract::FromRequestParts, http::request::Parts};
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header::USER_AGENT, request::Parts},
+};
 use jsonwebtoken::{DecodingKey, Validation, decode};
+use uuid::Uuid;
 
-use crate::{AppState, error::AppError, handlers::auth::Claims};
+use crate::{
+    AppState,
+    domains::{
+        permission::{Action, Ownership, Resource, allowed_by_ownership},
+        user::UserRole,
+    },
+    error::AppError,
+    handlers::auth::Claims,
+};
 
 pub struct AuthUser {
     pub claims: Claims,
 }
 
+impl AuthUser {
+    /// トークンに含まれる権限
+    pub fn role(&self) -> Result<UserRole, AppError> {
+        self.claims.role.parse()
+    }
+
+    /// トークンを発行したセッション
+    pub fn session_id(&self) -> Result<Uuid, AppError> {
+        Uuid::parse_str(&self.claims.sid).map_err(|_| AppError::AuthError)
+    }
+
+    /// 権限の対応表で許可されていない操作を 403 で拒否する
+    pub fn require(&self, resource: Resource, action: Action) -> Result<(), AppError> {
+        self.require_owned(resource, action, Ownership::Other)
+    }
+
+    /// 権限が足りない場合は、リソースの所有者・担当者として許可される操作かどうかを見る
+    pub fn require_owned(
+        &self,
+        resource: Resource,
+        action: Action,
+        ownership: Ownership,
+    ) -> Result<(), AppError> {
+        if self.role()?.can(resource, action) || allowed_by_ownership(resource, action, ownership) {
+            return Ok(());
+        }
+
+        tracing::warn!(
+            "User {} ({}) is not allowed to {:?} {:?} as {:?}",
+            self.claims.sub,
+            self.claims.role,
+            action,
+            resource,
+            ownership
+        );
+        Err(AppError::Forbidden)
+    }
+}
+
 impl FromRequestParts<AppState> for AuthUser {
     type Rejection = AppError;
 
@@ -34,8 +87,64 @@ impl FromRequestParts<AppState> for AuthUser {
             AppError::AuthError
         })?;
 
-        Ok(AuthUser {
+        let auth_user = AuthUser {
             claims: token_data.claims,
-        })
+        };
+
+        // 取り消されたセッションや無効化されたユーザーのトークンは有効期限内でも拒否する
+        let session_id = auth_user.session_id()?;
+        if !state.session_repository.is_active(session_id).await? {
+            tracing::warn!(
+                "Rejected token of revoked session {} (user {})",
+                session_id,
+                auth_user.claims.sub
+            );
+            return Err(AppError::AuthError);
+        }
+
+        Ok(auth_user)
+    }
+}
+
+/// リクエスト元のIPアドレスとユーザーエージェント
+pub struct ClientInfo {
+    pub ip: String,
+    pub user_agent: Option<String>,
+}
+
+impl FromRequestParts<AppState> for ClientInfo {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        // 信頼できるリバースプロキシの背後では、プロキシが末尾に追加したアドレスを使う
+        // （IPアドレスとして読めない値は無視し、記録する値を ip_address 列の桁数に収める）
+        let forwarded = state
+            .trust_proxy_headers
+            .then(|| parts.headers.get("X-Forwarded-For"))
+            .flatten()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|ip| ip.trim().parse::<IpAddr>().ok())
+            .map(|ip| ip.to_string());
+
+        let ip = forwarded
+            .or_else(|| {
+                parts
+                    .extensions
+                    .get::<ConnectInfo<SocketAddr>>()
+                    .map(|ConnectInfo(addr)| addr.ip().to_string())
+            })
+            .unwrap_or_else(|| "unknown".to_string());
+
+        let user_agent = parts
+            .headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        Ok(ClientInfo { ip, user_agent })
     }
 }
@@ -1,9 +1,275 @@
-use axum::{Json, extract::State};
+use std::collections::HashSet;
 
-use crate::{AppState, domains::account_item::AccountItem, error::Result};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Deserializer};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    domains::{
+        account_item::{
+            AccountItem, AccountType, CreateAccountItemParam, UpdateAccountItemParam,
+            in_hierarchy_order,
+        },
+        permission::{Action, Resource},
+    },
+    error::{AppError, Result},
+    extractors::AuthUser,
+};
+
+/// 勘定科目一覧のクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct AccountItemQuery {
+    /// true の場合は有効な科目のみ返す（明細入力用）
+    pub active: Option<bool>,
+}
+
+/// 勘定科目作成リクエスト
+#[derive(Debug, Deserialize)]
+pub struct CreateAccountItemRequest {
+    pub name: String,
+    /// 省略時は親科目の区分を引き継ぐ
+    pub account_type: Option<AccountType>,
+    pub parent_id: Option<Uuid>,
+    pub display_order: Option<i32>,
+    pub description: Option<String>,
+}
+
+/// 勘定科目更新リクエスト
+#[derive(Debug, Deserialize)]
+pub struct UpdateAccountItemRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// null を指定すると親科目を外す
+    #[serde(default, deserialize_with = "double_option")]
+    pub parent_id: Option<Option<Uuid>>,
+    pub display_order: Option<i32>,
+    pub is_active: Option<bool>,
+}
+
+/// 表示順変更リクエスト
+#[derive(Debug, Deserialize)]
+pub struct ReorderAccountItemsRequest {
+    pub ids: Vec<Uuid>,
+}
+
+/// 一覧取得 (GET /account-items?active=...)
+///
+/// 親科目の直後に子科目が続く順で返す
+pub async fn list_account_items(
+    State(state): State<AppState>,
+    Query(query): Query<AccountItemQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<AccountItem>>> {
+    auth_user.require(Resource::AccountItem, Action::Read)?;
 
-pub async fn list_account_items(State(state): State<AppState>) -> Result<Json<Vec<AccountItem>>> {
     let items = state.account_item_repository.find_all().await?;
+    let active_only = query.active.unwrap_or(false);
+
+    let items = in_hierarchy_order(items.iter().filter(|item| !active_only || item.is_active))
+        .into_iter()
+        .cloned()
+        .collect();
 
     Ok(Json(items))
 }
+
+/// 詳細取得 (GET /account-items/{aid})
+pub async fn get_account_item(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<Json<AccountItem>> {
+    auth_user.require(Resource::AccountItem, Action::Read)?;
+
+    let item = find_account_item(&state, id).await?;
+
+    Ok(Json(item))
+}
+
+/// 新規作成 (POST /account-items)
+pub async fn create_account_item(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateAccountItemRequest>,
+) -> Result<(StatusCode, Json<AccountItem>)> {
+    auth_user.require(Resource::AccountItem, Action::Create)?;
+
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::Validation("Name is required".to_string()));
+    }
+
+    let parent = match payload.parent_id {
+        Some(parent_id) => Some(find_parent(&state, parent_id).await?),
+        None => None,
+    };
+
+    let account_type = match (payload.account_type, &parent) {
+        (Some(account_type), _) => account_type,
+        (None, Some(parent)) => parent.account_type.clone(),
+        (None, None) => {
+            return Err(AppError::Validation(
+                "account_type is required for a top-level item".to_string(),
+            ));
+        }
+    };
+
+    if let Some(parent) = &parent {
+        ensure_same_type(parent, &account_type)?;
+    }
+
+    let param = CreateAccountItemParam {
+        name,
+        account_type,
+        parent_id: payload.parent_id,
+        display_order: payload.display_order,
+        description: payload.description,
+    };
+
+    let item = state.account_item_repository.create(param).await?;
+
+    Ok((StatusCode::CREATED, Json(item)))
+}
+
+/// 更新 (PATCH /account-items/{aid})
+///
+/// is_active を false にすると新規の明細入力には使えなくなるが、過去の明細は帳票に残る
+pub async fn update_account_item(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+    Json(payload): Json<UpdateAccountItemRequest>,
+) -> Result<Json<AccountItem>> {
+    auth_user.require(Resource::AccountItem, Action::Update)?;
+
+    let item = find_account_item(&state, id).await?;
+
+    if let Some(name) = &payload.name
+        && name.trim().is_empty()
+    {
+        return Err(AppError::Validation("Name must not be empty".to_string()));
+    }
+
+    if let Some(Some(parent_id)) = payload.parent_id {
+        if parent_id == id {
+            return Err(AppError::Validation(
+                "An account item cannot be its own parent".to_string(),
+            ));
+        }
+
+        let parent = find_parent(&state, parent_id).await?;
+        ensure_same_type(&parent, &item.account_type)?;
+
+        let items = state.account_item_repository.find_all().await?;
+        if items.iter().any(|child| child.parent_id == Some(id)) {
+            return Err(AppError::Validation(format!(
+                "Account item '{}' has child items and cannot be nested",
+                item.name
+            )));
+        }
+    }
+
+    let param = UpdateAccountItemParam {
+        name: payload.name.map(|name| name.trim().to_string()),
+        description: payload.description,
+        parent_id: payload.parent_id,
+        display_order: payload.display_order,
+        is_active: payload.is_active,
+    };
+
+    let item = state.account_item_repository.update(id, param).await?;
+
+    Ok(Json(item))
+}
+
+/// 表示順の一括変更 (PUT /account-items/order)
+///
+/// 指定された順に表示順を振り直す。指定されなかった科目の表示順は変えない
+pub async fn reorder_account_items(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<ReorderAccountItemsRequest>,
+) -> Result<Json<Vec<AccountItem>>> {
+    auth_user.require(Resource::AccountItem, Action::Update)?;
+
+    let mut seen = HashSet::new();
+    if let Some(id) = payload.ids.iter().find(|id| !seen.insert(**id)) {
+        return Err(AppError::Validation(format!(
+            "Account item {} is listed more than once",
+            id
+        )));
+    }
+
+    let items = state.account_item_repository.reorder(payload.ids).await?;
+
+    Ok(Json(
+        in_hierarchy_order(&items).into_iter().cloned().collect(),
+    ))
+}
+
+/// 削除 (DELETE /account-items/{aid})
+pub async fn delete_account_item(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<StatusCode> {
+    auth_user.require(Resource::AccountItem, Action::Delete)?;
+
+    state.account_item_repository.delete(id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_account_item(state: &AppState, id: Uuid) -> Result<AccountItem> {
+    state
+        .account_item_repository
+        .find_by_id(id)
+        .await?
+        .ok_or(AppError::NotFound(format!("Account item {} not found", id)))
+}
+
+/// 親科目は最上位の科目に限る（2階層まで）
+async fn find_parent(state: &AppState, parent_id: Uuid) -> Result<AccountItem> {
+    let parent = state
+        .account_item_repository
+        .find_by_id(parent_id)
+        .await?
+        .ok_or(AppError::Validation(format!(
+            "Parent account item {} not found",
+            parent_id
+        )))?;
+
+    if parent.parent_id.is_some() {
+        return Err(AppError::Validation(format!(
+            "Account item '{}' is already a child item and cannot have children",
+            parent.name
+        )));
+    }
+
+    Ok(parent)
+}
+
+fn ensure_same_type(parent: &AccountItem, account_type: &AccountType) -> Result<()> {
+    if parent.account_type != *account_type {
+        return Err(AppError::Validation(format!(
+            "Child item must have the same account type as its parent ({:?})",
+            parent.account_type
+        )));
+    }
+
+    Ok(())
+}
+
+/// 省略（変更しない）と null（値を消す）を区別する
+fn double_option<'de, D, T>(deserializer: D) -> std::result::Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
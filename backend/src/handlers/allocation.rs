@@ -0,0 +1,420 @@
+use std::collections::HashSet;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    domains::{
+        allocation::{
+            AllocationDriver, AllocationLine, AllocationRule, AllocationRuleParam, AllocationRun,
+            AllocationTarget,
+        },
+        fiscal::FiscalPeriodSpec,
+        permission::{Action, Resource},
+        pl_entry::Scenario,
+    },
+    error::{AppError, Result},
+    extractors::AuthUser,
+};
+
+/// 配賦ルール登録・更新リクエスト
+#[derive(Debug, Deserialize)]
+pub struct AllocationRuleRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub source_project_id: Uuid,
+    pub source_account_item_id: Uuid,
+    /// 配賦先に計上する勘定科目（省略時は配賦元と同じ科目）
+    pub target_account_item_id: Option<Uuid>,
+    pub scenario: Scenario,
+    pub period: FiscalPeriodSpec,
+    pub driver: AllocationDriver,
+    /// 配賦先（FixedRatio では比率とあわせて必須、それ以外は省略すると配賦元以外の全Project）
+    #[serde(default)]
+    pub targets: Vec<AllocationTarget>,
+}
+
+/// 配賦ルールと配賦先・実行履歴
+#[derive(Debug, Serialize)]
+pub struct AllocationRuleResponse {
+    #[serde(flatten)]
+    pub rule: AllocationRule,
+    pub targets: Vec<AllocationTarget>,
+    pub runs: Vec<AllocationRun>,
+}
+
+/// 配賦の実行結果と生成した明細
+#[derive(Debug, Serialize)]
+pub struct AllocationRunResponse {
+    pub run: AllocationRun,
+    pub entries: Vec<AllocationLine>,
+}
+
+/// 一覧取得 (GET /allocation-rules)
+pub async fn list_allocation_rules(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<AllocationRule>>> {
+    auth_user.require(Resource::Allocation, Action::Read)?;
+
+    let rules = state.allocation_repository.find_rules().await?;
+
+    Ok(Json(rules))
+}
+
+/// 詳細取得 (GET /allocation-rules/{rid})
+pub async fn get_allocation_rule(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<Json<AllocationRuleResponse>> {
+    auth_user.require(Resource::Allocation, Action::Read)?;
+
+    let rule = find_rule(&state, id).await?;
+    let response = rule_response(&state, rule).await?;
+
+    Ok(Json(response))
+}
+
+/// 登録 (POST /allocation-rules)
+pub async fn create_allocation_rule(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<AllocationRuleRequest>,
+) -> Result<(StatusCode, Json<AllocationRuleResponse>)> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::Allocation, Action::Create)?;
+
+    let param = rule_param(&state, payload, user_id).await?;
+    let rule = state.allocation_repository.create_rule(param).await?;
+    let response = rule_response(&state, rule).await?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// 更新 (PUT /allocation-rules/{rid})
+///
+/// 生成済みの明細は再実行するまで変わらない
+pub async fn update_allocation_rule(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+    Json(payload): Json<AllocationRuleRequest>,
+) -> Result<Json<AllocationRuleResponse>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::Allocation, Action::Update)?;
+
+    let param = rule_param(&state, payload, user_id).await?;
+    let rule = state.allocation_repository.update_rule(id, param).await?;
+    let response = rule_response(&state, rule).await?;
+
+    Ok(Json(response))
+}
+
+/// 削除 (DELETE /allocation-rules/{rid})
+///
+/// 有効な実行がある場合は先に取り消す必要がある
+pub async fn delete_allocation_rule(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<StatusCode> {
+    auth_user.require(Resource::Allocation, Action::Delete)?;
+
+    let rule = find_rule(&state, id).await?;
+    let runs = state.allocation_repository.find_runs(rule.id).await?;
+    if runs.iter().any(|run| run.reversed_at.is_none()) {
+        return Err(AppError::Validation(format!(
+            "Allocation rule '{}' has allocated entries; reverse them first",
+            rule.name
+        )));
+    }
+
+    state.allocation_repository.delete_rule(id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 実行 (POST /allocation-rules/{rid}/run)
+///
+/// 前回の実行で生成した明細を置き換えるため、何度実行しても配賦は二重にならない
+pub async fn run_allocation(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<Json<AllocationRunResponse>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::Allocation, Action::Run)?;
+
+    let (run, entries) = state.allocation_repository.run(id, user_id).await?;
+
+    tracing::info!(
+        "Allocated {} of rule {} to {} entries",
+        run.total_amount,
+        run.rule_id,
+        entries.len()
+    );
+
+    Ok(Json(AllocationRunResponse { run, entries }))
+}
+
+/// 取り消し (POST /allocation-runs/{run_id}/reverse)
+pub async fn reverse_allocation(
+    State(state): State<AppState>,
+    Path(run_id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<Json<AllocationRun>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::Allocation, Action::Run)?;
+
+    let run = state
+        .allocation_repository
+        .find_run(run_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Allocation run {} not found",
+            run_id
+        )))?;
+
+    let run = state.allocation_repository.reverse(run.id, user_id).await?;
+
+    Ok(Json(run))
+}
+
+async fn find_rule(state: &AppState, id: Uuid) -> Result<AllocationRule> {
+    state
+        .allocation_repository
+        .find_rule(id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Allocation rule {} not found",
+            id
+        )))
+}
+
+async fn rule_response(state: &AppState, rule: AllocationRule) -> Result<AllocationRuleResponse> {
+    let targets = state.allocation_repository.find_targets(rule.id).await?;
+    let runs = state.allocation_repository.find_runs(rule.id).await?;
+
+    Ok(AllocationRuleResponse {
+        rule,
+        targets,
+        runs,
+    })
+}
+
+/// リクエストを検証し、ルールの登録内容に変換する
+async fn rule_param(
+    state: &AppState,
+    payload: AllocationRuleRequest,
+    user_id: Uuid,
+) -> Result<AllocationRuleParam> {
+    if payload.name.trim().is_empty() {
+        return Err(AppError::Validation("name is required".to_string()));
+    }
+
+    // JobPlan は Project の予算の消化として Job ごとに登録するため配賦の対象にしない
+    if payload.scenario.is_computed() || payload.scenario == Scenario::JobPlan {
+        return Err(AppError::Validation(format!(
+            "{:?} cannot be allocated",
+            payload.scenario
+        )));
+    }
+
+    let target_account_item_id = payload
+        .target_account_item_id
+        .unwrap_or(payload.source_account_item_id);
+    let items = state.account_item_repository.find_all().await?;
+    for account_item_id in [payload.source_account_item_id, target_account_item_id] {
+        match items.iter().find(|item| item.id == account_item_id) {
+            Some(item) if item.is_active => {}
+            Some(item) => {
+                return Err(AppError::Validation(format!(
+                    "Account item '{}' is inactive",
+                    item.name
+                )));
+            }
+            None => {
+                return Err(AppError::Validation(format!(
+                    "Account item {} not found",
+                    account_item_id
+                )));
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for target in &payload.targets {
+        if target.project_id == payload.source_project_id {
+            return Err(AppError::Validation(
+                "The source project cannot be an allocation target".to_string(),
+            ));
+        }
+        if !seen.insert(target.project_id) {
+            return Err(AppError::Validation(format!(
+                "Duplicate allocation target {}",
+                target.project_id
+            )));
+        }
+        if target.ratio.is_some_and(|ratio| ratio <= Decimal::ZERO) {
+            return Err(AppError::Validation(
+                "ratio must be greater than zero".to_string(),
+            ));
+        }
+    }
+
+    if payload.driver == AllocationDriver::FixedRatio
+        && (payload.targets.is_empty() || payload.targets.iter().any(|t| t.ratio.is_none()))
+    {
+        return Err(AppError::Validation(
+            "FixedRatio requires targets with a ratio".to_string(),
+        ));
+    }
+
+    for project_id in std::iter::once(payload.source_project_id).chain(seen.iter().copied()) {
+        state
+            .project_repository
+            .find_by_id(project_id)
+            .await?
+            .ok_or(AppError::Validation(format!(
+                "Project {} not found",
+                project_id
+            )))?;
+    }
+
+    Ok(AllocationRuleParam {
+        name: payload.name,
+        description: payload.description,
+        source_project_id: payload.source_project_id,
+        source_account_item_id: payload.source_account_item_id,
+        target_account_item_id,
+        scenario: payload.scenario,
+        period: state.fiscal_calendar.range(payload.period)?,
+        driver: payload.driver,
+        targets: payload.targets,
+        user_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::{
+        domains::{
+            allocation::{AllocationDriver, AllocationRuleParam, AllocationTarget},
+            fiscal::PeriodRange,
+            pl_entry::{SaveProjectEntriesParam, Scenario, UpsertPlEntryParam},
+            report::PlScope,
+            user::UserRole,
+        },
+        test_support::{account_item_id, app_state, auth_user, insert_project, insert_user},
+    };
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[sqlx::test]
+    async fn plan_allocations_keep_exec_plan_adjust_in_sync(pool: sqlx::PgPool) {
+        let state = app_state(pool.clone());
+        let admin = insert_user(&pool, UserRole::Admin).await;
+        let source = insert_project(&pool, admin).await;
+        let target = insert_project(&pool, admin).await;
+        let item = account_item_id(&pool).await;
+        let month = NaiveDate::from_ymd_opt(2026, 4, 1).unwrap();
+
+        state
+            .pl_entry_repository
+            .save_project_entries(SaveProjectEntriesParam {
+                project_id: source,
+                scenario: Scenario::MasterPlan,
+                entries: vec![UpsertPlEntryParam {
+                    account_item_id: item,
+                    date: month,
+                    amount: dec("1000"),
+                    currency: None,
+                    description: None,
+                }],
+                comment: None,
+                user_id: admin,
+            })
+            .await
+            .unwrap();
+        let rule = state
+            .allocation_repository
+            .create_rule(AllocationRuleParam {
+                name: "Rule".to_string(),
+                description: None,
+                source_project_id: source,
+                source_account_item_id: item,
+                target_account_item_id: item,
+                scenario: Scenario::MasterPlan,
+                period: PeriodRange {
+                    start: month,
+                    end: month,
+                },
+                driver: AllocationDriver::FixedRatio,
+                targets: vec![AllocationTarget {
+                    project_id: target,
+                    ratio: Some(Decimal::ONE),
+                }],
+                user_id: admin,
+            })
+            .await
+            .unwrap();
+
+        let exec_plan_adjust = |project_id| {
+            let state = state.clone();
+            async move {
+                state
+                    .pl_entry_repository
+                    .find_monthly_totals(
+                        PlScope::Project(project_id),
+                        Scenario::ExecPlanAdjust,
+                        None,
+                    )
+                    .await
+                    .unwrap()
+                    .iter()
+                    .map(|row| row.amount)
+                    .sum::<Decimal>()
+            }
+        };
+
+        let Json(response) = run_allocation(
+            State(state.clone()),
+            Path(rule.id),
+            auth_user(admin, UserRole::Admin),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.run.total_amount, dec("1000"));
+        assert_eq!(exec_plan_adjust(target).await, dec("1000"));
+        assert_eq!(exec_plan_adjust(source).await, dec("0"));
+
+        let Json(run) = reverse_allocation(
+            State(state.clone()),
+            Path(response.run.id),
+            auth_user(admin, UserRole::Admin),
+        )
+        .await
+        .unwrap();
+        assert!(run.reversed_at.is_some());
+        assert_eq!(exec_plan_adjust(target).await, dec("0"));
+        assert_eq!(exec_plan_adjust(source).await, dec("1000"));
+    }
+}
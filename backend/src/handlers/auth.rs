@@ -1,15 +1,21 @@
-use argon2::{Argon2, PasswordHash, PasswordVerifier};
-use axum::{Json, extract::State};
-use chrono::{Duration, Utc};
+use axum::{Json, extract::State, http::StatusCode};
+use chrono::Utc;
 use jsonwebtoken::{EncodingKey, Header, encode};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
     AppState,
-    domains::user::User,
+    domains::{
+        login_attempt::{CreateLoginEventParam, LoginOutcome},
+        mailer::Mail,
+        session::SecretToken,
+        user::{
+            EMAIL_MAX_LENGTH, User, hash_password, validate_new_password, verify_dummy_password,
+        },
+    },
     error::{AppError, Result},
-    extractors::AuthUser,
+    extractors::{AuthUser, ClientInfo},
 };
 
 #[derive(Debug, Clone, Deserialize)]
@@ -18,9 +24,40 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+/// トークン更新リクエスト
+#[derive(Debug, Clone, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+/// パスワード変更リクエスト
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// パスワード再設定の依頼
+#[derive(Debug, Clone, Deserialize)]
+pub struct PasswordResetRequest {
+    pub email: String,
+}
+
+/// パスワード再設定リクエスト
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct LoginResponse {
+    /// アクセストークン
     pub token: String,
+    /// アクセストークンの有効期間（秒）
+    pub expires_in: i64,
+    /// 一度使うと無効になるため、更新のたびに置き換える
+    pub refresh_token: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +67,8 @@ pub struct Claims {
     pub iat: usize,
     pub exp: usize,
     pub name: String,
+    /// セッションID
+    pub sid: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -56,39 +95,322 @@ impl From<User> for UserResponse {
 }
 
 /// ログイン (POST /login)
+///
+/// 失敗が続いたアカウントは一定時間ロックし、成否にかかわらず試行を記録する
 pub async fn login(
     State(state): State<AppState>,
+    client: ClientInfo,
     Json(payload): Json<LoginRequest>,
 ) -> Result<Json<LoginResponse>> {
+    let email = login_email(&payload.email);
+
+    if let Some(locked_until) = state.login_attempt_repository.locked_until(&email).await? {
+        record_login(&state, &client, &email, None, LoginOutcome::Locked).await?;
+        return Err(AppError::TooManyRequests(format!(
+            "Too many failed login attempts; try again after {}",
+            locked_until.to_rfc3339()
+        )));
+    }
+
+    let Some(user) = state.user_repository.find_by_email(&email).await? else {
+        verify_dummy_password(&payload.password);
+        record_login_failure(&state, &client, &email, None, LoginOutcome::UnknownEmail).await?;
+        return Err(AppError::AuthError);
+    };
+
+    if !user.verify_password(&payload.password)? {
+        record_login_failure(
+            &state,
+            &client,
+            &email,
+            Some(user.id),
+            LoginOutcome::InvalidPassword,
+        )
+        .await?;
+        return Err(AppError::AuthError);
+    }
+
+    if !user.is_active {
+        tracing::warn!("Login refused for inactive user {}", user.id);
+        record_login(
+            &state,
+            &client,
+            &email,
+            Some(user.id),
+            LoginOutcome::Inactive,
+        )
+        .await?;
+        return Err(AppError::AuthError);
+    }
+
+    state
+        .login_attempt_repository
+        .clear_failures(&email)
+        .await?;
+    record_login(
+        &state,
+        &client,
+        &email,
+        Some(user.id),
+        LoginOutcome::Succeeded,
+    )
+    .await?;
+
+    let refresh_token = SecretToken::generate();
+    let session = state
+        .session_repository
+        .create(
+            user.id,
+            &refresh_token.hash,
+            Utc::now() + state.refresh_token_ttl,
+        )
+        .await?;
+
+    let response = issue_tokens(&state, &user, session.id, refresh_token)?;
+
+    Ok(Json(response))
+}
+
+/// アクセストークンの更新 (POST /token/refresh)
+///
+/// リフレッシュトークンは使い捨てで、新しいリフレッシュトークンとあわせて返す
+pub async fn refresh_token(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshTokenRequest>,
+) -> Result<Json<LoginResponse>> {
+    let refresh_token = SecretToken::generate();
+    let session = state
+        .session_repository
+        .rotate(
+            &SecretToken::hash(&payload.refresh_token),
+            &refresh_token.hash,
+            Utc::now() + state.refresh_token_ttl,
+        )
+        .await?;
+
+    // 権限の変更を反映するため、ユーザーは毎回読み直す
     let user = state
         .user_repository
-        .find_by_email(&payload.email)
+        .find_by_id(session.user_id)
         .await?
         .ok_or(AppError::AuthError)?;
 
-    let parsed_hash = PasswordHash::new(&user.password_hash)
-        .map_err(|e| anyhow::anyhow!("Invalid password hash in DB: {}", e))?;
+    if !user.is_active {
+        state.session_repository.revoke(session.id).await?;
+        return Err(AppError::AuthError);
+    }
+
+    let response = issue_tokens(&state, &user, session.id, refresh_token)?;
 
-    let is_valid = Argon2::default()
-        .verify_password(payload.password.as_bytes(), &parsed_hash)
-        .is_ok();
+    Ok(Json(response))
+}
 
-    if !is_valid {
-        return Err(AppError::AuthError);
+/// ログアウト (POST /logout)
+///
+/// セッションを取り消し、発行済みのアクセストークンとリフレッシュトークンを無効にする
+pub async fn logout(State(state): State<AppState>, auth_user: AuthUser) -> Result<StatusCode> {
+    let session_id = auth_user.session_id()?;
+
+    state.session_repository.revoke(session_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// パスワード変更 (PUT /me/password)
+///
+/// 現在のパスワードが必要で、変更後はこのセッション以外をすべて取り消す
+pub async fn change_password(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<StatusCode> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    let user = state
+        .user_repository
+        .find_by_id(user_id)
+        .await?
+        .ok_or(AppError::AuthError)?;
+
+    if !user.verify_password(&payload.current_password)? {
+        return Err(AppError::Validation(
+            "Current password is incorrect".to_string(),
+        ));
+    }
+    validate_new_password(&payload.new_password)?;
+
+    let password_hash = hash_password(&payload.new_password)?;
+    state
+        .user_repository
+        .update_password(user_id, &password_hash, user_id)
+        .await?;
+    let revoked = state
+        .session_repository
+        .revoke_others(user_id, auth_user.session_id()?)
+        .await?;
+
+    tracing::info!(
+        "User {} changed password and revoked {} other session(s)",
+        user_id,
+        revoked
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// パスワード再設定の依頼 (POST /password/forgot)
+///
+/// 登録済みのメールアドレスかどうかを推測されないよう、結果によらず 202 を返す
+pub async fn request_password_reset(
+    State(state): State<AppState>,
+    Json(payload): Json<PasswordResetRequest>,
+) -> Result<StatusCode> {
+    let Some(user) = state
+        .user_repository
+        .find_by_email(&login_email(&payload.email))
+        .await?
+        .filter(|user| user.is_active)
+    else {
+        tracing::info!("Password reset requested for unknown or inactive email");
+        return Ok(StatusCode::ACCEPTED);
+    };
+
+    let token = SecretToken::generate();
+    state
+        .password_reset_repository
+        .create(user.id, &token.hash, Utc::now() + state.password_reset_ttl)
+        .await?;
+
+    let mail = Mail {
+        to: user.email.clone(),
+        subject: "パスワード再設定のご案内".to_string(),
+        body: format!(
+            "{} 様\n\n以下のURLから{}分以内にパスワードを再設定してください。\n{}/reset-password?token={}\n\nお心当たりがない場合は、このメールを破棄してください。\n",
+            user.name,
+            state.password_reset_ttl.num_minutes(),
+            state.app_url.trim_end_matches('/'),
+            token.token
+        ),
+    };
+
+    // 送信にかかる時間から登録済みかどうかを推測されないよう、送信を待たずに応答する。失敗はログにだけ残す
+    let mailer = state.mailer.clone();
+    let user_id = user.id;
+    tokio::spawn(async move {
+        if let Err(e) = mailer.send(mail).await {
+            tracing::error!("Password reset mail to user {} failed: {:?}", user_id, e);
+        }
+    });
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// パスワード再設定 (POST /password/reset)
+///
+/// トークンは一度だけ使え、再設定後はすべてのセッションを取り消す
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<StatusCode> {
+    validate_new_password(&payload.new_password)?;
+
+    let password_hash = hash_password(&payload.new_password)?;
+    let user_id = state
+        .password_reset_repository
+        .consume(&SecretToken::hash(&payload.token), &password_hash)
+        .await?;
+    let revoked = state.session_repository.revoke_all(user_id).await?;
+
+    // 失敗が続いてロックされたアカウントも、再設定したパスワードですぐにログインできるようにする
+    if let Some(user) = state.user_repository.find_by_id(user_id).await? {
+        state
+            .login_attempt_repository
+            .clear_failures(&user.email.to_lowercase())
+            .await?;
+    }
+
+    tracing::info!(
+        "User {} reset password and revoked {} session(s)",
+        user_id,
+        revoked
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 照合と試行の記録に使うメールアドレス
+///
+/// 正規化したうえで、試行履歴の記録で桁あふれしないよう email 列の桁数に切り詰める
+fn login_email(email: &str) -> String {
+    email
+        .trim()
+        .to_lowercase()
+        .chars()
+        .take(EMAIL_MAX_LENGTH)
+        .collect()
+}
+
+/// ログインの試行を記録する
+async fn record_login(
+    state: &AppState,
+    client: &ClientInfo,
+    email: &str,
+    user_id: Option<Uuid>,
+    outcome: LoginOutcome,
+) -> Result<()> {
+    state
+        .login_attempt_repository
+        .record_event(CreateLoginEventParam {
+            email: email.to_string(),
+            user_id,
+            outcome,
+            ip_address: client.ip.clone(),
+            user_agent: client.user_agent.clone(),
+        })
+        .await
+}
+
+/// 失敗を数えたうえで試行を記録する
+async fn record_login_failure(
+    state: &AppState,
+    client: &ClientInfo,
+    email: &str,
+    user_id: Option<Uuid>,
+    outcome: LoginOutcome,
+) -> Result<()> {
+    if let Some(locked_until) = state
+        .login_attempt_repository
+        .record_failure(email, state.lockout_policy)
+        .await?
+    {
+        tracing::warn!(
+            "Locked login for {} until {} after repeated failures (last from {})",
+            email,
+            locked_until,
+            client.ip
+        );
     }
 
-    let issue_at = Utc::now().timestamp();
-    let expire_at = Utc::now()
-        .checked_add_signed(Duration::hours(24))
-        .expect("valid timestamp")
-        .timestamp();
+    record_login(state, client, email, user_id, outcome).await
+}
+
+/// セッションに紐づくアクセストークンを発行する
+fn issue_tokens(
+    state: &AppState,
+    user: &User,
+    session_id: Uuid,
+    refresh_token: SecretToken,
+) -> Result<LoginResponse> {
+    let issue_at = Utc::now();
+    let expire_at = issue_at + state.access_token_ttl;
 
     let claims = Claims {
         sub: user.id.to_string(),
-        role: user.role,
-        iat: issue_at as usize,
-        exp: expire_at as usize,
+        role: user.role.clone(),
+        iat: issue_at.timestamp() as usize,
+        exp: expire_at.timestamp() as usize,
         name: user.name.to_string(),
+        sid: session_id.to_string(),
     };
 
     let token = encode(
@@ -98,7 +420,11 @@ pub async fn login(
     )
     .map_err(|e| anyhow::anyhow!("Token creation failed: {}", e))?;
 
-    Ok(Json(LoginResponse { token }))
+    Ok(LoginResponse {
+        token,
+        expires_in: state.access_token_ttl.num_seconds(),
+        refresh_token: refresh_token.token,
+    })
 }
 
 /// ログインユーザーの詳細取得 (GET /me)
@@ -0,0 +1,171 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    domains::{
+        exchange_rate::{
+            ExchangeRate, RateType, ScenarioRateType, UpsertExchangeRateParam, normalize_currency,
+        },
+        permission::{Action, Resource},
+        pl_entry::Scenario,
+    },
+    error::{AppError, Result},
+    extractors::AuthUser,
+};
+
+/// 為替レート一覧のクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct ExchangeRateQuery {
+    pub rate_type: Option<RateType>,
+    pub currency: Option<String>,
+}
+
+/// 為替レート一覧
+#[derive(Debug, Serialize)]
+pub struct ExchangeRateList {
+    /// レートの換算先の通貨
+    pub reporting_currency: String,
+    pub rates: Vec<ExchangeRate>,
+}
+
+/// 為替レート登録リクエスト
+#[derive(Debug, Deserialize)]
+pub struct UpsertExchangeRateRequest {
+    pub rate_type: RateType,
+    pub currency: String,
+    /// 適用開始月（月次期間の初日に揃える）
+    pub month: NaiveDate,
+    pub rate: Decimal,
+}
+
+/// シナリオのレート種類変更リクエスト
+#[derive(Debug, Deserialize)]
+pub struct UpdateScenarioRateTypeRequest {
+    pub rate_type: RateType,
+}
+
+/// 一覧取得 (GET /exchange-rates?rate_type=...&currency=...)
+pub async fn list_exchange_rates(
+    State(state): State<AppState>,
+    Query(query): Query<ExchangeRateQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<ExchangeRateList>> {
+    auth_user.require(Resource::ExchangeRate, Action::Read)?;
+
+    let currency = query
+        .currency
+        .as_deref()
+        .map(normalize_currency)
+        .transpose()?;
+
+    let rates = state
+        .exchange_rate_repository
+        .find_all(query.rate_type, currency)
+        .await?;
+
+    Ok(Json(ExchangeRateList {
+        reporting_currency: state.reporting_currency.clone(),
+        rates,
+    }))
+}
+
+/// 登録・更新 (PUT /exchange-rates)
+///
+/// 同じ種類・通貨・月のレートがあれば置き換える
+pub async fn upsert_exchange_rate(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<UpsertExchangeRateRequest>,
+) -> Result<Json<ExchangeRate>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::ExchangeRate, Action::Update)?;
+
+    let currency = normalize_currency(&payload.currency)?;
+    if currency == state.reporting_currency {
+        return Err(AppError::Validation(format!(
+            "{} is the reporting currency and needs no exchange rate",
+            currency
+        )));
+    }
+
+    if payload.rate <= Decimal::ZERO {
+        return Err(AppError::Validation(
+            "rate must be greater than zero".to_string(),
+        ));
+    }
+
+    let param = UpsertExchangeRateParam {
+        rate_type: payload.rate_type,
+        currency,
+        month: state.fiscal_calendar.normalize(payload.month),
+        rate: payload.rate,
+        user_id,
+    };
+
+    let rate = state.exchange_rate_repository.upsert(param).await?;
+
+    Ok(Json(rate))
+}
+
+/// 削除 (DELETE /exchange-rates/{rid})
+pub async fn delete_exchange_rate(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<StatusCode> {
+    auth_user.require(Resource::ExchangeRate, Action::Delete)?;
+
+    state.exchange_rate_repository.delete(id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// シナリオごとのレート種類 (GET /exchange-rates/scenarios)
+pub async fn list_scenario_rate_types(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<ScenarioRateType>>> {
+    auth_user.require(Resource::ExchangeRate, Action::Read)?;
+
+    let rate_types = state
+        .exchange_rate_repository
+        .find_scenario_rate_types()
+        .await?;
+
+    Ok(Json(rate_types))
+}
+
+/// シナリオのレート種類の変更 (PUT /exchange-rates/scenarios/{scenario})
+pub async fn update_scenario_rate_type(
+    State(state): State<AppState>,
+    Path(scenario): Path<Scenario>,
+    auth_user: AuthUser,
+    Json(payload): Json<UpdateScenarioRateTypeRequest>,
+) -> Result<Json<ScenarioRateType>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::ExchangeRate, Action::Update)?;
+
+    let rate_type = state
+        .exchange_rate_repository
+        .update_scenario_rate_type(scenario, payload.rate_type, user_id)
+        .await?;
+
+    tracing::info!(
+        "Scenario {:?} now converts with {:?} rates (changed by {})",
+        scenario,
+        payload.rate_type,
+        user_id
+    );
+
+    Ok(Json(rate_type))
+}
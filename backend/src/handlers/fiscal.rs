@@ -0,0 +1,206 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    domains::{
+        fiscal::{
+            ChangePeriodStatusParam, FISCAL_YEARS, FiscalMonth, FiscalMonthStatus, FiscalPeriod,
+            FiscalPeriodEvent, PeriodStatus,
+        },
+        permission::{Action, Resource},
+    },
+    error::{AppError, Result},
+    extractors::AuthUser,
+};
+
+/// 日付から会計期間を求めるクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct ResolveFiscalPeriodQuery {
+    pub date: NaiveDate,
+}
+
+/// 月次締め・締め解除リクエスト
+#[derive(Debug, Deserialize)]
+pub struct ChangePeriodStatusRequest {
+    pub reason: Option<String>,
+}
+
+/// 月次期間の締め状況
+#[derive(Debug, Serialize)]
+pub struct FiscalPeriodStatusResponse {
+    #[serde(flatten)]
+    pub period: FiscalMonthStatus,
+    pub history: Vec<FiscalPeriodEvent>,
+}
+
+/// 年度の月次期間一覧 (GET /fiscal-years/{fy}/periods)
+pub async fn list_fiscal_periods(
+    State(state): State<AppState>,
+    Path(fiscal_year): Path<i32>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<FiscalMonthStatus>>> {
+    auth_user.require(Resource::FiscalPeriod, Action::Read)?;
+
+    let months = fiscal_months(&state, fiscal_year)?;
+    let registered = state
+        .fiscal_period_repository
+        .find_by_year(fiscal_year)
+        .await?;
+
+    let periods = months
+        .into_iter()
+        .map(|month| {
+            let period = registered.iter().find(|p| p.period == month.period as i32);
+            FiscalMonthStatus::new(month, period)
+        })
+        .collect();
+
+    Ok(Json(periods))
+}
+
+/// 日付が属する会計期間 (GET /fiscal-calendar/resolve?date=...)
+pub async fn resolve_fiscal_period(
+    State(state): State<AppState>,
+    Query(query): Query<ResolveFiscalPeriodQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<FiscalMonth>> {
+    auth_user.require(Resource::FiscalPeriod, Action::Read)?;
+
+    Ok(Json(state.fiscal_calendar.month_of(query.date)))
+}
+
+/// 締め状況取得 (GET /fiscal-years/{fy}/periods/{period})
+pub async fn get_fiscal_period(
+    State(state): State<AppState>,
+    Path((fiscal_year, period)): Path<(i32, i32)>,
+    auth_user: AuthUser,
+) -> Result<Json<FiscalPeriodStatusResponse>> {
+    auth_user.require(Resource::FiscalPeriod, Action::Read)?;
+
+    let month = fiscal_month(&state, fiscal_year, period)?;
+    let registered = state
+        .fiscal_period_repository
+        .find_by_period(fiscal_year, period)
+        .await?;
+    let history = state
+        .fiscal_period_repository
+        .find_events(fiscal_year, period)
+        .await?;
+
+    Ok(Json(FiscalPeriodStatusResponse {
+        period: FiscalMonthStatus::new(month, registered.as_ref()),
+        history,
+    }))
+}
+
+/// 月次締め (POST /fiscal-years/{fy}/periods/{period}/close)
+pub async fn close_fiscal_period(
+    State(state): State<AppState>,
+    Path((fiscal_year, period)): Path<(i32, i32)>,
+    auth_user: AuthUser,
+    Json(payload): Json<ChangePeriodStatusRequest>,
+) -> Result<Json<FiscalPeriod>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::FiscalPeriod, Action::Lock)?;
+
+    let month = fiscal_month(&state, fiscal_year, period)?;
+    register_months(&state, fiscal_year).await?;
+
+    let param = ChangePeriodStatusParam {
+        month,
+        status: PeriodStatus::Closed,
+        reason: payload.reason,
+        acted_by: user_id,
+    };
+
+    let period = state.fiscal_period_repository.change_status(param).await?;
+
+    tracing::info!(
+        "Fiscal period FY{} M{:02} closed by {}",
+        period.fiscal_year,
+        period.period,
+        user_id
+    );
+
+    Ok(Json(period))
+}
+
+/// 締め解除 (POST /fiscal-years/{fy}/periods/{period}/reopen)
+///
+/// 管理者のみ実行可能。理由は必須
+pub async fn reopen_fiscal_period(
+    State(state): State<AppState>,
+    Path((fiscal_year, period)): Path<(i32, i32)>,
+    auth_user: AuthUser,
+    Json(payload): Json<ChangePeriodStatusRequest>,
+) -> Result<Json<FiscalPeriod>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::FiscalPeriod, Action::Unlock)?;
+
+    let reason = match payload.reason {
+        Some(r) if !r.trim().is_empty() => r,
+        _ => return Err(AppError::Validation("reason is required".to_string())),
+    };
+
+    let month = fiscal_month(&state, fiscal_year, period)?;
+    register_months(&state, fiscal_year).await?;
+
+    let param = ChangePeriodStatusParam {
+        month,
+        status: PeriodStatus::Open,
+        reason: Some(reason),
+        acted_by: user_id,
+    };
+
+    let period = state.fiscal_period_repository.change_status(param).await?;
+
+    tracing::warn!(
+        "Fiscal period FY{} M{:02} reopened by {}",
+        period.fiscal_year,
+        period.period,
+        user_id
+    );
+
+    Ok(Json(period))
+}
+
+/// 年度内の全ての月次期間
+fn fiscal_months(state: &AppState, fiscal_year: i32) -> Result<Vec<FiscalMonth>> {
+    if !FISCAL_YEARS.contains(&fiscal_year) {
+        return Err(AppError::BadRequest(format!(
+            "Fiscal year must be between {} and {}",
+            FISCAL_YEARS.start(),
+            FISCAL_YEARS.end()
+        )));
+    }
+
+    state.fiscal_calendar.months(fiscal_year)
+}
+
+/// 年度と月番号から月次期間を求める
+fn fiscal_month(state: &AppState, fiscal_year: i32, period: i32) -> Result<FiscalMonth> {
+    if !(1..=12).contains(&period) {
+        return Err(AppError::BadRequest(format!(
+            "Invalid fiscal period: {}",
+            period
+        )));
+    }
+
+    Ok(fiscal_months(state, fiscal_year)?[period as usize - 1])
+}
+
+/// 締め・締め解除の前に、年度の月次期間を未登録のものだけ登録する
+async fn register_months(state: &AppState, fiscal_year: i32) -> Result<()> {
+    state
+        .fiscal_period_repository
+        .ensure_months(fiscal_months(state, fiscal_year)?)
+        .await
+}
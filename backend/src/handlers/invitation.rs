@@ -0,0 +1,109 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    domains::{
+        invitation::{CreateInvitationParam, Invitation},
+        permission::{Action, Resource},
+        session::SecretToken,
+        user::{UserRole, normalize_email},
+    },
+    error::{AppError, Result},
+    extractors::AuthUser,
+};
+
+/// 招待リクエスト
+#[derive(Debug, Deserialize)]
+pub struct CreateInvitationRequest {
+    pub email: String,
+    #[serde(default)]
+    pub role: UserRole,
+}
+
+/// 発行した招待と招待トークン（トークンはこのレスポンスでしか返さない）
+#[derive(Debug, Serialize)]
+pub struct CreateInvitationResponse {
+    #[serde(flatten)]
+    pub invitation: Invitation,
+    pub token: String,
+}
+
+/// 一覧取得 (GET /invitations)
+pub async fn list_invitations(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<Invitation>>> {
+    auth_user.require(Resource::User, Action::Read)?;
+
+    let invitations = state.invitation_repository.find_all().await?;
+
+    Ok(Json(invitations))
+}
+
+/// 発行 (POST /invitations)
+pub async fn create_invitation(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateInvitationRequest>,
+) -> Result<(StatusCode, Json<CreateInvitationResponse>)> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::User, Action::Create)?;
+
+    let email = normalize_email(&payload.email)?;
+    if state.user_repository.find_by_email(&email).await?.is_some() {
+        return Err(AppError::Validation(format!(
+            "User with email '{}' already exists",
+            email
+        )));
+    }
+
+    let token = SecretToken::generate();
+    let invitation = state
+        .invitation_repository
+        .create(CreateInvitationParam {
+            email,
+            role: payload.role,
+            token_hash: token.hash,
+            expires_at: Utc::now() + state.invitation_ttl,
+            invited_by: user_id,
+        })
+        .await?;
+
+    tracing::info!(
+        "Invited {} as {} by {}",
+        invitation.email,
+        invitation.role,
+        user_id
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateInvitationResponse {
+            invitation,
+            token: token.token,
+        }),
+    ))
+}
+
+/// 取り消し (DELETE /invitations/{iid})
+pub async fn revoke_invitation(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<StatusCode> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::User, Action::Delete)?;
+
+    state.invitation_repository.revoke(id, user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,6 +1,6 @@
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
 };
 use serde::Deserialize;
@@ -8,11 +8,23 @@ use uuid::Uuid;
 
 use crate::{
     AppState,
-    domains::job::{CreateJobParam, Job, UpdateJobParam},
+    domains::{
+        job::{CreateJobParam, Job, JobAssignee, UpdateJobParam},
+        permission::{Action, Ownership, Resource},
+    },
     error::{AppError, Result},
     extractors::AuthUser,
+    handlers::project::authorize_project,
 };
 
+/// 一覧取得のクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct JobQuery {
+    /// true なら自分が所有者か担当者のJobに絞り込む
+    #[serde(default)]
+    pub mine: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateJobRequest {
     pub service_id: Uuid,
@@ -24,6 +36,7 @@ pub struct CreateJobRequest {
     #[serde(default = "default_status")]
     pub status: String,
 
+    /// 省略時は作成者
     pub owner_id: Option<Uuid>,
 }
 
@@ -40,15 +53,29 @@ pub struct UpdateJobRequest {
     pub updated_by: Option<Uuid>,
 }
 
+/// 担当者の置き換えリクエスト
+#[derive(Debug, Deserialize)]
+pub struct ReplaceJobAssigneesRequest {
+    pub user_ids: Vec<Uuid>,
+}
+
 fn default_status() -> String {
     "Draft".to_string()
 }
 
 pub async fn list_jobs(
     State(state): State<AppState>,
-    _auth_user: AuthUser,
+    Query(query): Query<JobQuery>,
+    auth_user: AuthUser,
 ) -> Result<Json<Vec<Job>>> {
-    let jobs = state.job_repository.find_all().await?;
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::Job, Action::Read)?;
+
+    let jobs = state
+        .job_repository
+        .find_all(query.mine.then_some(user_id))
+        .await?;
     Ok(Json(jobs))
 }
 
@@ -59,6 +86,20 @@ pub async fn create_job(
 ) -> Result<Json<Job>> {
     let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
 
+    auth_user.require(Resource::Job, Action::Create)?;
+
+    // Projectに紐づけるとそのProjectのPL明細を書き込めるため、Projectを更新できる場合に限る
+    if let Some(project_id) = payload.project_id {
+        authorize_project(
+            &state,
+            &auth_user,
+            project_id,
+            Resource::Project,
+            Action::Update,
+        )
+        .await?;
+    }
+
     let param = CreateJobParam {
         service_id: payload.service_id,
         project_id: payload.project_id,
@@ -66,7 +107,7 @@ pub async fn create_job(
         title: payload.title,
         description: payload.description,
         status: payload.status,
-        owner_id: payload.owner_id,
+        owner_id: payload.owner_id.or(Some(user_id)),
         created_by: user_id,
     };
 
@@ -78,8 +119,10 @@ pub async fn create_job(
 pub async fn get_job(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
 ) -> Result<Json<Job>> {
+    auth_user.require(Resource::Job, Action::Read)?;
+
     let job = state.job_repository.find_by_id(id).await?;
 
     match job {
@@ -89,6 +132,8 @@ pub async fn get_job(
 }
 
 // 更新 (PATCH /jobs/{id})
+//
+// 所有者・Projectの付け替えは担当者には許可せず、所有者かマネージャー以上に限る
 pub async fn update_job(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
@@ -97,6 +142,28 @@ pub async fn update_job(
 ) -> Result<Json<Job>> {
     let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
 
+    let job = authorize_job(&state, &auth_user, id, Resource::Job, Action::Update).await?;
+
+    let changes_owner = payload.owner_id.is_some_and(|o| job.owner_id != Some(o));
+    let new_project_id = payload.project_id.filter(|p| job.project_id != Some(*p));
+    if changes_owner || new_project_id.is_some() {
+        auth_user.require_owned(
+            Resource::Job,
+            Action::Update,
+            Ownership::of(user_id, job.owner_id, &[]),
+        )?;
+    }
+    if let Some(project_id) = new_project_id {
+        authorize_project(
+            &state,
+            &auth_user,
+            project_id,
+            Resource::Project,
+            Action::Update,
+        )
+        .await?;
+    }
+
     let param = UpdateJobParam {
         service_id: payload.service_id,
         project_id: payload.project_id,
@@ -117,9 +184,252 @@ pub async fn update_job(
 pub async fn delete_job(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
 ) -> Result<StatusCode> {
+    authorize_job(&state, &auth_user, id, Resource::Job, Action::Delete).await?;
+
     state.job_repository.delete(id).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// 担当者一覧 (GET /jobs/{jid}/assignees)
+pub async fn list_job_assignees(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<JobAssignee>>> {
+    auth_user.require(Resource::Job, Action::Read)?;
+
+    find_job(&state, id).await?;
+    let assignees = state.job_repository.find_assignees(id).await?;
+
+    Ok(Json(assignees))
+}
+
+/// 担当者の置き換え (PUT /jobs/{jid}/assignees)
+///
+/// 担当者は変更できないため、所有者かマネージャー以上が実行できる
+pub async fn replace_job_assignees(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+    Json(payload): Json<ReplaceJobAssigneesRequest>,
+) -> Result<Json<Vec<JobAssignee>>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    let job = find_job(&state, id).await?;
+    auth_user.require_owned(
+        Resource::Job,
+        Action::Update,
+        Ownership::of(user_id, job.owner_id, &[]),
+    )?;
+
+    let mut user_ids = payload.user_ids;
+    user_ids.sort();
+    user_ids.dedup();
+    for assignee_id in &user_ids {
+        state
+            .user_repository
+            .find_by_id(*assignee_id)
+            .await?
+            .ok_or(AppError::Validation(format!(
+                "User {} not found",
+                assignee_id
+            )))?;
+    }
+
+    let assignees = state
+        .job_repository
+        .replace_assignees(id, user_ids, user_id)
+        .await?;
+
+    Ok(Json(assignees))
+}
+
+async fn find_job(state: &AppState, id: Uuid) -> Result<Job> {
+    state
+        .job_repository
+        .find_by_id(id)
+        .await?
+        .ok_or(AppError::NotFound(format!("Job '{}' not found", id)))
+}
+
+/// Jobを取得し、所有者・担当者であれば権限が足りなくても許可される操作かどうかを確かめる
+pub(crate) async fn authorize_job(
+    state: &AppState,
+    auth_user: &AuthUser,
+    job_id: Uuid,
+    resource: Resource,
+    action: Action,
+) -> Result<Job> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    let job = find_job(state, job_id).await?;
+    let assignee_ids: Vec<Uuid> = state
+        .job_repository
+        .find_assignees(job_id)
+        .await?
+        .into_iter()
+        .map(|a| a.user_id)
+        .collect();
+
+    auth_user.require_owned(
+        resource,
+        action,
+        Ownership::of(user_id, job.owner_id, &assignee_ids),
+    )?;
+
+    Ok(job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domains::user::UserRole,
+        test_support::{
+            app_state, auth_user, insert_assignee, insert_job, insert_project, insert_service,
+            insert_user,
+        },
+    };
+
+    fn create_request(service_id: Uuid, project_id: Option<Uuid>) -> CreateJobRequest {
+        CreateJobRequest {
+            service_id,
+            project_id,
+            theme_id: None,
+            title: "Job".to_string(),
+            description: None,
+            status: default_status(),
+            owner_id: None,
+        }
+    }
+
+    fn update_request() -> UpdateJobRequest {
+        UpdateJobRequest {
+            service_id: None,
+            project_id: None,
+            theme_id: None,
+            title: None,
+            description: None,
+            status: None,
+            owner_id: None,
+            updated_by: None,
+        }
+    }
+
+    #[sqlx::test]
+    async fn general_users_create_jobs_only_under_their_own_projects(pool: sqlx::PgPool) {
+        let state = app_state(pool.clone());
+        let user = insert_user(&pool, UserRole::General).await;
+        let other = insert_user(&pool, UserRole::General).await;
+        let own_project = insert_project(&pool, user).await;
+        let foreign_project = insert_project(&pool, other).await;
+        let service = insert_service(&pool).await;
+
+        let result = create_job(
+            State(state.clone()),
+            auth_user(user, UserRole::General),
+            Json(create_request(service, Some(foreign_project))),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::Forbidden)));
+
+        let Json(job) = create_job(
+            State(state.clone()),
+            auth_user(user, UserRole::General),
+            Json(create_request(service, Some(own_project))),
+        )
+        .await
+        .unwrap();
+        assert_eq!(job.project_id, Some(own_project));
+
+        let Json(job) = create_job(
+            State(state),
+            auth_user(other, UserRole::Manager),
+            Json(create_request(service, Some(own_project))),
+        )
+        .await
+        .unwrap();
+        assert_eq!(job.project_id, Some(own_project));
+    }
+
+    #[sqlx::test]
+    async fn assignees_cannot_move_jobs_or_take_ownership(pool: sqlx::PgPool) {
+        let state = app_state(pool.clone());
+        let owner = insert_user(&pool, UserRole::General).await;
+        let assignee = insert_user(&pool, UserRole::General).await;
+        let project = insert_project(&pool, owner).await;
+        let assignee_project = insert_project(&pool, assignee).await;
+        let service = insert_service(&pool).await;
+        let job = insert_job(&pool, service, Some(project), owner).await;
+        insert_assignee(&pool, job, assignee).await;
+
+        let as_assignee = |payload| {
+            update_job(
+                State(state.clone()),
+                Path(job),
+                auth_user(assignee, UserRole::General),
+                Json(payload),
+            )
+        };
+
+        let result = as_assignee(UpdateJobRequest {
+            project_id: Some(assignee_project),
+            ..update_request()
+        })
+        .await;
+        assert!(matches!(result, Err(AppError::Forbidden)));
+
+        let result = as_assignee(UpdateJobRequest {
+            owner_id: Some(assignee),
+            ..update_request()
+        })
+        .await;
+        assert!(matches!(result, Err(AppError::Forbidden)));
+
+        // 付け替えを伴わない更新や、現在と同じ値の指定は担当者にも許可する
+        let Json(updated) = as_assignee(UpdateJobRequest {
+            title: Some("Renamed".to_string()),
+            project_id: Some(project),
+            owner_id: Some(owner),
+            ..update_request()
+        })
+        .await
+        .unwrap();
+        assert_eq!(updated.title, "Renamed");
+        assert_eq!(updated.project_id, Some(project));
+        assert_eq!(updated.owner_id, Some(owner));
+    }
+
+    #[sqlx::test]
+    async fn owners_move_jobs_only_into_projects_they_can_update(pool: sqlx::PgPool) {
+        let state = app_state(pool.clone());
+        let owner = insert_user(&pool, UserRole::General).await;
+        let other = insert_user(&pool, UserRole::General).await;
+        let project = insert_project(&pool, owner).await;
+        let own_project = insert_project(&pool, owner).await;
+        let foreign_project = insert_project(&pool, other).await;
+        let service = insert_service(&pool).await;
+        let job = insert_job(&pool, service, Some(project), owner).await;
+
+        let as_owner = |project_id| {
+            update_job(
+                State(state.clone()),
+                Path(job),
+                auth_user(owner, UserRole::General),
+                Json(UpdateJobRequest {
+                    project_id: Some(project_id),
+                    ..update_request()
+                }),
+            )
+        };
+
+        let result = as_owner(foreign_project).await;
+        assert!(matches!(result, Err(AppError::Forbidden)));
+
+        let Json(updated) = as_owner(own_project).await.unwrap();
+        assert_eq!(updated.project_id, Some(own_project));
+    }
+}
@@ -0,0 +1,56 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    domains::{
+        login_attempt::{LoginEvent, LoginEventFilter, LoginOutcome},
+        permission::{Action, Resource},
+    },
+    error::{AppError, Result},
+    extractors::AuthUser,
+};
+
+const MAX_LIMIT: i64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct LoginEventQuery {
+    pub user_id: Option<Uuid>,
+    pub email: Option<String>,
+    pub outcome: Option<LoginOutcome>,
+    /// 新しい順に取得する件数（省略時は100件）
+    pub limit: Option<i64>,
+}
+
+/// ログイン試行履歴の一覧取得 (GET /login-events)
+pub async fn list_login_events(
+    State(state): State<AppState>,
+    Query(query): Query<LoginEventQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<LoginEvent>>> {
+    auth_user.require(Resource::LoginEvent, Action::Read)?;
+
+    let limit = query.limit.unwrap_or(100);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(AppError::Validation(format!(
+            "limit must be between 1 and {}",
+            MAX_LIMIT
+        )));
+    }
+
+    let events = state
+        .login_attempt_repository
+        .find_events(LoginEventFilter {
+            user_id: query.user_id,
+            email: query.email.map(|email| email.trim().to_lowercase()),
+            outcome: query.outcome,
+            limit,
+        })
+        .await?;
+
+    Ok(Json(events))
+}
@@ -1,7 +1,17 @@
 pub mod account_item;
+pub mod allocation;
 pub mod auth;
+pub mod exchange_rate;
+pub mod fiscal;
+pub mod invitation;
 pub mod job;
+pub mod login_attempt;
+pub mod pl_entry;
+pub mod pl_sandbox;
+pub mod pl_snapshot;
 pub mod project;
+pub mod report;
+pub mod scenario_lock;
 pub mod segment;
 pub mod service;
 pub mod theme;
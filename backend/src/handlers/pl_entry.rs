@@ -0,0 +1,738 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    domains::{
+        account_item::AccountItem,
+        budget::BudgetLine,
+        exchange_rate::normalize_currency,
+        fiscal::{FiscalCalendar, FiscalPeriodSpec, PeriodRange},
+        job::Job,
+        permission::{Action, Resource},
+        pl_csv::{parse_plan_csv, write_plan_csv},
+        pl_entry::{
+            JobBudgetCheck, PlEntryDetail, SaveJobEntriesParam, SaveProjectEntriesParam, Scenario,
+            UpsertPlEntryParam,
+        },
+        pl_statement::PlStatement,
+    },
+    error::{AppError, Result},
+    extractors::AuthUser,
+    handlers::{job::authorize_job, project::authorize_project},
+};
+
+/// PL明細取得のクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct PlEntryQuery {
+    pub scenario: Scenario,
+    pub period: Option<FiscalPeriodSpec>,
+}
+
+/// CSV出力のクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct PlCsvExportQuery {
+    pub period: Option<FiscalPeriodSpec>,
+    /// 出力する明細の通貨。明細の通貨が1種類であれば省略できる
+    pub currency: Option<String>,
+}
+
+/// PL明細と損益計算書
+#[derive(Debug, Serialize)]
+pub struct PlEntryResponse {
+    pub scenario: Scenario,
+    pub entries: Vec<PlEntryDetail>,
+    pub statement: PlStatement,
+}
+
+/// Job単位のPL明細と予算超過の警告
+#[derive(Debug, Serialize)]
+pub struct JobPlEntryResponse {
+    #[serde(flatten)]
+    pub pl: PlEntryResponse,
+    /// JobPlanがProjectの予算残を超えた明細
+    pub budget_overruns: Vec<BudgetLine>,
+}
+
+/// CSV取り込み結果
+#[derive(Debug, Serialize)]
+pub struct PlCsvImportResponse {
+    pub imported: usize,
+}
+
+/// CSV取り込みのクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct PlCsvImportQuery {
+    /// 取り込む明細の通貨（省略時は報告通貨）
+    pub currency: Option<String>,
+    /// 保存した版に残すコメント
+    pub comment: Option<String>,
+}
+
+/// PL明細一括登録リクエスト
+#[derive(Debug, Deserialize)]
+pub struct BulkUpsertPlEntryRequest {
+    pub entries: Vec<UpsertPlEntryParam>,
+    /// 保存した版に残すコメント
+    pub comment: Option<String>,
+}
+
+/// 一覧取得 (GET /projects/{pid}/pl?scenario=...&period=...)
+pub async fn list_pl_entries(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    Query(query): Query<PlEntryQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<PlEntryResponse>> {
+    auth_user.require(Resource::PlEntry, Action::Read)?;
+
+    ensure_project_exists(&state, project_id).await?;
+
+    let range = query
+        .period
+        .map(|p| state.fiscal_calendar.range(p))
+        .transpose()?;
+    let entries = state
+        .pl_entry_repository
+        .find_details_by_project(project_id, query.scenario, range)
+        .await?;
+
+    let response = pl_entry_response(&state, query.scenario, range, entries).await?;
+
+    Ok(Json(response))
+}
+
+/// 一括登録・更新 (PUT /projects/{pid}/pl/{scenario})
+///
+/// 保存後の明細を版として記録する
+pub async fn upsert_pl_entries(
+    State(state): State<AppState>,
+    Path((project_id, scenario)): Path<(Uuid, Scenario)>,
+    auth_user: AuthUser,
+    Json(payload): Json<BulkUpsertPlEntryRequest>,
+) -> Result<Json<PlEntryResponse>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    authorize_project(
+        &state,
+        &auth_user,
+        project_id,
+        Resource::PlEntry,
+        Action::Update,
+    )
+    .await?;
+    ensure_project_level(scenario)?;
+
+    let items = state.account_item_repository.find_all().await?;
+    ensure_active_items(&items, &payload.entries)?;
+    let entries = normalize_entries(&state.fiscal_calendar, payload.entries)?;
+    ensure_convertible(&state, scenario, &entries).await?;
+
+    let param = SaveProjectEntriesParam {
+        project_id,
+        scenario,
+        entries,
+        comment: payload.comment.filter(|c| !c.trim().is_empty()),
+        user_id,
+    };
+    let version = state
+        .pl_entry_repository
+        .save_project_entries(param)
+        .await?;
+
+    tracing::info!(
+        "Saved {:?} of project {} as version {}",
+        scenario,
+        project_id,
+        version
+    );
+
+    let entries = state
+        .pl_entry_repository
+        .find_details_by_project(project_id, scenario, None)
+        .await?;
+
+    let response = pl_entry_response(&state, scenario, None, entries).await?;
+
+    Ok(Json(response))
+}
+
+/// Job単位の一覧取得 (GET /jobs/{jid}/pl?scenario=...&period=...)
+pub async fn list_job_pl_entries(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+    Query(query): Query<PlEntryQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<PlEntryResponse>> {
+    auth_user.require(Resource::PlEntry, Action::Read)?;
+
+    find_job(&state, job_id).await?;
+
+    let range = query
+        .period
+        .map(|p| state.fiscal_calendar.range(p))
+        .transpose()?;
+    let entries = state
+        .pl_entry_repository
+        .find_details_by_job(job_id, query.scenario, range)
+        .await?;
+
+    let response = pl_entry_response(&state, query.scenario, range, entries).await?;
+
+    Ok(Json(response))
+}
+
+/// Job単位の一括登録・更新 (PUT /jobs/{jid}/pl/{scenario})
+///
+/// 保存後のProject配下のJobの明細を版として記録する。
+/// JobPlanがProjectの予算残を超える場合は、設定に応じて警告を返すかエラーとする
+pub async fn upsert_job_pl_entries(
+    State(state): State<AppState>,
+    Path((job_id, scenario)): Path<(Uuid, Scenario)>,
+    auth_user: AuthUser,
+    Json(payload): Json<BulkUpsertPlEntryRequest>,
+) -> Result<Json<JobPlEntryResponse>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    let job = authorize_job(
+        &state,
+        &auth_user,
+        job_id,
+        Resource::PlEntry,
+        Action::Update,
+    )
+    .await?;
+
+    if !scenario.is_job_level() {
+        return Err(AppError::Validation(format!(
+            "{:?} cannot be registered per job",
+            scenario
+        )));
+    }
+
+    let project_id = job.project_id.ok_or(AppError::Validation(format!(
+        "Job {} is not linked to a project",
+        job_id
+    )))?;
+
+    let items = state.account_item_repository.find_all().await?;
+    ensure_active_items(&items, &payload.entries)?;
+    let entries = normalize_entries(&state.fiscal_calendar, payload.entries)?;
+
+    let budget_check = if scenario == Scenario::JobPlan {
+        Some(JobBudgetCheck {
+            converted: to_reporting_currency(&state, scenario, &entries).await?,
+            items,
+            policy: state.job_budget_policy,
+        })
+    } else {
+        None
+    };
+
+    let param = SaveJobEntriesParam {
+        project_id,
+        job_id,
+        scenario,
+        entries,
+        budget_check,
+        comment: payload.comment.filter(|c| !c.trim().is_empty()),
+        user_id,
+    };
+    let budget_overruns = state.pl_entry_repository.bulk_upsert_job(param).await?;
+
+    if !budget_overruns.is_empty() {
+        tracing::warn!(
+            "JobPlan of job {} exceeds the budget of project {} in {} line(s)",
+            job_id,
+            project_id,
+            budget_overruns.len()
+        );
+    }
+
+    let entries = state
+        .pl_entry_repository
+        .find_details_by_job(job_id, scenario, None)
+        .await?;
+
+    let response = pl_entry_response(&state, scenario, None, entries).await?;
+
+    Ok(Json(JobPlEntryResponse {
+        pl: response,
+        budget_overruns,
+    }))
+}
+
+/// ExecPlanAdjustの再計算 (POST /projects/{pid}/pl/exec-plan-adjust)
+///
+/// 計画やJobPlanの登録時にも自動で再計算されるため、通常は既存データの同期に使う
+pub async fn recompute_exec_plan_adjust(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<Json<PlEntryResponse>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    authorize_project(
+        &state,
+        &auth_user,
+        project_id,
+        Resource::PlEntry,
+        Action::Update,
+    )
+    .await?;
+
+    state
+        .pl_entry_repository
+        .sync_exec_plan_adjust(project_id, user_id)
+        .await?;
+
+    let entries = state
+        .pl_entry_repository
+        .find_details_by_project(project_id, Scenario::ExecPlanAdjust, None)
+        .await?;
+
+    let response = pl_entry_response(&state, Scenario::ExecPlanAdjust, None, entries).await?;
+
+    Ok(Json(response))
+}
+
+/// CSV出力 (GET /projects/{pid}/pl/{scenario}/csv?period=...)
+///
+/// 期間の指定がなければ明細が存在する年度全体（明細がなければ今年度）を出力する。
+/// 金額は換算せず、指定した通貨の明細のみを出力する
+pub async fn export_pl_csv(
+    State(state): State<AppState>,
+    Path((project_id, scenario)): Path<(Uuid, Scenario)>,
+    Query(query): Query<PlCsvExportQuery>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse> {
+    auth_user.require(Resource::PlEntry, Action::Read)?;
+
+    ensure_project_exists(&state, project_id).await?;
+
+    let calendar = state.fiscal_calendar;
+    let range = query.period.map(|p| calendar.range(p)).transpose()?;
+    let entries = state
+        .pl_entry_repository
+        .find_details_by_project(project_id, scenario, range)
+        .await?;
+
+    let currency = match query
+        .currency
+        .as_deref()
+        .map(normalize_currency)
+        .transpose()?
+    {
+        Some(currency) => currency,
+        None => {
+            let currencies: BTreeSet<&str> = entries.iter().map(|e| e.currency.as_str()).collect();
+            if currencies.len() > 1 {
+                return Err(AppError::Validation(format!(
+                    "Entries are in multiple currencies ({}); specify currency",
+                    currencies.into_iter().collect::<Vec<_>>().join(", ")
+                )));
+            }
+            currencies
+                .into_iter()
+                .next()
+                .map(str::to_string)
+                .unwrap_or_else(|| state.reporting_currency.clone())
+        }
+    };
+    let entries: Vec<_> = entries
+        .into_iter()
+        .filter(|e| e.currency == currency)
+        .collect();
+
+    let range = calendar.report_range(range, entries.iter().map(|e| e.date))?;
+
+    let mut amounts = HashMap::new();
+    for entry in &entries {
+        *amounts
+            .entry((entry.account_item_id, calendar.normalize(entry.date)))
+            .or_default() += entry.amount;
+    }
+
+    // 無効な科目も明細があれば出力する
+    let items: Vec<_> = state
+        .account_item_repository
+        .find_all()
+        .await?
+        .into_iter()
+        .filter(|item| item.is_active || entries.iter().any(|e| e.account_item_id == item.id))
+        .collect();
+
+    let body = write_plan_csv(&calendar.month_starts(range), &items, &amounts)?;
+    let disposition = format!(
+        "attachment; filename=\"pl_{}_{:?}_{}.csv\"",
+        project_id, scenario, currency
+    );
+
+    Ok((
+        [
+            (CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    ))
+}
+
+/// CSV取り込み (PUT /projects/{pid}/pl/{scenario}/csv)
+///
+/// 全行を検証し、エラーがあれば行番号付きで返して1件も登録しない。取り込み後の明細を版として記録する
+pub async fn import_pl_csv(
+    State(state): State<AppState>,
+    Path((project_id, scenario)): Path<(Uuid, Scenario)>,
+    Query(query): Query<PlCsvImportQuery>,
+    auth_user: AuthUser,
+    body: String,
+) -> Result<Json<PlCsvImportResponse>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    authorize_project(
+        &state,
+        &auth_user,
+        project_id,
+        Resource::PlEntry,
+        Action::Update,
+    )
+    .await?;
+    ensure_project_level(scenario)?;
+
+    let items = state.account_item_repository.find_all().await?;
+    let mut entries = parse_plan_csv(&body, &items).map_err(AppError::ImportFailed)?;
+    for entry in &mut entries {
+        entry.currency = query.currency.clone();
+    }
+    ensure_active_items(&items, &entries)?;
+    let entries = normalize_entries(&state.fiscal_calendar, entries)?;
+    ensure_convertible(&state, scenario, &entries).await?;
+    let imported = entries.len();
+
+    let param = SaveProjectEntriesParam {
+        project_id,
+        scenario,
+        entries,
+        comment: query.comment.filter(|c| !c.trim().is_empty()),
+        user_id,
+    };
+    let version = state
+        .pl_entry_repository
+        .save_project_entries(param)
+        .await?;
+
+    tracing::info!(
+        "Imported {:?} of project {} as version {}",
+        scenario,
+        project_id,
+        version
+    );
+
+    Ok(Json(PlCsvImportResponse { imported }))
+}
+
+/// 期間の指定がなければ明細が存在する年度全体（明細がなければ今年度）を損益計算書の対象とする
+async fn pl_entry_response(
+    state: &AppState,
+    scenario: Scenario,
+    range: Option<PeriodRange>,
+    entries: Vec<PlEntryDetail>,
+) -> Result<PlEntryResponse> {
+    let calendar = state.fiscal_calendar;
+    let range = calendar.report_range(range, entries.iter().map(|e| e.date))?;
+    let items = state.account_item_repository.find_all().await?;
+    let statement = PlStatement::build(&calendar.month_starts(range), &items, &entries);
+
+    Ok(PlEntryResponse {
+        scenario,
+        entries,
+        statement,
+    })
+}
+
+async fn find_job(state: &AppState, job_id: Uuid) -> Result<Job> {
+    state
+        .job_repository
+        .find_by_id(job_id)
+        .await?
+        .ok_or(AppError::NotFound(format!("Job '{}' not found", job_id)))
+}
+
+/// 予算と比較できるよう明細の金額を報告通貨に換算する
+async fn to_reporting_currency(
+    state: &AppState,
+    scenario: Scenario,
+    entries: &[UpsertPlEntryParam],
+) -> Result<Vec<UpsertPlEntryParam>> {
+    let mut converted = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let currency = entry
+            .currency
+            .as_deref()
+            .unwrap_or(&state.reporting_currency);
+        let rate = state
+            .exchange_rate_repository
+            .find_rate(scenario, currency, entry.date)
+            .await?
+            .ok_or_else(|| {
+                AppError::MissingExchangeRate(format!(
+                    "No exchange rate for {} in {}",
+                    currency,
+                    entry.date.format("%Y-%m")
+                ))
+            })?;
+
+        converted.push(UpsertPlEntryParam {
+            amount: (entry.amount * rate).round_dp(4),
+            currency: None,
+            ..entry.clone()
+        });
+    }
+
+    Ok(converted)
+}
+
+/// ExecPlanAdjust の再計算に使う明細は、登録前に換算できることを確かめる
+async fn ensure_convertible(
+    state: &AppState,
+    scenario: Scenario,
+    entries: &[UpsertPlEntryParam],
+) -> Result<()> {
+    if scenario.affects_exec_plan_adjust() {
+        to_reporting_currency(state, scenario, entries).await?;
+    }
+
+    Ok(())
+}
+
+/// Project単位で登録できるシナリオか検証する（自動計算されるシナリオとJob単位のシナリオは登録できない）
+fn ensure_project_level(scenario: Scenario) -> Result<()> {
+    if scenario.is_computed() {
+        return Err(AppError::Validation(format!(
+            "{:?} is computed automatically and cannot be edited",
+            scenario
+        )));
+    }
+    if scenario.is_job_level() {
+        return Err(AppError::Validation(format!(
+            "{:?} must be registered per job",
+            scenario
+        )));
+    }
+
+    Ok(())
+}
+
+async fn ensure_project_exists(state: &AppState, project_id: Uuid) -> Result<()> {
+    state
+        .project_repository
+        .find_by_id(project_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Project {} not found",
+            project_id
+        )))?;
+
+    Ok(())
+}
+
+/// 無効化された科目や存在しない科目への入力を弾く（過去の明細はそのまま残る）
+fn ensure_active_items(items: &[AccountItem], entries: &[UpsertPlEntryParam]) -> Result<()> {
+    let items: HashMap<Uuid, &AccountItem> = items.iter().map(|item| (item.id, item)).collect();
+
+    for entry in entries {
+        match items.get(&entry.account_item_id) {
+            Some(item) if item.is_active => {}
+            Some(item) => {
+                return Err(AppError::Validation(format!(
+                    "Account item '{}' is inactive",
+                    item.name
+                )));
+            }
+            None => {
+                return Err(AppError::Validation(format!(
+                    "Account item {} not found",
+                    entry.account_item_id
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 日付を月次期間の初日に、通貨コードを大文字に揃え、同じ勘定科目・期間の重複を弾く
+fn normalize_entries(
+    calendar: &FiscalCalendar,
+    entries: Vec<UpsertPlEntryParam>,
+) -> Result<Vec<UpsertPlEntryParam>> {
+    let mut seen = HashSet::new();
+
+    entries
+        .into_iter()
+        .map(|mut entry| {
+            entry.date = calendar.normalize(entry.date);
+            entry.currency = entry
+                .currency
+                .as_deref()
+                .map(normalize_currency)
+                .transpose()?;
+            if !seen.insert((entry.account_item_id, entry.date)) {
+                return Err(AppError::Validation(format!(
+                    "Duplicate entry for account item {} in period starting {}",
+                    entry.account_item_id, entry.date
+                )));
+            }
+            Ok(entry)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::{
+        AppState,
+        domains::{budget::BudgetPolicy, user::UserRole},
+        test_support::{
+            account_item_id, app_state, auth_user, insert_job, insert_project, insert_service,
+            insert_user,
+        },
+    };
+
+    fn request(account_item_id: Uuid, amount: &str) -> BulkUpsertPlEntryRequest {
+        BulkUpsertPlEntryRequest {
+            entries: vec![UpsertPlEntryParam {
+                account_item_id,
+                date: NaiveDate::from_ymd_opt(2026, 4, 1).unwrap(),
+                amount: amount.parse::<Decimal>().unwrap(),
+                currency: None,
+                description: None,
+            }],
+            comment: None,
+        }
+    }
+
+    #[sqlx::test]
+    async fn job_scenarios_cannot_be_saved_per_project(pool: sqlx::PgPool) {
+        let state = app_state(pool.clone());
+        let user = insert_user(&pool, UserRole::Manager).await;
+        let project = insert_project(&pool, user).await;
+        let item = account_item_id(&pool).await;
+
+        for scenario in Scenario::ALL
+            .into_iter()
+            .filter(|s| s.is_job_level() || s.is_computed())
+        {
+            let result = upsert_pl_entries(
+                State(state.clone()),
+                Path((project, scenario)),
+                auth_user(user, UserRole::Manager),
+                Json(request(item, "100")),
+            )
+            .await;
+            assert!(matches!(result, Err(AppError::Validation(_))));
+        }
+    }
+
+    #[sqlx::test]
+    async fn job_saves_record_versions_of_the_project_job_entries(pool: sqlx::PgPool) {
+        let state = app_state(pool.clone());
+        let user = insert_user(&pool, UserRole::Manager).await;
+        let project = insert_project(&pool, user).await;
+        let service = insert_service(&pool).await;
+        let first = insert_job(&pool, service, Some(project), user).await;
+        let second = insert_job(&pool, service, Some(project), user).await;
+        let item = account_item_id(&pool).await;
+
+        for (job, amount) in [(first, "100"), (second, "200")] {
+            let Json(response) = upsert_job_pl_entries(
+                State(state.clone()),
+                Path((job, Scenario::Actual)),
+                auth_user(user, UserRole::Manager),
+                Json(request(item, amount)),
+            )
+            .await
+            .unwrap();
+            assert_eq!(response.pl.entries.len(), 1);
+        }
+
+        let snapshots = state
+            .pl_snapshot_repository
+            .find_by_project(project, Scenario::Actual)
+            .await
+            .unwrap();
+        assert_eq!(
+            snapshots.iter().map(|s| s.version).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+
+        let entries = state
+            .pl_snapshot_repository
+            .find_entries(snapshots[0].id)
+            .await
+            .unwrap();
+        let mut jobs: Vec<Option<Uuid>> = entries.iter().map(|e| e.job_id).collect();
+        jobs.sort();
+        let mut expected = vec![Some(first), Some(second)];
+        expected.sort();
+        assert_eq!(jobs, expected);
+    }
+
+    #[sqlx::test]
+    async fn reject_policy_keeps_job_plans_within_the_budget(pool: sqlx::PgPool) {
+        let state = AppState {
+            job_budget_policy: BudgetPolicy::Reject,
+            ..app_state(pool.clone())
+        };
+        let user = insert_user(&pool, UserRole::Manager).await;
+        let project = insert_project(&pool, user).await;
+        let service = insert_service(&pool).await;
+        let job = insert_job(&pool, service, Some(project), user).await;
+        let item = account_item_id(&pool).await;
+
+        let Json(plan) = upsert_pl_entries(
+            State(state.clone()),
+            Path((project, Scenario::MasterPlan)),
+            auth_user(user, UserRole::Manager),
+            Json(request(item, "1000")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(plan.entries.len(), 1);
+
+        let save_job_plan = |amount| {
+            upsert_job_pl_entries(
+                State(state.clone()),
+                Path((job, Scenario::JobPlan)),
+                auth_user(user, UserRole::Manager),
+                Json(request(item, amount)),
+            )
+        };
+
+        let result = save_job_plan("1200").await;
+        assert!(matches!(result, Err(AppError::BudgetExceeded(lines)) if lines.len() == 1));
+        let saved = state
+            .pl_entry_repository
+            .find_details_by_job(job, Scenario::JobPlan, None)
+            .await
+            .unwrap();
+        assert!(saved.is_empty());
+
+        let Json(response) = save_job_plan("900").await.unwrap();
+        assert!(response.budget_overruns.is_empty());
+        assert_eq!(response.pl.entries.len(), 1);
+    }
+}
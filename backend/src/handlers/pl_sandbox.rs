@@ -0,0 +1,249 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    domains::{
+        permission::{Action, Resource},
+        pl_entry::Scenario,
+        pl_sandbox::{CreatePlSandboxParam, PlSandbox, SandboxOperation, apply_operations},
+        pl_statement::PlStatement,
+        report::{MonthlyAmount, PlScope, ReportScenario},
+    },
+    error::{AppError, Result},
+    extractors::AuthUser,
+    handlers::report::{
+        PeriodQuery, VarianceQuery, VarianceReport, report_totals, variance_report,
+    },
+};
+
+/// 試算用シナリオ作成リクエスト
+///
+/// project_id と theme_id のどちらか一方を指定する
+#[derive(Debug, Deserialize)]
+pub struct CreateSandboxRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub project_id: Option<Uuid>,
+    pub theme_id: Option<Uuid>,
+    pub source_scenario: Scenario,
+}
+
+/// 一括変換リクエスト（指定された順に適用する）
+#[derive(Debug, Deserialize)]
+pub struct TransformSandboxRequest {
+    pub operations: Vec<SandboxOperation>,
+}
+
+/// 試算用シナリオの金額と損益計算書
+#[derive(Debug, Serialize)]
+pub struct PlSandboxResponse {
+    #[serde(flatten)]
+    pub sandbox: PlSandbox,
+    pub lines: Vec<MonthlyAmount>,
+    pub statement: PlStatement,
+}
+
+/// 自分の試算用シナリオの一覧 (GET /sandboxes)
+pub async fn list_sandboxes(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<PlSandbox>>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::Sandbox, Action::Read)?;
+
+    let sandboxes = state.pl_sandbox_repository.find_by_owner(user_id).await?;
+
+    Ok(Json(sandboxes))
+}
+
+/// 新規作成 (POST /sandboxes)
+///
+/// ProjectまたはThemeの既存シナリオの勘定科目・月ごとの合計をコピーする
+pub async fn create_sandbox(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateSandboxRequest>,
+) -> Result<(StatusCode, Json<PlSandboxResponse>)> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::Sandbox, Action::Create)?;
+
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::Validation("Name is required".to_string()));
+    }
+
+    let scope = match (payload.project_id, payload.theme_id) {
+        (Some(project_id), None) => {
+            state
+                .project_repository
+                .find_by_id(project_id)
+                .await?
+                .ok_or(AppError::NotFound(format!(
+                    "Project {} not found",
+                    project_id
+                )))?;
+            PlScope::Project(project_id)
+        }
+        (None, Some(theme_id)) => {
+            state
+                .theme_repository
+                .find_by_id(theme_id)
+                .await?
+                .ok_or(AppError::NotFound(format!("Theme {} not found", theme_id)))?;
+            PlScope::Theme(theme_id)
+        }
+        _ => {
+            return Err(AppError::Validation(
+                "Specify either project_id or theme_id".to_string(),
+            ));
+        }
+    };
+
+    let entries = report_totals(
+        &state,
+        scope,
+        ReportScenario::Stored(payload.source_scenario),
+        None,
+    )
+    .await?;
+
+    let param = CreatePlSandboxParam {
+        name,
+        description: payload.description,
+        project_id: payload.project_id,
+        theme_id: payload.theme_id,
+        source_scenario: payload.source_scenario,
+        owner_id: user_id,
+        entries,
+    };
+
+    let sandbox = state.pl_sandbox_repository.create(param).await?;
+    let response = sandbox_response(&state, sandbox).await?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// 詳細取得 (GET /sandboxes/{sid})
+pub async fn get_sandbox(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<Json<PlSandboxResponse>> {
+    auth_user.require(Resource::Sandbox, Action::Read)?;
+
+    let sandbox = find_own_sandbox(&state, &auth_user, id).await?;
+    let response = sandbox_response(&state, sandbox).await?;
+
+    Ok(Json(response))
+}
+
+/// 一括変換 (POST /sandboxes/{sid}/transform)
+///
+/// 月のずらし・割合での増減・勘定科目ごとの上書きを順に適用する
+pub async fn transform_sandbox(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+    Json(payload): Json<TransformSandboxRequest>,
+) -> Result<Json<PlSandboxResponse>> {
+    auth_user.require(Resource::Sandbox, Action::Update)?;
+
+    find_own_sandbox(&state, &auth_user, id).await?;
+
+    let lines = state.pl_sandbox_repository.find_totals(id, None).await?;
+    let items = state.account_item_repository.find_all().await?;
+    let lines = apply_operations(lines, &payload.operations, &items, &state.fiscal_calendar)?;
+
+    let sandbox = state
+        .pl_sandbox_repository
+        .replace_entries(id, lines)
+        .await?;
+    let response = sandbox_response(&state, sandbox).await?;
+
+    Ok(Json(response))
+}
+
+/// コピー元との差異 (GET /sandboxes/{sid}/variance?period=...)
+///
+/// コピー元シナリオの現在の明細を base、試算用シナリオを compare とする
+pub async fn sandbox_variance(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<PeriodQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<VarianceReport>> {
+    auth_user.require(Resource::Sandbox, Action::Read)?;
+
+    let sandbox = find_own_sandbox(&state, &auth_user, id).await?;
+
+    let query = VarianceQuery {
+        base: ReportScenario::Stored(sandbox.source_scenario),
+        compare: ReportScenario::Sandbox(sandbox.id),
+        period: query.period,
+    };
+    let report = variance_report(&state, &auth_user, sandbox.scope(), query).await?;
+
+    Ok(Json(report))
+}
+
+/// 削除 (DELETE /sandboxes/{sid})
+pub async fn delete_sandbox(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<StatusCode> {
+    auth_user.require(Resource::Sandbox, Action::Delete)?;
+
+    find_own_sandbox(&state, &auth_user, id).await?;
+
+    state.pl_sandbox_repository.delete(id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 試算用シナリオは作成者のみ参照・編集できる
+pub(crate) async fn find_own_sandbox(
+    state: &AppState,
+    auth_user: &AuthUser,
+    id: Uuid,
+) -> Result<PlSandbox> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    let sandbox = state
+        .pl_sandbox_repository
+        .find_by_id(id)
+        .await?
+        .ok_or(AppError::NotFound(format!("Sandbox {} not found", id)))?;
+
+    if sandbox.owner_id != user_id {
+        return Err(AppError::Forbidden);
+    }
+
+    Ok(sandbox)
+}
+
+/// 金額が存在する年度全体（金額がなければ今年度）を損益計算書の対象とする
+async fn sandbox_response(state: &AppState, sandbox: PlSandbox) -> Result<PlSandboxResponse> {
+    let calendar = state.fiscal_calendar;
+    let lines = state
+        .pl_sandbox_repository
+        .find_totals(sandbox.id, None)
+        .await?;
+    let range = calendar.report_range(None, lines.iter().map(|l| l.month))?;
+    let items = state.account_item_repository.find_all().await?;
+    let statement = PlStatement::build(&calendar.month_starts(range), &items, &lines);
+
+    Ok(PlSandboxResponse {
+        sandbox,
+        lines,
+        statement,
+    })
+}
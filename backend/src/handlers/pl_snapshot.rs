@@ -0,0 +1,144 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    domains::{
+        permission::{Action, Resource},
+        pl_entry::Scenario,
+        pl_snapshot::{PlSnapshot, PlSnapshotEntry, diff_snapshots},
+        pl_statement::PlStatement,
+        report::VarianceLine,
+    },
+    error::{AppError, Result},
+    extractors::AuthUser,
+};
+
+/// 版の比較のクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct SnapshotDiffQuery {
+    pub from: i32,
+    pub to: i32,
+}
+
+/// 版の明細と損益計算書
+#[derive(Debug, Serialize)]
+pub struct PlSnapshotResponse {
+    #[serde(flatten)]
+    pub snapshot: PlSnapshot,
+    pub entries: Vec<PlSnapshotEntry>,
+    pub statement: PlStatement,
+}
+
+/// 2つの版の差分（base が from、compare が to）
+#[derive(Debug, Serialize)]
+pub struct PlSnapshotDiffResponse {
+    pub scenario: Scenario,
+    pub from: PlSnapshot,
+    pub to: PlSnapshot,
+    pub lines: Vec<VarianceLine>,
+}
+
+/// 版の一覧 (GET /projects/{pid}/pl/{scenario}/versions)
+///
+/// 新しい版から順に返す
+pub async fn list_pl_snapshots(
+    State(state): State<AppState>,
+    Path((project_id, scenario)): Path<(Uuid, Scenario)>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<PlSnapshot>>> {
+    auth_user.require(Resource::PlEntry, Action::Read)?;
+
+    ensure_project_exists(&state, project_id).await?;
+
+    let snapshots = state
+        .pl_snapshot_repository
+        .find_by_project(project_id, scenario)
+        .await?;
+
+    Ok(Json(snapshots))
+}
+
+/// 版の詳細 (GET /projects/{pid}/pl/{scenario}/versions/{version})
+pub async fn get_pl_snapshot(
+    State(state): State<AppState>,
+    Path((project_id, scenario, version)): Path<(Uuid, Scenario, i32)>,
+    auth_user: AuthUser,
+) -> Result<Json<PlSnapshotResponse>> {
+    auth_user.require(Resource::PlEntry, Action::Read)?;
+
+    let snapshot = find_snapshot(&state, project_id, scenario, version).await?;
+    let entries = state
+        .pl_snapshot_repository
+        .find_entries(snapshot.id)
+        .await?;
+
+    let calendar = state.fiscal_calendar;
+    let range = calendar.report_range(None, entries.iter().map(|e| e.date))?;
+    let items = state.account_item_repository.find_all().await?;
+    let statement = PlStatement::build(&calendar.month_starts(range), &items, &entries);
+
+    Ok(Json(PlSnapshotResponse {
+        snapshot,
+        entries,
+        statement,
+    }))
+}
+
+/// 版の比較 (GET /projects/{pid}/pl/{scenario}/versions/diff?from=...&to=...)
+///
+/// 勘定科目・月ごとに金額が変わった明細だけを返す
+pub async fn diff_pl_snapshots(
+    State(state): State<AppState>,
+    Path((project_id, scenario)): Path<(Uuid, Scenario)>,
+    Query(query): Query<SnapshotDiffQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<PlSnapshotDiffResponse>> {
+    auth_user.require(Resource::PlEntry, Action::Read)?;
+
+    let from = find_snapshot(&state, project_id, scenario, query.from).await?;
+    let to = find_snapshot(&state, project_id, scenario, query.to).await?;
+
+    let from_entries = state.pl_snapshot_repository.find_entries(from.id).await?;
+    let to_entries = state.pl_snapshot_repository.find_entries(to.id).await?;
+
+    Ok(Json(PlSnapshotDiffResponse {
+        scenario,
+        from,
+        to,
+        lines: diff_snapshots(&from_entries, &to_entries)?,
+    }))
+}
+
+async fn find_snapshot(
+    state: &AppState,
+    project_id: Uuid,
+    scenario: Scenario,
+    version: i32,
+) -> Result<PlSnapshot> {
+    state
+        .pl_snapshot_repository
+        .find_by_version(project_id, scenario, version)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Version {} of {:?} for project {} not found",
+            version, scenario, project_id
+        )))
+}
+
+async fn ensure_project_exists(state: &AppState, project_id: Uuid) -> Result<()> {
+    state
+        .project_repository
+        .find_by_id(project_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Project {} not found",
+            project_id
+        )))?;
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
 };
 use serde::Deserialize;
@@ -8,11 +8,22 @@ use uuid::Uuid;
 
 use crate::{
     AppState,
-    domains::project::{CreateProjectParam, Project, ProjectType, UpdateProjectParam},
+    domains::{
+        permission::{Action, Ownership, Resource},
+        project::{CreateProjectParam, Project, ProjectType, UpdateProjectParam},
+    },
     error::{AppError, Result},
     extractors::AuthUser,
 };
 
+/// 一覧取得のクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct ProjectQuery {
+    /// true なら自分が所有するProjectに絞り込む
+    #[serde(default)]
+    pub mine: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateProjectRequest {
     pub theme_id: Option<Uuid>,
@@ -25,6 +36,7 @@ pub struct CreateProjectRequest {
     pub value_prop: Option<String>,
     pub target_client: Option<String>,
     pub kpis: Option<String>,
+    /// 省略時は作成者
     pub owner_id: Option<Uuid>,
 }
 
@@ -48,9 +60,17 @@ pub struct UpdateProjectRequest {
 
 pub async fn list_projects(
     State(state): State<AppState>,
-    _auth_user: AuthUser,
+    Query(query): Query<ProjectQuery>,
+    auth_user: AuthUser,
 ) -> Result<Json<Vec<Project>>> {
-    let projects = state.project_repository.find_all().await?;
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::Project, Action::Read)?;
+
+    let projects = state
+        .project_repository
+        .find_all(query.mine.then_some(user_id))
+        .await?;
 
     Ok(Json(projects))
 }
@@ -58,8 +78,10 @@ pub async fn list_projects(
 pub async fn get_project(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
 ) -> Result<Json<Project>> {
+    auth_user.require(Resource::Project, Action::Read)?;
+
     let project = state
         .project_repository
         .find_by_id(id)
@@ -76,6 +98,8 @@ pub async fn create_project(
 ) -> Result<Json<Project>> {
     let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
 
+    auth_user.require(Resource::Project, Action::Create)?;
+
     let param = CreateProjectParam {
         theme_id: payload.theme_id,
         name: payload.name,
@@ -86,7 +110,7 @@ pub async fn create_project(
         value_prop: payload.value_prop,
         target_client: payload.target_client,
         kpis: payload.kpis,
-        owner_id: payload.owner_id,
+        owner_id: payload.owner_id.or(Some(user_id)),
         created_by: user_id,
     };
 
@@ -103,6 +127,8 @@ pub async fn update_project(
 ) -> Result<Json<Project>> {
     let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
 
+    authorize_project(&state, &auth_user, id, Resource::Project, Action::Update).await?;
+
     let param = UpdateProjectParam {
         theme_id: payload.theme_id,
         name: payload.name,
@@ -126,9 +152,39 @@ pub async fn update_project(
 pub async fn delete_project(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
 ) -> Result<StatusCode> {
+    authorize_project(&state, &auth_user, id, Resource::Project, Action::Delete).await?;
+
     state.project_repository.delete(id).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Projectを取得し、所有者であれば権限が足りなくても許可される操作かどうかを確かめる
+pub(crate) async fn authorize_project(
+    state: &AppState,
+    auth_user: &AuthUser,
+    project_id: Uuid,
+    resource: Resource,
+    action: Action,
+) -> Result<Project> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    let project = state
+        .project_repository
+        .find_by_id(project_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Project {} not found",
+            project_id
+        )))?;
+
+    auth_user.require_owned(
+        resource,
+        action,
+        Ownership::of(user_id, project.owner_id, &[]),
+    )?;
+
+    Ok(project)
+}
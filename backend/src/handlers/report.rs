@@ -0,0 +1,631 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    domains::{
+        budget::{BudgetLine, build_consumption},
+        fiscal::{FiscalPeriodSpec, PeriodRange},
+        permission::{Action, Resource},
+        pl_entry::Scenario,
+        pl_statement::PlStatement,
+        pl_xlsx::write_pl_workbook,
+        report::{MonthlyAmount, PlScope, ReportScenario, VarianceLine, build_variance},
+    },
+    error::{AppError, Result},
+    extractors::AuthUser,
+    handlers::pl_sandbox::find_own_sandbox,
+};
+
+/// 集計レポートのクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct ScenarioQuery {
+    pub scenario: ReportScenario,
+    pub period: Option<FiscalPeriodSpec>,
+}
+
+/// 組織単位のPL集計
+#[derive(Debug, Serialize)]
+pub struct PlRollup {
+    pub scenario: ReportScenario,
+    pub lines: Vec<MonthlyAmount>,
+    pub statement: PlStatement,
+}
+
+/// 期間指定のクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct PeriodQuery {
+    pub period: Option<FiscalPeriodSpec>,
+}
+
+/// 差異レポートのクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct VarianceQuery {
+    pub base: ReportScenario,
+    pub compare: ReportScenario,
+    pub period: Option<FiscalPeriodSpec>,
+}
+
+/// 予算消化レポートのクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct BudgetQuery {
+    /// 予算とするシナリオ。省略時は InitialPlan があればそれを、なければ MasterPlan を使う
+    pub budget: Option<Scenario>,
+    pub period: Option<FiscalPeriodSpec>,
+}
+
+/// 予算消化レポート
+#[derive(Debug, Serialize)]
+pub struct BudgetReport {
+    pub budget_scenario: Scenario,
+    pub lines: Vec<BudgetLine>,
+}
+
+/// 差異レポート
+#[derive(Debug, Serialize)]
+pub struct VarianceReport {
+    pub base: ReportScenario,
+    pub compare: ReportScenario,
+    pub lines: Vec<VarianceLine>,
+}
+
+/// Project単位の差異 (GET /projects/{pid}/pl/variance?base=...&compare=...&period=...)
+pub async fn project_variance(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    Query(query): Query<VarianceQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<VarianceReport>> {
+    auth_user.require(Resource::Report, Action::Read)?;
+
+    state
+        .project_repository
+        .find_by_id(project_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Project {} not found",
+            project_id
+        )))?;
+
+    let report = variance_report(&state, &auth_user, PlScope::Project(project_id), query).await?;
+
+    Ok(Json(report))
+}
+
+/// Projectの予算消化状況 (GET /projects/{pid}/pl/budget?budget=...&period=...)
+///
+/// 勘定科目・月ごとに、Projectの予算から配下のJobのJobPlanの合計を引いた予算残を返す
+pub async fn project_budget(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    Query(query): Query<BudgetQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<BudgetReport>> {
+    auth_user.require(Resource::Report, Action::Read)?;
+
+    state
+        .project_repository
+        .find_by_id(project_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Project {} not found",
+            project_id
+        )))?;
+
+    let range = query
+        .period
+        .map(|p| state.fiscal_calendar.range(p))
+        .transpose()?;
+    let (budget_scenario, budget) = budget_totals(&state, project_id, query.budget, range).await?;
+    let consumed = state
+        .pl_entry_repository
+        .find_monthly_totals(PlScope::JobsOfProject(project_id), Scenario::JobPlan, range)
+        .await?;
+
+    Ok(Json(BudgetReport {
+        budget_scenario,
+        lines: build_consumption(&budget, &consumed),
+    }))
+}
+
+/// Project単位の集計 (GET /projects/{pid}/pl/summary?scenario=...&period=...)
+pub async fn project_summary(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    Query(query): Query<ScenarioQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<PlRollup>> {
+    auth_user.require(Resource::Report, Action::Read)?;
+
+    state
+        .project_repository
+        .find_by_id(project_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Project {} not found",
+            project_id
+        )))?;
+
+    let rollup = rollup_report(&state, &auth_user, PlScope::Project(project_id), query).await?;
+
+    Ok(Json(rollup))
+}
+
+/// Job単位の集計 (GET /jobs/{jid}/pl/summary?scenario=...&period=...)
+pub async fn job_summary(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+    Query(query): Query<ScenarioQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<PlRollup>> {
+    auth_user.require(Resource::Report, Action::Read)?;
+
+    state
+        .job_repository
+        .find_by_id(job_id)
+        .await?
+        .ok_or(AppError::NotFound(format!("Job '{}' not found", job_id)))?;
+
+    let rollup = rollup_report(&state, &auth_user, PlScope::Job(job_id), query).await?;
+
+    Ok(Json(rollup))
+}
+
+/// Job単位の差異 (GET /jobs/{jid}/pl/variance?base=...&compare=...&period=...)
+pub async fn job_variance(
+    State(state): State<AppState>,
+    Path(job_id): Path<Uuid>,
+    Query(query): Query<VarianceQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<VarianceReport>> {
+    auth_user.require(Resource::Report, Action::Read)?;
+
+    state
+        .job_repository
+        .find_by_id(job_id)
+        .await?
+        .ok_or(AppError::NotFound(format!("Job '{}' not found", job_id)))?;
+
+    let report = variance_report(&state, &auth_user, PlScope::Job(job_id), query).await?;
+
+    Ok(Json(report))
+}
+
+/// Theme単位の集計 (GET /themes/{tid}/pl?scenario=...&period=...)
+pub async fn theme_rollup(
+    State(state): State<AppState>,
+    Path(theme_id): Path<Uuid>,
+    Query(query): Query<ScenarioQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<PlRollup>> {
+    auth_user.require(Resource::Report, Action::Read)?;
+
+    state
+        .theme_repository
+        .find_by_id(theme_id)
+        .await?
+        .ok_or(AppError::NotFound(format!("Theme {} not found", theme_id)))?;
+
+    let rollup = rollup_report(&state, &auth_user, PlScope::Theme(theme_id), query).await?;
+
+    Ok(Json(rollup))
+}
+
+/// Theme単位の差異 (GET /themes/{tid}/pl/variance?base=...&compare=...&period=...)
+pub async fn theme_variance(
+    State(state): State<AppState>,
+    Path(theme_id): Path<Uuid>,
+    Query(query): Query<VarianceQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<VarianceReport>> {
+    auth_user.require(Resource::Report, Action::Read)?;
+
+    state
+        .theme_repository
+        .find_by_id(theme_id)
+        .await?
+        .ok_or(AppError::NotFound(format!("Theme {} not found", theme_id)))?;
+
+    let report = variance_report(&state, &auth_user, PlScope::Theme(theme_id), query).await?;
+
+    Ok(Json(report))
+}
+
+/// Segment単位の集計 (GET /segments/{sid}/pl?scenario=...&period=...)
+pub async fn segment_rollup(
+    State(state): State<AppState>,
+    Path(segment_id): Path<Uuid>,
+    Query(query): Query<ScenarioQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<PlRollup>> {
+    auth_user.require(Resource::Report, Action::Read)?;
+
+    state
+        .segment_repository
+        .find_by_id(segment_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Segment {} not found",
+            segment_id
+        )))?;
+
+    let rollup = rollup_report(&state, &auth_user, PlScope::Segment(segment_id), query).await?;
+
+    Ok(Json(rollup))
+}
+
+/// Service単位の集計 (GET /services/{identifier}/pl?scenario=...&period=...)
+pub async fn service_rollup(
+    State(state): State<AppState>,
+    Path(identifier): Path<String>,
+    Query(query): Query<ScenarioQuery>,
+    auth_user: AuthUser,
+) -> Result<Json<PlRollup>> {
+    auth_user.require(Resource::Report, Action::Read)?;
+
+    let service = if let Ok(uuid) = Uuid::parse_str(&identifier) {
+        state.service_repository.find_by_id(uuid).await?
+    } else {
+        state.service_repository.find_by_slug(&identifier).await?
+    };
+
+    let service = service.ok_or(AppError::NotFound(format!(
+        "Service '{}' not found",
+        identifier
+    )))?;
+
+    let rollup = rollup_report(&state, &auth_user, PlScope::Service(service.id), query).await?;
+
+    Ok(Json(rollup))
+}
+
+/// 仮想シナリオは登録済みの明細から算出する
+pub(crate) async fn report_totals(
+    state: &AppState,
+    scope: PlScope,
+    scenario: ReportScenario,
+    range: Option<PeriodRange>,
+) -> Result<Vec<MonthlyAmount>> {
+    let totals = match scenario {
+        ReportScenario::Stored(scenario) => {
+            state
+                .pl_entry_repository
+                .find_monthly_totals(scope, scenario, range)
+                .await?
+        }
+        ReportScenario::Forecast => {
+            state
+                .pl_entry_repository
+                .find_forecast_totals(scope, range)
+                .await?
+        }
+        ReportScenario::Sandbox(id) => state.pl_sandbox_repository.find_totals(id, range).await?,
+    };
+
+    Ok(totals)
+}
+
+/// 試算用のシナリオは作成者のみが、コピー元と同じ範囲でのみ参照できる
+async fn ensure_scenario_access(
+    state: &AppState,
+    auth_user: &AuthUser,
+    scope: PlScope,
+    scenario: ReportScenario,
+) -> Result<()> {
+    if let ReportScenario::Sandbox(id) = scenario {
+        let sandbox = find_own_sandbox(state, auth_user, id).await?;
+        if sandbox.scope() != scope {
+            return Err(AppError::Validation(format!(
+                "Sandbox '{}' was not copied from this scope",
+                sandbox.name
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+async fn rollup_report(
+    state: &AppState,
+    auth_user: &AuthUser,
+    scope: PlScope,
+    query: ScenarioQuery,
+) -> Result<PlRollup> {
+    ensure_scenario_access(state, auth_user, scope, query.scenario).await?;
+
+    let calendar = state.fiscal_calendar;
+    let range = query.period.map(|p| calendar.range(p)).transpose()?;
+    let lines = report_totals(state, scope, query.scenario, range).await?;
+
+    let range = calendar.report_range(range, lines.iter().map(|l| l.month))?;
+    let items = state.account_item_repository.find_all().await?;
+    let statement = PlStatement::build(&calendar.month_starts(range), &items, &lines);
+
+    Ok(PlRollup {
+        scenario: query.scenario,
+        lines,
+        statement,
+    })
+}
+
+/// Projectの予算とするシナリオとその月次合計
+async fn budget_totals(
+    state: &AppState,
+    project_id: Uuid,
+    budget: Option<Scenario>,
+    range: Option<PeriodRange>,
+) -> Result<(Scenario, Vec<MonthlyAmount>)> {
+    let scope = PlScope::Project(project_id);
+
+    // 予算として扱えるのは確定させる計画シナリオのみ
+    let scenario = match budget {
+        Some(scenario) if scenario.is_lockable() => scenario,
+        Some(scenario) => {
+            return Err(AppError::Validation(format!(
+                "{:?} cannot be used as a budget",
+                scenario
+            )));
+        }
+        None => {
+            let initial = state
+                .pl_entry_repository
+                .find_monthly_totals(scope, Scenario::InitialPlan, None)
+                .await?;
+            if initial.is_empty() {
+                Scenario::MasterPlan
+            } else {
+                Scenario::InitialPlan
+            }
+        }
+    };
+
+    let totals = state
+        .pl_entry_repository
+        .find_monthly_totals(scope, scenario, range)
+        .await?;
+
+    Ok((scenario, totals))
+}
+
+pub(crate) async fn variance_report(
+    state: &AppState,
+    auth_user: &AuthUser,
+    scope: PlScope,
+    query: VarianceQuery,
+) -> Result<VarianceReport> {
+    ensure_scenario_access(state, auth_user, scope, query.base).await?;
+    ensure_scenario_access(state, auth_user, scope, query.compare).await?;
+
+    let range = query
+        .period
+        .map(|p| state.fiscal_calendar.range(p))
+        .transpose()?;
+    let base = report_totals(state, scope, query.base, range).await?;
+    let compare = report_totals(state, scope, query.compare, range).await?;
+
+    Ok(VarianceReport {
+        base: query.base,
+        compare: query.compare,
+        lines: build_variance(&base, &compare),
+    })
+}
+
+/// Project単位のExcel出力 (GET /projects/{pid}/pl/xlsx?period=...)
+pub async fn export_project_xlsx(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    Query(query): Query<PeriodQuery>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse> {
+    auth_user.require(Resource::Report, Action::Read)?;
+
+    let project = state
+        .project_repository
+        .find_by_id(project_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Project {} not found",
+            project_id
+        )))?;
+
+    let body = xlsx_report(
+        &state,
+        PlScope::Project(project_id),
+        &project.name,
+        query.period,
+    )
+    .await?;
+
+    Ok(xlsx_response(
+        format!("pl_project_{}.xlsx", project_id),
+        body,
+    ))
+}
+
+/// Segment単位のExcel出力 (GET /segments/{sid}/pl/xlsx?period=...)
+pub async fn export_segment_xlsx(
+    State(state): State<AppState>,
+    Path(segment_id): Path<Uuid>,
+    Query(query): Query<PeriodQuery>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse> {
+    auth_user.require(Resource::Report, Action::Read)?;
+
+    let segment = state
+        .segment_repository
+        .find_by_id(segment_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Segment {} not found",
+            segment_id
+        )))?;
+
+    let body = xlsx_report(
+        &state,
+        PlScope::Segment(segment_id),
+        &segment.name,
+        query.period,
+    )
+    .await?;
+
+    Ok(xlsx_response(
+        format!("pl_segment_{}.xlsx", segment.slug),
+        body,
+    ))
+}
+
+/// 全シナリオを1シートずつ出力する。期間の指定がなければ明細が存在する年度全体を対象とする
+async fn xlsx_report(
+    state: &AppState,
+    scope: PlScope,
+    title: &str,
+    period: Option<FiscalPeriodSpec>,
+) -> Result<Vec<u8>> {
+    let calendar = state.fiscal_calendar;
+    let range = period.map(|p| calendar.range(p)).transpose()?;
+
+    let mut totals = Vec::new();
+    for scenario in ReportScenario::all() {
+        let lines = report_totals(state, scope, scenario, range).await?;
+        totals.push((scenario, lines));
+    }
+
+    let range = calendar.report_range(
+        range,
+        totals
+            .iter()
+            .flat_map(|(_, lines)| lines.iter().map(|l| l.month)),
+    )?;
+    let periods = calendar.month_starts(range);
+    let items = state.account_item_repository.find_all().await?;
+
+    let sheets: Vec<_> = totals
+        .into_iter()
+        .map(|(scenario, lines)| (scenario, PlStatement::build(&periods, &items, &lines)))
+        .collect();
+
+    let body = write_pl_workbook(title, &sheets)?;
+
+    Ok(body)
+}
+
+fn xlsx_response(filename: String, body: Vec<u8>) -> impl IntoResponse {
+    (
+        [
+            (
+                CONTENT_TYPE,
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet".to_string(),
+            ),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::{
+        domains::{
+            pl_entry::{SaveJobEntriesParam, SaveProjectEntriesParam, UpsertPlEntryParam},
+            user::UserRole,
+        },
+        test_support::{
+            account_item_id, app_state, auth_user, insert_job, insert_project, insert_service,
+            insert_user,
+        },
+    };
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn entry(account_item_id: Uuid, date: NaiveDate, amount: &str) -> UpsertPlEntryParam {
+        UpsertPlEntryParam {
+            account_item_id,
+            date,
+            amount: dec(amount),
+            currency: None,
+            description: None,
+        }
+    }
+
+    #[sqlx::test]
+    async fn project_variance_includes_job_actuals(pool: sqlx::PgPool) {
+        let state = app_state(pool.clone());
+        let user = insert_user(&pool, UserRole::Manager).await;
+        let project = insert_project(&pool, user).await;
+        let service = insert_service(&pool).await;
+        let job = insert_job(&pool, service, Some(project), user).await;
+        let item = account_item_id(&pool).await;
+        let month = NaiveDate::from_ymd_opt(2026, 4, 1).unwrap();
+
+        state
+            .pl_entry_repository
+            .save_project_entries(SaveProjectEntriesParam {
+                project_id: project,
+                scenario: Scenario::MasterPlan,
+                entries: vec![entry(item, month, "1000")],
+                comment: None,
+                user_id: user,
+            })
+            .await
+            .unwrap();
+        state
+            .pl_entry_repository
+            .bulk_upsert_job(SaveJobEntriesParam {
+                project_id: project,
+                job_id: job,
+                scenario: Scenario::Actual,
+                entries: vec![entry(item, month, "800")],
+                budget_check: None,
+                comment: None,
+                user_id: user,
+            })
+            .await
+            .unwrap();
+
+        let Json(report) = project_variance(
+            State(state),
+            Path(project),
+            Query(VarianceQuery {
+                base: ReportScenario::Stored(Scenario::MasterPlan),
+                compare: ReportScenario::Stored(Scenario::Actual),
+                period: None,
+            }),
+            auth_user(user, UserRole::Manager),
+        )
+        .await
+        .unwrap();
+
+        let found: Vec<(Uuid, NaiveDate, Decimal, Decimal, Decimal)> = report
+            .lines
+            .iter()
+            .map(|l| {
+                (
+                    l.account_item_id,
+                    l.month,
+                    l.base_amount,
+                    l.compare_amount,
+                    l.delta,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![(item, month, dec("1000"), dec("800"), dec("-200"))]
+        );
+    }
+}
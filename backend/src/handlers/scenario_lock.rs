@@ -0,0 +1,144 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    domains::{
+        permission::{Action, Resource},
+        pl_entry::Scenario,
+        scenario_lock::{CreateScenarioLockParam, ScenarioLock, UnlockScenarioParam},
+    },
+    error::{AppError, Result},
+    extractors::AuthUser,
+};
+
+/// シナリオロック作成リクエスト
+#[derive(Debug, Deserialize)]
+pub struct CreateScenarioLockRequest {
+    pub scenario: Scenario,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+}
+
+/// シナリオロック解除リクエスト
+#[derive(Debug, Deserialize)]
+pub struct UnlockScenarioRequest {
+    pub reason: String,
+}
+
+/// 一覧取得 (GET /projects/{pid}/pl/locks)
+pub async fn list_scenario_locks(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<ScenarioLock>>> {
+    auth_user.require(Resource::ScenarioLock, Action::Read)?;
+
+    let locks = state
+        .scenario_lock_repository
+        .find_by_project(project_id)
+        .await?;
+
+    Ok(Json(locks))
+}
+
+/// ロック（確定） (POST /projects/{pid}/pl/locks)
+///
+/// マネージャー以上が実行可能
+pub async fn lock_scenario(
+    State(state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateScenarioLockRequest>,
+) -> Result<(StatusCode, Json<ScenarioLock>)> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::ScenarioLock, Action::Lock)?;
+
+    if !payload.scenario.is_lockable() {
+        return Err(AppError::Validation(format!(
+            "{:?} cannot be locked",
+            payload.scenario
+        )));
+    }
+
+    if payload.period_start > payload.period_end {
+        return Err(AppError::Validation(
+            "period_start must be on or before period_end".to_string(),
+        ));
+    }
+
+    state
+        .project_repository
+        .find_by_id(project_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Project {} not found",
+            project_id
+        )))?;
+
+    let param = CreateScenarioLockParam {
+        project_id,
+        scenario: payload.scenario,
+        period_start: payload.period_start,
+        period_end: payload.period_end,
+        locked_by: user_id,
+    };
+
+    let lock = state.scenario_lock_repository.create(param).await?;
+
+    tracing::info!(
+        "Scenario {:?} of project {} locked by {} ({} - {})",
+        lock.scenario,
+        lock.project_id,
+        user_id,
+        lock.period_start,
+        lock.period_end
+    );
+
+    Ok((StatusCode::CREATED, Json(lock)))
+}
+
+/// ロック解除 (POST /projects/{pid}/pl/locks/{lid}/unlock)
+///
+/// 管理者のみ実行可能。解除者・解除日時・理由はロックの履歴として残る
+pub async fn unlock_scenario(
+    State(state): State<AppState>,
+    Path((project_id, lock_id)): Path<(Uuid, Uuid)>,
+    auth_user: AuthUser,
+    Json(payload): Json<UnlockScenarioRequest>,
+) -> Result<Json<ScenarioLock>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::ScenarioLock, Action::Unlock)?;
+
+    if payload.reason.trim().is_empty() {
+        return Err(AppError::Validation("reason is required".to_string()));
+    }
+
+    let param = UnlockScenarioParam {
+        unlocked_by: user_id,
+        reason: payload.reason,
+    };
+
+    let lock = state
+        .scenario_lock_repository
+        .unlock(project_id, lock_id, param)
+        .await?;
+
+    tracing::warn!(
+        "Scenario {:?} of project {} unlocked by {}: {}",
+        lock.scenario,
+        lock.project_id,
+        user_id,
+        lock.unlock_reason.as_deref().unwrap_or_default()
+    );
+
+    Ok(Json(lock))
+}
@@ -5,7 +5,10 @@ use uuid::Uuid;
 
 use crate::{
     AppState,
-    domains::segment::{CreateSegmentParam, Segment, SegmentUiConfig},
+    domains::{
+        permission::{Action, Resource},
+        segment::{CreateSegmentParam, Segment, SegmentUiConfig},
+    },
     error::{AppError, Result},
     extractors::AuthUser,
 };
@@ -20,8 +23,10 @@ pub struct CreateSegmentRequest {
 
 pub async fn list_segment(
     State(state): State<AppState>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
 ) -> Result<Json<Vec<Segment>>> {
+    auth_user.require(Resource::Segment, Action::Read)?;
+
     let segments = state.segment_repository.find_all().await?;
     Ok(Json(segments))
 }
@@ -33,6 +38,8 @@ pub async fn create_segment(
 ) -> Result<(StatusCode, Json<Segment>)> {
     let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
 
+    auth_user.require(Resource::Segment, Action::Create)?;
+
     let slug = match payload.slug {
         Some(s) if !s.trim().is_empty() => s,
         _ => slugify(&payload.name),
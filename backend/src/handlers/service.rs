@@ -1,6 +1,6 @@
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
 };
 use serde::Deserialize;
@@ -9,11 +9,22 @@ use uuid::Uuid;
 
 use crate::{
     AppState,
-    domains::service::{CreateServiceParam, Service, UpdateServiceParam},
+    domains::{
+        permission::{Action, Ownership, Resource},
+        service::{CreateServiceParam, Service, UpdateServiceParam},
+    },
     error::{AppError, Result},
     extractors::AuthUser,
 };
 
+/// 一覧取得のクエリパラメーター
+#[derive(Debug, Deserialize)]
+pub struct ServiceQuery {
+    /// true なら自分が所有するServiceに絞り込む
+    #[serde(default)]
+    pub mine: bool,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct CreateServiceRequest {
     pub name: String,
@@ -32,9 +43,17 @@ pub struct UpdateServiceRequest {
 
 pub async fn list_service(
     State(state): State<AppState>,
-    _auth_user: AuthUser,
+    Query(query): Query<ServiceQuery>,
+    auth_user: AuthUser,
 ) -> Result<Json<Vec<Service>>> {
-    let services = state.service_repository.find_all().await?;
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::Service, Action::Read)?;
+
+    let services = state
+        .service_repository
+        .find_all(query.mine.then_some(user_id))
+        .await?;
 
     Ok(Json(services))
 }
@@ -46,6 +65,8 @@ pub async fn create_service(
 ) -> Result<(StatusCode, Json<Service>)> {
     let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
 
+    auth_user.require(Resource::Service, Action::Create)?;
+
     let slug = match payload.slug {
         Some(s) if !s.trim().is_empty() => s,
         _ => slugify(&payload.name),
@@ -53,7 +74,7 @@ pub async fn create_service(
 
     let param = CreateServiceParam {
         name: payload.name,
-        slug: slug,
+        slug,
         owner_id: payload.owner_id,
         segment_id: payload.segment_id,
         created_by: user_id,
@@ -67,8 +88,10 @@ pub async fn create_service(
 pub async fn get_service(
     State(state): State<AppState>,
     Path(identifier): Path<String>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
 ) -> Result<Json<Service>> {
+    auth_user.require(Resource::Service, Action::Read)?;
+
     let service = if let Ok(uuid) = Uuid::parse_str(&identifier) {
         state.service_repository.find_by_id(uuid).await?
     } else {
@@ -93,6 +116,8 @@ pub async fn update_service(
         AppError::BadRequest("Updating by slug is not allowed. Please use ID.".to_string())
     })?;
 
+    authorize_service(&state, &auth_user, service_id, Action::Update).await?;
+
     let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
     let slug = match payload.slug {
         Some(s) if !s.trim().is_empty() => Some(s),
@@ -115,13 +140,42 @@ pub async fn update_service(
 pub async fn delete_service(
     State(state): State<AppState>,
     Path(identifier): Path<String>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
 ) -> Result<StatusCode> {
     let service_id = Uuid::parse_str(&identifier).map_err(|_| {
         AppError::BadRequest("Deleting by slug is not allowed. Please use ID".to_string())
     })?;
 
+    authorize_service(&state, &auth_user, service_id, Action::Delete).await?;
+
     state.service_repository.delete(service_id).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Serviceを取得し、所有者であれば権限が足りなくても許可される操作かどうかを確かめる
+async fn authorize_service(
+    state: &AppState,
+    auth_user: &AuthUser,
+    service_id: Uuid,
+    action: Action,
+) -> Result<Service> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    let service = state
+        .service_repository
+        .find_by_id(service_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Service '{}' not found",
+            service_id
+        )))?;
+
+    auth_user.require_owned(
+        Resource::Service,
+        action,
+        Ownership::of(user_id, service.owner_id, &[]),
+    )?;
+
+    Ok(service)
+}
@@ -8,7 +8,10 @@ use uuid::Uuid;
 
 use crate::{
     AppState,
-    domains::theme::{CreateThemeParam, Theme, UpdateThemeParam},
+    domains::{
+        permission::{Action, Resource},
+        theme::{CreateThemeParam, Theme, UpdateThemeParam},
+    },
     error::{AppError, Result},
     extractors::AuthUser,
 };
@@ -33,8 +36,10 @@ pub struct UpdateThemeRequest {
 /// 一覧取得 (GET /themes)
 pub async fn list_themes(
     State(state): State<AppState>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
 ) -> Result<Json<Vec<Theme>>> {
+    auth_user.require(Resource::Theme, Action::Read)?;
+
     let themes = state.theme_repository.find_all().await?;
 
     Ok(Json(themes))
@@ -46,6 +51,8 @@ pub async fn create_theme(
     auth_user: AuthUser,
     Json(payload): Json<CreateThemeRequest>,
 ) -> Result<(StatusCode, Json<Theme>)> {
+    auth_user.require(Resource::Theme, Action::Create)?;
+
     let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
     let param = CreateThemeParam {
         title: payload.title,
@@ -63,8 +70,10 @@ pub async fn create_theme(
 pub async fn get_theme(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
 ) -> Result<Json<Theme>> {
+    auth_user.require(Resource::Theme, Action::Read)?;
+
     let theme = state
         .theme_repository
         .find_by_id(id)
@@ -83,6 +92,8 @@ pub async fn update_theme(
 ) -> Result<Json<Theme>> {
     let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
 
+    auth_user.require(Resource::Theme, Action::Update)?;
+
     let param = UpdateThemeParam {
         title: payload.title,
         description: payload.description,
@@ -100,8 +111,10 @@ pub async fn update_theme(
 pub async fn delete_theme(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
 ) -> Result<StatusCode> {
+    auth_user.require(Resource::Theme, Action::Delete)?;
+
     state.theme_repository.delete(id).await?;
 
     Ok(StatusCode::NO_CONTENT)
@@ -8,8 +8,13 @@ use uuid::Uuid;
 
 use crate::{
     AppState,
-    domains::user::{CreateUserParam, User, UserRole},
+    domains::{
+        permission::{Action, Resource},
+        session::SecretToken,
+        user::{CreateUserParam, User, UserRole, validate_new_password},
+    },
     error::{AppError, Result},
+    extractors::AuthUser,
 };
 
 #[derive(Debug, Deserialize)]
@@ -19,30 +24,50 @@ pub struct CreateUserRequest {
     pub name: String,
     pub email: String,
     pub password: String,
-    pub role: Option<UserRole>,
+    /// 管理者が発行した招待トークン
+    pub invitation_token: String,
+}
+
+/// 権限変更リクエスト
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserRoleRequest {
+    pub role: UserRole,
 }
 
 /// 新規作成 (POST /signup)
+///
+/// 招待トークンが必要で、権限は招待で指定したものになる
+/// （最初の管理者は起動時に環境変数から登録する）
 pub async fn create_user(
     State(state): State<AppState>,
     Json(payload): Json<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<User>)> {
-    let payload = CreateUserParam {
+    validate_new_password(&payload.password)?;
+
+    let params = CreateUserParam {
         employee_id: payload.employee_id,
         username: payload.username,
         name: payload.name,
         email: payload.email,
         password: payload.password,
-        role: payload.role,
     };
 
-    let user = state.user_repository.create(payload).await?;
+    let user = state
+        .invitation_repository
+        .accept(&SecretToken::hash(&payload.invitation_token), params)
+        .await?;
 
     Ok((StatusCode::CREATED, Json(user)))
 }
 
 /// 詳細取得 (GET /users/{id})
-pub async fn get_user(State(state): State<AppState>, Path(id): Path<Uuid>) -> Result<Json<User>> {
+pub async fn get_user(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<Json<User>> {
+    auth_user.require(Resource::User, Action::Read)?;
+
     let user = state.user_repository.find_by_id(id).await?;
 
     match user {
@@ -50,3 +75,249 @@ pub async fn get_user(State(state): State<AppState>, Path(id): Path<Uuid>) -> Re
         None => Err(AppError::NotFound(format!("User {} not found", id))),
     }
 }
+
+/// 全セッションの取り消し (DELETE /users/{id}/sessions)
+///
+/// 退職・異動時などに、発行済みのトークンをすべて即時に無効にする
+pub async fn revoke_user_sessions(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<StatusCode> {
+    auth_user.require(Resource::User, Action::Update)?;
+
+    state
+        .user_repository
+        .find_by_id(id)
+        .await?
+        .ok_or(AppError::NotFound(format!("User {} not found", id)))?;
+
+    let revoked = state.session_repository.revoke_all(id).await?;
+
+    tracing::info!(
+        "Revoked {} session(s) of user {} by {}",
+        revoked,
+        id,
+        auth_user.claims.sub
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 無効化 (POST /users/{id}/deactivate)
+///
+/// 無効にしたユーザーはログインできず、発行済みのトークンもすべて取り消す
+pub async fn deactivate_user(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<Json<User>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::User, Action::Update)?;
+
+    if id == user_id {
+        return Err(AppError::Validation(
+            "You cannot deactivate yourself".to_string(),
+        ));
+    }
+
+    let user = state.user_repository.set_active(id, false, user_id).await?;
+    let revoked = state.session_repository.revoke_all(id).await?;
+
+    tracing::info!(
+        "Deactivated user {} and revoked {} session(s) by {}",
+        id,
+        revoked,
+        user_id
+    );
+
+    Ok(Json(user))
+}
+
+/// 再有効化 (POST /users/{id}/reactivate)
+///
+/// 無効化の際に取り消したセッションは戻らないため、再度ログインが必要
+pub async fn reactivate_user(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<Json<User>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::User, Action::Update)?;
+
+    let user = state.user_repository.set_active(id, true, user_id).await?;
+
+    tracing::info!("Reactivated user {} by {}", id, user_id);
+
+    Ok(Json(user))
+}
+
+/// 権限変更 (PUT /users/{id}/role)
+///
+/// 権限はトークンに含まれるため、変更したユーザーのセッションは取り消し、再ログインで反映する
+pub async fn update_user_role(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+    Json(payload): Json<UpdateUserRoleRequest>,
+) -> Result<Json<User>> {
+    let user_id = Uuid::parse_str(&auth_user.claims.sub).map_err(|_| AppError::AuthError)?;
+
+    auth_user.require(Resource::User, Action::Update)?;
+
+    if id == user_id {
+        return Err(AppError::Validation(
+            "You cannot change your own role".to_string(),
+        ));
+    }
+
+    let current = state
+        .user_repository
+        .find_by_id(id)
+        .await?
+        .ok_or(AppError::NotFound(format!("User {} not found", id)))?;
+    if current.role == payload.role.as_str() {
+        return Ok(Json(current));
+    }
+
+    let user = state
+        .user_repository
+        .update_role(id, payload.role, user_id)
+        .await?;
+    state.session_repository.revoke_all(id).await?;
+
+    tracing::info!(
+        "Changed role of user {} from {} to {} by {}",
+        id,
+        current.role,
+        user.role,
+        user_id
+    );
+
+    Ok(Json(user))
+}
+
+/// ログインロックの解除 (POST /users/{id}/unlock)
+pub async fn unlock_user(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<StatusCode> {
+    auth_user.require(Resource::User, Action::Update)?;
+
+    let user = state
+        .user_repository
+        .find_by_id(id)
+        .await?
+        .ok_or(AppError::NotFound(format!("User {} not found", id)))?;
+
+    state
+        .login_attempt_repository
+        .clear_failures(&user.email.to_lowercase())
+        .await?;
+
+    tracing::info!("Unlocked login of user {} by {}", id, auth_user.claims.sub);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::*;
+    use crate::{
+        domains::invitation::CreateInvitationParam,
+        test_support::{app_state, insert_user},
+    };
+
+    #[sqlx::test]
+    async fn signups_clashing_with_existing_users_keep_the_invitation(pool: sqlx::PgPool) {
+        let state = app_state(pool.clone());
+        let admin = insert_user(&pool, UserRole::Admin).await;
+        let existing = state
+            .user_repository
+            .find_by_id(admin)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let token = SecretToken::generate();
+        state
+            .invitation_repository
+            .create(CreateInvitationParam {
+                email: "new@example.com".to_string(),
+                role: UserRole::General,
+                token_hash: token.hash,
+                expires_at: Utc::now() + Duration::days(1),
+                invited_by: admin,
+            })
+            .await
+            .unwrap();
+
+        let signup = |employee_id: &str, username: &str| {
+            create_user(
+                State(state.clone()),
+                Json(CreateUserRequest {
+                    employee_id: employee_id.to_string(),
+                    username: username.to_string(),
+                    name: "New".to_string(),
+                    email: "new@example.com".to_string(),
+                    password: "password1".to_string(),
+                    invitation_token: token.token.clone(),
+                }),
+            )
+        };
+
+        let result = signup("99999", &existing.username).await;
+        assert!(matches!(result, Err(AppError::Validation(m)) if m.contains("username")));
+        let result = signup(&existing.employee_id, "new-user").await;
+        assert!(matches!(result, Err(AppError::Validation(m)) if m.contains("employee ID")));
+
+        let (status, Json(user)) = signup("99999", "new-user").await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(user.role, UserRole::General.as_str());
+    }
+
+    #[sqlx::test]
+    async fn emails_differing_only_in_case_cannot_both_sign_up(pool: sqlx::PgPool) {
+        let state = app_state(pool.clone());
+        let admin = insert_user(&pool, UserRole::Admin).await;
+        sqlx::query!(
+            "UPDATE users SET email = 'Taro@Example.com' WHERE id = $1",
+            admin
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let token = SecretToken::generate();
+        state
+            .invitation_repository
+            .create(CreateInvitationParam {
+                email: "taro@example.com".to_string(),
+                role: UserRole::General,
+                token_hash: token.hash,
+                expires_at: Utc::now() + Duration::days(1),
+                invited_by: admin,
+            })
+            .await
+            .unwrap();
+
+        let result = create_user(
+            State(state),
+            Json(CreateUserRequest {
+                employee_id: "99999".to_string(),
+                username: "taro".to_string(),
+                name: "Taro".to_string(),
+                email: "taro@example.com".to_string(),
+                password: "password1".to_string(),
+                invitation_token: token.token,
+            }),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::Validation(m)) if m.contains("email")));
+    }
+}
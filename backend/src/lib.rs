@@ -1,9 +1,31 @@
 use std::sync::Arc;
 
-use crate::domains::{
-    account_item::AccountItemRepository, job::JobRepository, project::ProjectRepository,
-    segment::SegmentRepository, service::ServiceRepository, theme::ThemeRepository,
-    user::UserRepository,
+use chrono::Duration;
+
+use crate::{
+    domains::{
+        account_item::AccountItemRepository,
+        allocation::AllocationRepository,
+        budget::BudgetPolicy,
+        exchange_rate::ExchangeRateRepository,
+        fiscal::{FiscalCalendar, FiscalPeriodRepository},
+        invitation::InvitationRepository,
+        job::JobRepository,
+        login_attempt::{LockoutPolicy, LoginAttemptRepository},
+        mailer::Mailer,
+        password_reset::PasswordResetRepository,
+        pl_entry::PlEntryRepository,
+        pl_sandbox::PlSandboxRepository,
+        pl_snapshot::PlSnapshotRepository,
+        project::ProjectRepository,
+        scenario_lock::ScenarioLockRepository,
+        segment::SegmentRepository,
+        service::ServiceRepository,
+        session::SessionRepository,
+        theme::ThemeRepository,
+        user::UserRepository,
+    },
+    rate_limit::IpRateLimiter,
 };
 
 pub mod config;
@@ -12,7 +34,11 @@ pub mod domains;
 pub mod error;
 pub mod extractors;
 pub mod handlers;
+pub mod mailer;
+pub mod rate_limit;
 pub mod repositories;
+#[cfg(test)]
+mod test_support;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -23,5 +49,36 @@ pub struct AppState {
     pub service_repository: Arc<dyn ServiceRepository>,
     pub job_repository: Arc<dyn JobRepository>,
     pub account_item_repository: Arc<dyn AccountItemRepository>,
+    pub pl_entry_repository: Arc<dyn PlEntryRepository>,
+    pub pl_sandbox_repository: Arc<dyn PlSandboxRepository>,
+    pub pl_snapshot_repository: Arc<dyn PlSnapshotRepository>,
+    pub scenario_lock_repository: Arc<dyn ScenarioLockRepository>,
+    pub fiscal_period_repository: Arc<dyn FiscalPeriodRepository>,
+    pub exchange_rate_repository: Arc<dyn ExchangeRateRepository>,
+    pub allocation_repository: Arc<dyn AllocationRepository>,
+    pub session_repository: Arc<dyn SessionRepository>,
+    pub invitation_repository: Arc<dyn InvitationRepository>,
+    pub password_reset_repository: Arc<dyn PasswordResetRepository>,
+    pub login_attempt_repository: Arc<dyn LoginAttemptRepository>,
+    pub mailer: Arc<dyn Mailer>,
+    pub fiscal_calendar: FiscalCalendar,
+    pub job_budget_policy: BudgetPolicy,
+    /// 帳票の金額を換算する通貨
+    pub reporting_currency: String,
     pub jwt_secret: String,
+    /// アクセストークンの有効期間
+    pub access_token_ttl: Duration,
+    /// リフレッシュトークンの有効期間（リフレッシュのたびに延長する）
+    pub refresh_token_ttl: Duration,
+    /// 招待の有効期間
+    pub invitation_ttl: Duration,
+    /// パスワード再設定トークンの有効期間
+    pub password_reset_ttl: Duration,
+    /// メールに記載するフロントエンドのURL
+    pub app_url: String,
+    pub lockout_policy: LockoutPolicy,
+    /// 認証まわりのルートの流量制限
+    pub auth_rate_limiter: IpRateLimiter,
+    /// X-Forwarded-For をクライアントのアドレスとして信頼するかどうか
+    pub trust_proxy_headers: bool,
 }
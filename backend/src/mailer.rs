@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+use uuid::Uuid;
+
+use crate::{
+    domains::mailer::{Mail, Mailer},
+    error::AppError,
+};
+
+/// SMTPサーバー（STARTTLS）から送信する
+#[derive(Clone)]
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(
+        host: &str,
+        port: u16,
+        credentials: Option<(String, String)>,
+        from: &str,
+    ) -> anyhow::Result<Self> {
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?.port(port);
+        if let Some((username, password)) = credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: from.parse()?,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: Mail) -> Result<(), AppError> {
+        let to: Mailbox = mail
+            .to
+            .parse()
+            .map_err(|e| AppError::Validation(format!("Invalid recipient {}: {}", mail.to, e)))?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(mail.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(mail.body)
+            .map_err(|e| anyhow::anyhow!("Mail build failed: {}", e))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| anyhow::anyhow!("Mail delivery failed: {}", e))?;
+
+        Ok(())
+    }
+}
+
+/// 送信せずに宛先と件名だけをログに出力し、ディレクトリが指定されていれば本文を含めてファイルに書き出す
+///
+/// 本文には招待やパスワード再設定のトークンが含まれるため、ログには出さない
+#[derive(Debug, Clone)]
+pub struct FileMailer {
+    dir: Option<PathBuf>,
+}
+
+impl FileMailer {
+    pub fn new(dir: Option<PathBuf>) -> anyhow::Result<Self> {
+        if let Some(dir) = &dir {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        Ok(Self { dir })
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, mail: Mail) -> Result<(), AppError> {
+        tracing::info!("Mail to {} (not sent): {}", mail.to, mail.subject);
+
+        if let Some(dir) = &self.dir {
+            let content = format!(
+                "To: {}\nSubject: {}\n\n{}",
+                mail.to, mail.subject, mail.body
+            );
+            let path = dir.join(format!(
+                "{}_{}.eml",
+                Utc::now().format("%Y%m%d%H%M%S"),
+                Uuid::new_v4()
+            ));
+            tokio::fs::write(&path, content)
+                .await
+                .map_err(|e| anyhow::anyhow!("Mail write to {} failed: {}", path.display(), e))?;
+        }
+
+        Ok(())
+    }
+}
@@ -4,21 +4,41 @@ use axum::{
         HeaderValue, Method,
         header::{AUTHORIZATION, CONTENT_TYPE},
     },
-    routing::{delete, get, patch, post},
+    middleware,
+    routing::{delete, get, patch, post, put},
 };
+use chrono::Duration;
 use std::{
-    net::{Ipv4Addr, SocketAddrV4},
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    path::PathBuf,
     sync::Arc,
 };
 use tokio::net::TcpListener;
 use tower_http::cors::CorsLayer;
 
 use ghost_api::{
-    AppState, config, db, handlers,
+    AppState, config, db,
+    domains::{
+        budget::BudgetPolicy,
+        exchange_rate::normalize_currency,
+        fiscal::FiscalCalendar,
+        login_attempt::LockoutPolicy,
+        mailer::{Mailer, MailerKind},
+        user::{CreateUserParam, UserRepository, normalize_email, validate_new_password},
+    },
+    handlers,
+    mailer::{FileMailer, SmtpMailer},
+    rate_limit::{self, IpRateLimiter},
     repositories::{
-        account_item::AccountItemRepositoryImpl, job::JobRepositoryImpl,
-        project::ProjectRepositoryImpl, segment::SegmentRepositoryImpl,
-        service::ServiceRepositoryImpl, theme::ThemeRepositoryImpl, user::UserRepositoryImpl,
+        account_item::AccountItemRepositoryImpl, allocation::AllocationRepositoryImpl,
+        exchange_rate::ExchangeRateRepositoryImpl, fiscal::FiscalPeriodRepositoryImpl,
+        invitation::InvitationRepositoryImpl, job::JobRepositoryImpl,
+        login_attempt::LoginAttemptRepositoryImpl, password_reset::PasswordResetRepositoryImpl,
+        pl_entry::PlEntryRepositoryImpl, pl_sandbox::PlSandboxRepositoryImpl,
+        pl_snapshot::PlSnapshotRepositoryImpl, project::ProjectRepositoryImpl,
+        scenario_lock::ScenarioLockRepositoryImpl, segment::SegmentRepositoryImpl,
+        service::ServiceRepositoryImpl, session::SessionRepositoryImpl, theme::ThemeRepositoryImpl,
+        user::UserRepositoryImpl,
     },
 };
 
@@ -41,13 +61,75 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     db::migrate_run(&pool).await?;
     tracing::info!("Migration complete");
 
+    let reporting_currency = normalize_currency(&config.reporting_currency)?;
+
     let user_repository = UserRepositoryImpl::new(pool.clone());
+
+    // 最初の管理者は招待できないため、ユーザーが1人もいない場合に限り環境変数から登録する
+    if let Some((email, password)) = config
+        .initial_admin_email
+        .as_deref()
+        .zip(config.initial_admin_password.clone())
+    {
+        validate_new_password(&password)?;
+        let params = CreateUserParam {
+            employee_id: config.initial_admin_employee_id.clone(),
+            username: config.initial_admin_username.clone(),
+            name: config.initial_admin_name.clone(),
+            email: normalize_email(email)?,
+            password,
+        };
+        match user_repository.create_initial_admin(params).await? {
+            Some(user) => tracing::info!("Registered the initial admin {}", user.id),
+            None => tracing::info!("Users already exist; skipped registering the initial admin"),
+        }
+    }
     let theme_repository = ThemeRepositoryImpl::new(pool.clone());
     let project_repository = ProjectRepositoryImpl::new(pool.clone());
     let segment_repository = SegmentRepositoryImpl::new(pool.clone());
     let service_repository = ServiceRepositoryImpl::new(pool.clone());
     let job_repository = JobRepositoryImpl::new(pool.clone());
     let account_item_repository = AccountItemRepositoryImpl::new(pool.clone());
+    let pl_entry_repository = PlEntryRepositoryImpl::new(pool.clone(), reporting_currency.clone());
+    let pl_sandbox_repository = PlSandboxRepositoryImpl::new(pool.clone());
+    let pl_snapshot_repository =
+        PlSnapshotRepositoryImpl::new(pool.clone(), reporting_currency.clone());
+    let scenario_lock_repository = ScenarioLockRepositoryImpl::new(pool.clone());
+    let fiscal_period_repository = FiscalPeriodRepositoryImpl::new(pool.clone());
+    let exchange_rate_repository =
+        ExchangeRateRepositoryImpl::new(pool.clone(), reporting_currency.clone());
+    let allocation_repository =
+        AllocationRepositoryImpl::new(pool.clone(), reporting_currency.clone());
+    let invitation_repository = InvitationRepositoryImpl::new(pool.clone());
+    let password_reset_repository = PasswordResetRepositoryImpl::new(pool.clone());
+    let login_attempt_repository = LoginAttemptRepositoryImpl::new(pool.clone());
+    let session_repository = SessionRepositoryImpl::new(
+        pool.clone(),
+        std::time::Duration::from_secs(config.auth_cache_ttl_seconds),
+    );
+
+    let mailer: Arc<dyn Mailer> = match config.mailer.parse::<MailerKind>()? {
+        MailerKind::Smtp => {
+            let host = config
+                .smtp_host
+                .as_deref()
+                .ok_or("SMTP_HOST is required when MAILER=smtp")?;
+            let credentials = config
+                .smtp_username
+                .clone()
+                .zip(config.smtp_password.clone());
+            Arc::new(SmtpMailer::new(
+                host,
+                config.smtp_port,
+                credentials,
+                &config.mail_from,
+            )?)
+        }
+        MailerKind::File => Arc::new(FileMailer::new(config.mail_dir.clone().map(PathBuf::from))?),
+    };
+
+    let fiscal_calendar = FiscalCalendar::new(config.fiscal_year_start_month)?;
+    let job_budget_policy: BudgetPolicy = config.job_budget_policy.parse()?;
 
     let state = AppState {
         user_repository: Arc::new(user_repository),
@@ -57,7 +139,36 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         service_repository: Arc::new(service_repository),
         job_repository: Arc::new(job_repository),
         account_item_repository: Arc::new(account_item_repository),
+        pl_entry_repository: Arc::new(pl_entry_repository),
+        pl_sandbox_repository: Arc::new(pl_sandbox_repository),
+        pl_snapshot_repository: Arc::new(pl_snapshot_repository),
+        scenario_lock_repository: Arc::new(scenario_lock_repository),
+        fiscal_period_repository: Arc::new(fiscal_period_repository),
+        exchange_rate_repository: Arc::new(exchange_rate_repository),
+        allocation_repository: Arc::new(allocation_repository),
+        session_repository: Arc::new(session_repository),
+        invitation_repository: Arc::new(invitation_repository),
+        password_reset_repository: Arc::new(password_reset_repository),
+        login_attempt_repository: Arc::new(login_attempt_repository),
+        mailer,
+        fiscal_calendar,
+        job_budget_policy,
+        reporting_currency,
         jwt_secret: config.jwt_secret,
+        access_token_ttl: Duration::minutes(config.access_token_ttl_minutes),
+        refresh_token_ttl: Duration::days(config.refresh_token_ttl_days),
+        invitation_ttl: Duration::days(config.invitation_ttl_days),
+        password_reset_ttl: Duration::minutes(config.password_reset_ttl_minutes),
+        app_url: config.app_url,
+        lockout_policy: LockoutPolicy {
+            max_failures: config.login_max_failures,
+            lockout: Duration::minutes(config.login_lockout_minutes),
+        },
+        auth_rate_limiter: IpRateLimiter::new(
+            config.auth_rate_limit_per_minute,
+            std::time::Duration::from_secs(60),
+        ),
+        trust_proxy_headers: config.trust_proxy_headers,
     };
 
     let cors = CorsLayer::new()
@@ -71,23 +182,150 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         ])
         .allow_headers([CONTENT_TYPE, AUTHORIZATION]);
 
-    let app = Router::new()
-        .route("/", get(|| async { "Ghost API v2" }))
+    // 認証前に呼べるルートは、IPアドレスごとに流量を制限する
+    let auth_routes = Router::new()
         .route("/signup", post(handlers::user::create_user))
         .route("/login", post(handlers::auth::login))
+        .route("/token/refresh", post(handlers::auth::refresh_token))
+        .route(
+            "/password/forgot",
+            post(handlers::auth::request_password_reset),
+        )
+        .route("/password/reset", post(handlers::auth::reset_password))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            rate_limit::limit_auth_requests,
+        ));
+
+    let app = Router::new()
+        .route("/", get(|| async { "Ghost API v2" }))
+        .merge(auth_routes)
+        .route("/logout", post(handlers::auth::logout))
+        .route(
+            "/login-events",
+            get(handlers::login_attempt::list_login_events),
+        )
         .route("/users/{uid}", get(handlers::user::get_user))
+        .route("/invitations", get(handlers::invitation::list_invitations))
+        .route(
+            "/invitations",
+            post(handlers::invitation::create_invitation),
+        )
+        .route(
+            "/invitations/{iid}",
+            delete(handlers::invitation::revoke_invitation),
+        )
+        .route("/users/{uid}/role", put(handlers::user::update_user_role))
+        .route(
+            "/users/{uid}/deactivate",
+            post(handlers::user::deactivate_user),
+        )
+        .route(
+            "/users/{uid}/reactivate",
+            post(handlers::user::reactivate_user),
+        )
+        .route("/users/{uid}/unlock", post(handlers::user::unlock_user))
+        .route(
+            "/users/{uid}/sessions",
+            delete(handlers::user::revoke_user_sessions),
+        )
         .route("/themes", get(handlers::theme::list_themes))
         .route("/themes", post(handlers::theme::create_theme))
         .route("/themes/{tid}", get(handlers::theme::get_theme))
         .route("/themes/{tid}", patch(handlers::theme::update_theme))
         .route("/themes/{tid}", delete(handlers::theme::delete_theme))
+        .route("/themes/{tid}/pl", get(handlers::report::theme_rollup))
+        .route(
+            "/themes/{tid}/pl/variance",
+            get(handlers::report::theme_variance),
+        )
         .route("/projects", get(handlers::project::list_projects))
         .route("/projects", post(handlers::project::create_project))
         .route("/projects/{pid}", get(handlers::project::get_project))
         .route("/projects/{pid}", patch(handlers::project::update_project))
         .route("/projects/{pid}", delete(handlers::project::delete_project))
+        .route(
+            "/projects/{pid}/pl",
+            get(handlers::pl_entry::list_pl_entries),
+        )
+        .route(
+            "/projects/{pid}/pl/{scenario}",
+            put(handlers::pl_entry::upsert_pl_entries),
+        )
+        .route(
+            "/projects/{pid}/pl/{scenario}/csv",
+            get(handlers::pl_entry::export_pl_csv),
+        )
+        .route(
+            "/projects/{pid}/pl/{scenario}/csv",
+            put(handlers::pl_entry::import_pl_csv),
+        )
+        .route(
+            "/projects/{pid}/pl/{scenario}/versions",
+            get(handlers::pl_snapshot::list_pl_snapshots),
+        )
+        .route(
+            "/projects/{pid}/pl/{scenario}/versions/diff",
+            get(handlers::pl_snapshot::diff_pl_snapshots),
+        )
+        .route(
+            "/projects/{pid}/pl/{scenario}/versions/{version}",
+            get(handlers::pl_snapshot::get_pl_snapshot),
+        )
+        .route(
+            "/projects/{pid}/pl/xlsx",
+            get(handlers::report::export_project_xlsx),
+        )
+        .route(
+            "/projects/{pid}/pl/exec-plan-adjust",
+            post(handlers::pl_entry::recompute_exec_plan_adjust),
+        )
+        .route(
+            "/projects/{pid}/pl/budget",
+            get(handlers::report::project_budget),
+        )
+        .route(
+            "/projects/{pid}/pl/summary",
+            get(handlers::report::project_summary),
+        )
+        .route(
+            "/projects/{pid}/pl/variance",
+            get(handlers::report::project_variance),
+        )
+        .route(
+            "/projects/{pid}/pl/locks",
+            get(handlers::scenario_lock::list_scenario_locks),
+        )
+        .route(
+            "/projects/{pid}/pl/locks",
+            post(handlers::scenario_lock::lock_scenario),
+        )
+        .route(
+            "/projects/{pid}/pl/locks/{lid}/unlock",
+            post(handlers::scenario_lock::unlock_scenario),
+        )
+        .route("/sandboxes", get(handlers::pl_sandbox::list_sandboxes))
+        .route("/sandboxes", post(handlers::pl_sandbox::create_sandbox))
+        .route("/sandboxes/{sid}", get(handlers::pl_sandbox::get_sandbox))
+        .route(
+            "/sandboxes/{sid}",
+            delete(handlers::pl_sandbox::delete_sandbox),
+        )
+        .route(
+            "/sandboxes/{sid}/transform",
+            post(handlers::pl_sandbox::transform_sandbox),
+        )
+        .route(
+            "/sandboxes/{sid}/variance",
+            get(handlers::pl_sandbox::sandbox_variance),
+        )
         .route("/segments", get(handlers::segment::list_segment))
         .route("/segments", post(handlers::segment::create_segment))
+        .route("/segments/{sid}/pl", get(handlers::report::segment_rollup))
+        .route(
+            "/segments/{sid}/pl/xlsx",
+            get(handlers::report::export_segment_xlsx),
+        )
         .route("/services", get(handlers::service::list_service))
         .route("/services", post(handlers::service::create_service))
         .route(
@@ -102,16 +340,130 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "/services/{identifier}",
             delete(handlers::service::delete_service),
         )
+        .route(
+            "/services/{identifier}/pl",
+            get(handlers::report::service_rollup),
+        )
         .route("/jobs", get(handlers::job::list_jobs))
         .route("/jobs", post(handlers::job::create_job))
         .route("/jobs/{jid}", get(handlers::job::get_job))
         .route("/jobs/{jid}", patch(handlers::job::update_job))
         .route("/jobs/{jid}", delete(handlers::job::delete_job))
+        .route(
+            "/jobs/{jid}/assignees",
+            get(handlers::job::list_job_assignees),
+        )
+        .route(
+            "/jobs/{jid}/assignees",
+            put(handlers::job::replace_job_assignees),
+        )
+        .route(
+            "/jobs/{jid}/pl",
+            get(handlers::pl_entry::list_job_pl_entries),
+        )
+        .route(
+            "/jobs/{jid}/pl/{scenario}",
+            put(handlers::pl_entry::upsert_job_pl_entries),
+        )
+        .route("/jobs/{jid}/pl/summary", get(handlers::report::job_summary))
+        .route(
+            "/jobs/{jid}/pl/variance",
+            get(handlers::report::job_variance),
+        )
         .route(
             "/account-items",
             get(handlers::account_item::list_account_items),
         )
+        .route(
+            "/account-items",
+            post(handlers::account_item::create_account_item),
+        )
+        .route(
+            "/account-items/order",
+            put(handlers::account_item::reorder_account_items),
+        )
+        .route(
+            "/account-items/{aid}",
+            get(handlers::account_item::get_account_item),
+        )
+        .route(
+            "/account-items/{aid}",
+            patch(handlers::account_item::update_account_item),
+        )
+        .route(
+            "/account-items/{aid}",
+            delete(handlers::account_item::delete_account_item),
+        )
+        .route(
+            "/fiscal-years/{fy}/periods",
+            get(handlers::fiscal::list_fiscal_periods),
+        )
+        .route(
+            "/fiscal-years/{fy}/periods/{period}",
+            get(handlers::fiscal::get_fiscal_period),
+        )
+        .route(
+            "/fiscal-years/{fy}/periods/{period}/close",
+            post(handlers::fiscal::close_fiscal_period),
+        )
+        .route(
+            "/fiscal-years/{fy}/periods/{period}/reopen",
+            post(handlers::fiscal::reopen_fiscal_period),
+        )
+        .route(
+            "/fiscal-calendar/resolve",
+            get(handlers::fiscal::resolve_fiscal_period),
+        )
+        .route(
+            "/exchange-rates",
+            get(handlers::exchange_rate::list_exchange_rates),
+        )
+        .route(
+            "/exchange-rates",
+            put(handlers::exchange_rate::upsert_exchange_rate),
+        )
+        .route(
+            "/exchange-rates/scenarios",
+            get(handlers::exchange_rate::list_scenario_rate_types),
+        )
+        .route(
+            "/exchange-rates/scenarios/{scenario}",
+            put(handlers::exchange_rate::update_scenario_rate_type),
+        )
+        .route(
+            "/exchange-rates/{rid}",
+            delete(handlers::exchange_rate::delete_exchange_rate),
+        )
+        .route(
+            "/allocation-rules",
+            get(handlers::allocation::list_allocation_rules),
+        )
+        .route(
+            "/allocation-rules",
+            post(handlers::allocation::create_allocation_rule),
+        )
+        .route(
+            "/allocation-rules/{rid}",
+            get(handlers::allocation::get_allocation_rule),
+        )
+        .route(
+            "/allocation-rules/{rid}",
+            put(handlers::allocation::update_allocation_rule),
+        )
+        .route(
+            "/allocation-rules/{rid}",
+            delete(handlers::allocation::delete_allocation_rule),
+        )
+        .route(
+            "/allocation-rules/{rid}/run",
+            post(handlers::allocation::run_allocation),
+        )
+        .route(
+            "/allocation-runs/{run_id}/reverse",
+            post(handlers::allocation::reverse_allocation),
+        )
         .route("/me", get(handlers::auth::get_current_user))
+        .route("/me/password", put(handlers::auth::change_password))
         .layer(cors)
         .with_state(state);
 
@@ -123,7 +475,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     tracing::info!("Server listening on {}", addr);
 
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
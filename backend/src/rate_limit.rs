@@ -0,0 +1,66 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use moka::sync::Cache;
+
+use crate::{AppState, error::AppError, extractors::ClientInfo};
+
+/// IPアドレスごとに、一定時間内のリクエスト数を制限する
+///
+/// 最初のリクエストから window の間を1区切りとして数える（固定ウィンドウ）
+#[derive(Clone)]
+pub struct IpRateLimiter {
+    counts: Cache<String, Arc<AtomicU32>>,
+    limit: u32,
+}
+
+impl IpRateLimiter {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        let counts = Cache::builder()
+            .max_capacity(100_000)
+            .time_to_live(window)
+            .build();
+
+        Self { counts, limit }
+    }
+
+    /// リクエストを数え、上限以内であれば true を返す
+    pub fn check(&self, ip: &str) -> bool {
+        let count = self
+            .counts
+            .get_with(ip.to_string(), || Arc::new(AtomicU32::new(0)));
+
+        count.fetch_add(1, Ordering::Relaxed) < self.limit
+    }
+}
+
+/// 認証まわりのルートに対するIPアドレスごとの流量制限
+pub async fn limit_auth_requests(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if !state.auth_rate_limiter.check(&client.ip) {
+        tracing::warn!(
+            "Rate limit exceeded on {} from {}",
+            request.uri().path(),
+            client.ip
+        );
+        return Err(AppError::TooManyRequests(
+            "Too many requests; try again later".to_string(),
+        ));
+    }
+
+    Ok(next.run(request).await)
+}
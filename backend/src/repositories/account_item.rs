@@ -1,8 +1,15 @@
 use crate::{
-    domains::account_item::{AccountItem, AccountItemRepository, AccountType},
+    domains::account_item::{
+        AccountItem, AccountItemRepository, AccountType, CreateAccountItemParam,
+        UpdateAccountItemParam,
+    },
     error::AppError,
 };
 use sqlx::PgPool;
+use uuid::Uuid;
+
+/// 表示順を振り直すときの間隔（間に科目を追加しやすくする）
+const DISPLAY_ORDER_STEP: i32 = 10;
 
 #[derive(Debug, Clone)]
 pub struct AccountItemRepositoryImpl {
@@ -25,6 +32,7 @@ impl AccountItemRepository for AccountItemRepositoryImpl {
                 id,
                 name,
                 account_type as "account_type: AccountType",
+                parent_id,
                 display_order,
                 description,
                 is_active,
@@ -43,4 +51,194 @@ impl AccountItemRepository for AccountItemRepositoryImpl {
 
         Ok(items)
     }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<AccountItem>, AppError> {
+        let item = sqlx::query_as!(
+            AccountItem,
+            r#"
+            SELECT
+                id,
+                name,
+                account_type as "account_type: AccountType",
+                parent_id,
+                display_order,
+                description,
+                is_active,
+                created_at,
+                updated_at
+            FROM account_items
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(item)
+    }
+
+    async fn create(&self, params: CreateAccountItemParam) -> Result<AccountItem, AppError> {
+        let item = sqlx::query_as!(
+            AccountItem,
+            r#"
+            INSERT INTO account_items (
+                name,
+                account_type,
+                parent_id,
+                display_order,
+                description,
+                is_active
+            )
+            VALUES (
+                $1,
+                $2,
+                $3,
+                COALESCE($4, (SELECT COALESCE(MAX(display_order), 0) + $5 FROM account_items)),
+                $6,
+                true
+            )
+            RETURNING
+                id,
+                name,
+                account_type as "account_type: AccountType",
+                parent_id,
+                display_order,
+                description,
+                is_active,
+                created_at,
+                updated_at
+            "#,
+            params.name,
+            params.account_type as AccountType,
+            params.parent_id,
+            params.display_order,
+            DISPLAY_ORDER_STEP,
+            params.description
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create account item: {:?}", e);
+            AppError::from(e)
+        })?;
+
+        Ok(item)
+    }
+
+    async fn update(
+        &self,
+        id: Uuid,
+        params: UpdateAccountItemParam,
+    ) -> Result<AccountItem, AppError> {
+        let item = sqlx::query_as!(
+            AccountItem,
+            r#"
+            UPDATE account_items
+            SET
+                name = COALESCE($1, name),
+                description = COALESCE($2, description),
+                parent_id = CASE WHEN $3 THEN $4 ELSE parent_id END,
+                display_order = COALESCE($5, display_order),
+                is_active = COALESCE($6, is_active),
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $7
+            RETURNING
+                id,
+                name,
+                account_type as "account_type: AccountType",
+                parent_id,
+                display_order,
+                description,
+                is_active,
+                created_at,
+                updated_at
+            "#,
+            params.name,
+            params.description,
+            params.parent_id.is_some(),
+            params.parent_id.flatten(),
+            params.display_order,
+            params.is_active,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AppError::NotFound(format!("Account item {} not found", id)))?;
+
+        Ok(item)
+    }
+
+    async fn reorder(&self, ids: Vec<Uuid>) -> Result<Vec<AccountItem>, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        for (index, id) in ids.iter().enumerate() {
+            let result = sqlx::query!(
+                r#"
+                UPDATE account_items
+                SET
+                    display_order = $1,
+                    updated_at = CURRENT_TIMESTAMP
+                WHERE id = $2
+                "#,
+                (index as i32 + 1) * DISPLAY_ORDER_STEP,
+                id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            if result.rows_affected() == 0 {
+                return Err(AppError::NotFound(format!("Account item {} not found", id)));
+            }
+        }
+
+        tx.commit().await?;
+
+        self.find_all().await
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let usage = sqlx::query!(
+            r#"
+            SELECT
+                EXISTS (SELECT 1 FROM pl_entries WHERE account_item_id = $1) as "has_entries!",
+                EXISTS (SELECT 1 FROM account_items WHERE parent_id = $1) as "has_children!"
+            "#,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if usage.has_entries {
+            return Err(AppError::Validation(format!(
+                "Account item {} has P&L entries, deactivate it instead",
+                id
+            )));
+        }
+        if usage.has_children {
+            return Err(AppError::Validation(format!(
+                "Account item {} has child items",
+                id
+            )));
+        }
+
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM account_items
+            WHERE id = $1
+            "#,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Account item {} not found", id)));
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,822 @@
+use std::collections::BTreeSet;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    domains::{
+        allocation::{
+            AllocationDriver, AllocationLine, AllocationRepository, AllocationRule,
+            AllocationRuleParam, AllocationRun, AllocationTarget, DriverWeight, SourceAmount,
+            allocate,
+        },
+        pl_entry::Scenario,
+    },
+    error::AppError,
+    repositories::{
+        pl_entry::{ensure_writable, sync_exec_plan_adjust},
+        pl_snapshot::lock_project,
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct AllocationRepositoryImpl {
+    pool: PgPool,
+    reporting_currency: String,
+}
+
+impl AllocationRepositoryImpl {
+    pub fn new(pool: PgPool, reporting_currency: String) -> Self {
+        Self {
+            pool,
+            reporting_currency,
+        }
+    }
+}
+
+/// 報告通貨に換算した月ごとの金額・配賦基準の値（換算レートが未登録の通貨を含む）
+struct ConvertedWeight {
+    project_id: Uuid,
+    month: NaiveDate,
+    weight: Decimal,
+    missing_currencies: Option<Vec<String>>,
+}
+
+#[async_trait::async_trait]
+impl AllocationRepository for AllocationRepositoryImpl {
+    async fn find_rules(&self) -> Result<Vec<AllocationRule>, AppError> {
+        let rules = sqlx::query_as!(
+            AllocationRule,
+            r#"
+            SELECT
+                id,
+                name,
+                description,
+                source_project_id,
+                source_account_item_id,
+                target_account_item_id,
+                scenario as "scenario: Scenario",
+                period_start,
+                period_end,
+                driver as "driver: AllocationDriver",
+                created_by,
+                updated_by,
+                created_at,
+                updated_at
+            FROM allocation_rules
+            ORDER BY name ASC, created_at ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    async fn find_rule(&self, id: Uuid) -> Result<Option<AllocationRule>, AppError> {
+        let rule = sqlx::query_as!(
+            AllocationRule,
+            r#"
+            SELECT
+                id,
+                name,
+                description,
+                source_project_id,
+                source_account_item_id,
+                target_account_item_id,
+                scenario as "scenario: Scenario",
+                period_start,
+                period_end,
+                driver as "driver: AllocationDriver",
+                created_by,
+                updated_by,
+                created_at,
+                updated_at
+            FROM allocation_rules
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(rule)
+    }
+
+    async fn find_targets(&self, rule_id: Uuid) -> Result<Vec<AllocationTarget>, AppError> {
+        let targets = sqlx::query_as!(
+            AllocationTarget,
+            r#"
+            SELECT t.project_id, t.ratio
+            FROM allocation_rule_targets t
+            JOIN projects p ON p.id = t.project_id
+            WHERE t.rule_id = $1
+            ORDER BY p.name ASC
+            "#,
+            rule_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(targets)
+    }
+
+    async fn create_rule(&self, params: AllocationRuleParam) -> Result<AllocationRule, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let rule = sqlx::query_as!(
+            AllocationRule,
+            r#"
+            INSERT INTO allocation_rules (
+                name,
+                description,
+                source_project_id,
+                source_account_item_id,
+                target_account_item_id,
+                scenario,
+                period_start,
+                period_end,
+                driver,
+                created_by,
+                updated_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10)
+            RETURNING
+                id,
+                name,
+                description,
+                source_project_id,
+                source_account_item_id,
+                target_account_item_id,
+                scenario as "scenario: Scenario",
+                period_start,
+                period_end,
+                driver as "driver: AllocationDriver",
+                created_by,
+                updated_by,
+                created_at,
+                updated_at
+            "#,
+            params.name,
+            params.description,
+            params.source_project_id,
+            params.source_account_item_id,
+            params.target_account_item_id,
+            params.scenario as Scenario,
+            params.period.start,
+            params.period.end,
+            params.driver as AllocationDriver,
+            params.user_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        insert_targets(&mut tx, rule.id, &params.targets).await?;
+
+        tx.commit().await?;
+
+        Ok(rule)
+    }
+
+    async fn update_rule(
+        &self,
+        id: Uuid,
+        params: AllocationRuleParam,
+    ) -> Result<AllocationRule, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let rule = sqlx::query_as!(
+            AllocationRule,
+            r#"
+            UPDATE allocation_rules
+            SET
+                name = $2,
+                description = $3,
+                source_project_id = $4,
+                source_account_item_id = $5,
+                target_account_item_id = $6,
+                scenario = $7,
+                period_start = $8,
+                period_end = $9,
+                driver = $10,
+                updated_by = $11,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            RETURNING
+                id,
+                name,
+                description,
+                source_project_id,
+                source_account_item_id,
+                target_account_item_id,
+                scenario as "scenario: Scenario",
+                period_start,
+                period_end,
+                driver as "driver: AllocationDriver",
+                created_by,
+                updated_by,
+                created_at,
+                updated_at
+            "#,
+            id,
+            params.name,
+            params.description,
+            params.source_project_id,
+            params.source_account_item_id,
+            params.target_account_item_id,
+            params.scenario as Scenario,
+            params.period.start,
+            params.period.end,
+            params.driver as AllocationDriver,
+            params.user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Allocation rule {} not found",
+            id
+        )))?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM allocation_rule_targets WHERE rule_id = $1
+            "#,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        insert_targets(&mut tx, id, &params.targets).await?;
+
+        tx.commit().await?;
+
+        Ok(rule)
+    }
+
+    async fn delete_rule(&self, id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM allocation_rules WHERE id = $1
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!(
+                "Allocation rule {} not found",
+                id
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn find_runs(&self, rule_id: Uuid) -> Result<Vec<AllocationRun>, AppError> {
+        let runs = sqlx::query_as!(
+            AllocationRun,
+            r#"
+            SELECT
+                id,
+                rule_id,
+                total_amount,
+                run_by,
+                run_at,
+                reversed_by,
+                reversed_at
+            FROM allocation_runs
+            WHERE rule_id = $1
+            ORDER BY run_at DESC
+            "#,
+            rule_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(runs)
+    }
+
+    async fn find_run(&self, id: Uuid) -> Result<Option<AllocationRun>, AppError> {
+        let run = sqlx::query_as!(
+            AllocationRun,
+            r#"
+            SELECT
+                id,
+                rule_id,
+                total_amount,
+                run_by,
+                run_at,
+                reversed_by,
+                reversed_at
+            FROM allocation_runs
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(run)
+    }
+
+    async fn run(
+        &self,
+        rule_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(AllocationRun, Vec<AllocationLine>), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        // 同じルールの実行やルールの更新が並行しないよう、ルールの行をロックしてから読み直す
+        let rule = sqlx::query_as!(
+            AllocationRule,
+            r#"
+            SELECT
+                id,
+                name,
+                description,
+                source_project_id,
+                source_account_item_id,
+                target_account_item_id,
+                scenario as "scenario: Scenario",
+                period_start,
+                period_end,
+                driver as "driver: AllocationDriver",
+                created_by,
+                updated_by,
+                created_at,
+                updated_at
+            FROM allocation_rules
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            rule_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Allocation rule {} not found",
+            rule_id
+        )))?;
+
+        // 配賦元の金額を読んでから明細を書き込むまでの間に、配賦元のPL明細が変わらないようにする
+        lock_project(&mut tx, rule.source_project_id).await?;
+        let sources = source_amounts(&mut tx, &rule, &self.reporting_currency).await?;
+        let weights = driver_weights(&mut tx, &rule, &self.reporting_currency).await?;
+        let lines = allocate(&rule, &sources, &weights)?;
+
+        let active = sqlx::query_scalar!(
+            r#"
+            SELECT id FROM allocation_runs
+            WHERE rule_id = $1 AND reversed_at IS NULL
+            "#,
+            rule.id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        // 配賦した明細はProjectの計画に含まれるため、前回と今回の配賦先の ExecPlanAdjust を再計算する
+        let mut project_ids: BTreeSet<Uuid> = lines.iter().map(|l| l.project_id).collect();
+        if let Some(run_id) = active {
+            project_ids.extend(run_project_ids(&mut tx, run_id).await?);
+        }
+        let sync_projects = rule.scenario.affects_exec_plan_adjust();
+        if sync_projects {
+            for project_id in &project_ids {
+                lock_project(&mut tx, *project_id).await?;
+            }
+        }
+
+        if let Some(run_id) = active {
+            reverse_run(&mut tx, run_id, user_id).await?;
+        }
+
+        let line_project_ids: BTreeSet<Uuid> = lines.iter().map(|l| l.project_id).collect();
+        for project_id in line_project_ids {
+            let dates: Vec<NaiveDate> = lines
+                .iter()
+                .filter(|l| l.project_id == project_id)
+                .map(|l| l.month)
+                .collect();
+            ensure_writable(&mut tx, project_id, rule.scenario, &dates).await?;
+        }
+
+        let total_amount: Decimal = lines
+            .iter()
+            .filter(|l| l.project_id != rule.source_project_id)
+            .map(|l| l.amount)
+            .sum();
+
+        let run = sqlx::query_as!(
+            AllocationRun,
+            r#"
+            INSERT INTO allocation_runs (rule_id, total_amount, run_by)
+            VALUES ($1, $2, $3)
+            RETURNING
+                id,
+                rule_id,
+                total_amount,
+                run_by,
+                run_at,
+                reversed_by,
+                reversed_at
+            "#,
+            rule.id,
+            total_amount,
+            user_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let project_ids: Vec<Uuid> = lines.iter().map(|l| l.project_id).collect();
+        let account_item_ids: Vec<Uuid> = lines.iter().map(|l| l.account_item_id).collect();
+        let dates: Vec<NaiveDate> = lines.iter().map(|l| l.month).collect();
+        let amounts: Vec<Decimal> = lines.iter().map(|l| l.amount).collect();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO pl_entries (
+                project_id,
+                scenario,
+                account_item_id,
+                date,
+                amount,
+                currency,
+                description,
+                allocation_run_id,
+                created_by,
+                updated_by,
+                created_at,
+                updated_at
+            )
+            SELECT
+                u.project_id,
+                $1,
+                u.account_item_id,
+                u.date,
+                u.amount,
+                $6,
+                $7,
+                $8,
+                $9, -- created_by
+                $9, -- updated_by
+                NOW(),
+                NOW()
+            FROM UNNEST(
+                $2::uuid[],
+                $3::uuid[],
+                $4::date[],
+                $5::numeric[]
+            ) AS u(project_id, account_item_id, date, amount)
+            "#,
+            rule.scenario as Scenario,
+            &project_ids,
+            &account_item_ids,
+            &dates,
+            &amounts as &[Decimal],
+            self.reporting_currency,
+            format!("配賦: {}", rule.name),
+            run.id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to insert allocated entries: {:?}", e);
+            AppError::from(e)
+        })?;
+
+        if sync_projects {
+            for project_id in project_ids {
+                sync_exec_plan_adjust(&mut tx, project_id, &self.reporting_currency, user_id)
+                    .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok((run, lines))
+    }
+
+    async fn reverse(&self, run_id: Uuid, user_id: Uuid) -> Result<AllocationRun, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let current = sqlx::query!(
+            r#"
+            SELECT u.reversed_at, r.scenario as "scenario: Scenario"
+            FROM allocation_runs u
+            JOIN allocation_rules r ON r.id = u.rule_id
+            WHERE u.id = $1
+            FOR UPDATE OF u
+            "#,
+            run_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Allocation run {} not found",
+            run_id
+        )))?;
+
+        if current.reversed_at.is_some() {
+            return Err(AppError::Validation(format!(
+                "Allocation run {} is already reversed",
+                run_id
+            )));
+        }
+
+        let project_ids = run_project_ids(&mut tx, run_id).await?;
+        let sync_projects = current.scenario.affects_exec_plan_adjust();
+        if sync_projects {
+            for project_id in &project_ids {
+                lock_project(&mut tx, *project_id).await?;
+            }
+        }
+
+        let run = reverse_run(&mut tx, run_id, user_id).await?;
+
+        if sync_projects {
+            for project_id in project_ids {
+                sync_exec_plan_adjust(&mut tx, project_id, &self.reporting_currency, user_id)
+                    .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(run)
+    }
+}
+
+/// 配賦元の月ごとの金額（配賦で生成した明細は含まない）
+async fn source_amounts(
+    conn: &mut PgConnection,
+    rule: &AllocationRule,
+    reporting_currency: &str,
+) -> Result<Vec<SourceAmount>, AppError> {
+    let rows = sqlx::query_as!(
+        ConvertedWeight,
+        r#"
+        SELECT
+            e.project_id,
+            date_trunc('month', e.date)::date as "month!",
+            COALESCE(ROUND(SUM(e.amount * x.rate), 4), 0) as "weight!",
+            array_agg(DISTINCT e.currency::text) FILTER (WHERE x.rate IS NULL) as missing_currencies
+        FROM pl_entries e
+        CROSS JOIN LATERAL (
+            SELECT exchange_rate(e.scenario, e.currency, $6, e.date) as rate
+        ) x
+        WHERE e.project_id = $1
+          AND e.account_item_id = $2
+          AND e.scenario = $3
+          AND e.date BETWEEN $4 AND $5
+          AND e.allocation_run_id IS NULL
+        GROUP BY e.project_id, 2
+        ORDER BY 2 ASC
+        "#,
+        rule.source_project_id,
+        rule.source_account_item_id,
+        rule.scenario as Scenario,
+        rule.period_start,
+        rule.period_end,
+        reporting_currency
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to fetch allocation source amounts: {:?}", e);
+        AppError::from(e)
+    })?;
+
+    Ok(into_weights(rows)?
+        .into_iter()
+        .map(|row| SourceAmount {
+            month: row.month,
+            amount: row.weight,
+        })
+        .collect())
+}
+
+/// 配賦先の月ごとの配賦基準の値
+async fn driver_weights(
+    conn: &mut PgConnection,
+    rule: &AllocationRule,
+    reporting_currency: &str,
+) -> Result<Vec<DriverWeight>, AppError> {
+    let rows = match rule.driver {
+        AllocationDriver::FixedRatio => sqlx::query_as!(
+            ConvertedWeight,
+            r#"
+            SELECT
+                t.project_id,
+                m.month as "month!",
+                t.ratio as "weight!",
+                NULL::text[] as missing_currencies
+            FROM allocation_rule_targets t
+            CROSS JOIN LATERAL (
+                SELECT generate_series($2::date, $3::date, interval '1 month')::date as month
+            ) m
+            WHERE t.rule_id = $1 AND t.ratio IS NOT NULL
+            ORDER BY 2 ASC
+            "#,
+            rule.id,
+            rule.period_start,
+            rule.period_end
+        )
+        .fetch_all(&mut *conn)
+        .await?,
+        // 配賦先の指定がなければ配賦元以外の全Projectを対象にする
+        AllocationDriver::RevenueShare => sqlx::query_as!(
+            ConvertedWeight,
+            r#"
+            SELECT
+                e.project_id,
+                date_trunc('month', e.date)::date as "month!",
+                COALESCE(ROUND(SUM(e.amount * x.rate), 4), 0) as "weight!",
+                array_agg(DISTINCT e.currency::text) FILTER (WHERE x.rate IS NULL) as missing_currencies
+            FROM pl_entries e
+            JOIN account_items a ON a.id = e.account_item_id
+            CROSS JOIN LATERAL (
+                SELECT exchange_rate(e.scenario, e.currency, $6, e.date) as rate
+            ) x
+            WHERE a.account_type = 'Revenue'
+              AND e.scenario = $3
+              AND e.date BETWEEN $4 AND $5
+              AND e.allocation_run_id IS NULL
+              AND e.project_id <> $2
+              AND (
+                  NOT EXISTS (SELECT 1 FROM allocation_rule_targets t WHERE t.rule_id = $1)
+                  OR e.project_id IN (
+                      SELECT t.project_id FROM allocation_rule_targets t WHERE t.rule_id = $1
+                  )
+              )
+            GROUP BY e.project_id, 2
+            ORDER BY 2 ASC
+            "#,
+            rule.id,
+            rule.source_project_id,
+            rule.scenario as Scenario,
+            rule.period_start,
+            rule.period_end,
+            reporting_currency
+        )
+        .fetch_all(&mut *conn)
+        .await?,
+        AllocationDriver::JobCount => sqlx::query_as!(
+            ConvertedWeight,
+            r#"
+            SELECT
+                j.project_id as "project_id!",
+                date_trunc('month', e.date)::date as "month!",
+                COUNT(DISTINCT j.id)::numeric as "weight!",
+                NULL::text[] as missing_currencies
+            FROM pl_entries e
+            JOIN jobs j ON j.id = e.job_id
+            WHERE e.scenario IN ('JobPlan', 'Actual')
+              AND e.date BETWEEN $3 AND $4
+              AND j.project_id <> $2
+              AND (
+                  NOT EXISTS (SELECT 1 FROM allocation_rule_targets t WHERE t.rule_id = $1)
+                  OR j.project_id IN (
+                      SELECT t.project_id FROM allocation_rule_targets t WHERE t.rule_id = $1
+                  )
+              )
+            GROUP BY j.project_id, 2
+            ORDER BY 2 ASC
+            "#,
+            rule.id,
+            rule.source_project_id,
+            rule.period_start,
+            rule.period_end
+        )
+        .fetch_all(&mut *conn)
+        .await?,
+    };
+
+    Ok(into_weights(rows)?
+        .into_iter()
+        .map(|row| DriverWeight {
+            project_id: row.project_id,
+            month: row.month,
+            weight: row.weight,
+        })
+        .collect())
+}
+
+/// 換算レートが未登録の通貨があればエラーとする
+fn into_weights(rows: Vec<ConvertedWeight>) -> Result<Vec<ConvertedWeight>, AppError> {
+    if let Some((row, currencies)) = rows.iter().find_map(|row| {
+        row.missing_currencies
+            .as_ref()
+            .filter(|currencies| !currencies.is_empty())
+            .map(|currencies| (row, currencies))
+    }) {
+        return Err(AppError::MissingExchangeRate(format!(
+            "No exchange rate for {} in {}",
+            currencies.join(", "),
+            row.month.format("%Y-%m")
+        )));
+    }
+
+    Ok(rows)
+}
+
+async fn insert_targets(
+    conn: &mut PgConnection,
+    rule_id: Uuid,
+    targets: &[AllocationTarget],
+) -> Result<(), AppError> {
+    let project_ids: Vec<Uuid> = targets.iter().map(|t| t.project_id).collect();
+    let ratios: Vec<Option<Decimal>> = targets.iter().map(|t| t.ratio).collect();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO allocation_rule_targets (rule_id, project_id, ratio)
+        SELECT $1, u.project_id, u.ratio
+        FROM UNNEST($2::uuid[], $3::numeric[]) AS u(project_id, ratio)
+        "#,
+        rule_id,
+        &project_ids,
+        &ratios as &[Option<Decimal>]
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// 実行で明細を生成したProject
+async fn run_project_ids(conn: &mut PgConnection, run_id: Uuid) -> Result<Vec<Uuid>, AppError> {
+    let project_ids = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT project_id FROM pl_entries
+        WHERE allocation_run_id = $1
+        ORDER BY project_id
+        "#,
+        run_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(project_ids)
+}
+
+/// 実行で生成した明細を削除して取り消し済みにする（締め済みの月やロック中の計画は取り消せない）
+async fn reverse_run(
+    conn: &mut PgConnection,
+    run_id: Uuid,
+    user_id: Uuid,
+) -> Result<AllocationRun, AppError> {
+    let generated = sqlx::query!(
+        r#"
+        SELECT
+            project_id,
+            scenario as "scenario: Scenario",
+            array_agg(date) as "dates!"
+        FROM pl_entries
+        WHERE allocation_run_id = $1
+        GROUP BY project_id, scenario
+        "#,
+        run_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for row in generated {
+        ensure_writable(conn, row.project_id, row.scenario, &row.dates).await?;
+    }
+
+    sqlx::query!(
+        r#"
+        DELETE FROM pl_entries WHERE allocation_run_id = $1
+        "#,
+        run_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let run = sqlx::query_as!(
+        AllocationRun,
+        r#"
+        UPDATE allocation_runs
+        SET reversed_by = $2, reversed_at = CURRENT_TIMESTAMP
+        WHERE id = $1
+        RETURNING
+            id,
+            rule_id,
+            total_amount,
+            run_by,
+            run_at,
+            reversed_by,
+            reversed_at
+        "#,
+        run_id,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(run)
+}
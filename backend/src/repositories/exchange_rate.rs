@@ -0,0 +1,199 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    domains::{
+        exchange_rate::{
+            ExchangeRate, ExchangeRateRepository, RateType, ScenarioRateType,
+            UpsertExchangeRateParam,
+        },
+        pl_entry::Scenario,
+    },
+    error::AppError,
+};
+
+#[derive(Debug, Clone)]
+pub struct ExchangeRateRepositoryImpl {
+    pool: PgPool,
+    reporting_currency: String,
+}
+
+impl ExchangeRateRepositoryImpl {
+    pub fn new(pool: PgPool, reporting_currency: String) -> Self {
+        Self {
+            pool,
+            reporting_currency,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ExchangeRateRepository for ExchangeRateRepositoryImpl {
+    async fn find_all(
+        &self,
+        rate_type: Option<RateType>,
+        currency: Option<String>,
+    ) -> Result<Vec<ExchangeRate>, AppError> {
+        let rates = sqlx::query_as!(
+            ExchangeRate,
+            r#"
+            SELECT
+                id,
+                rate_type as "rate_type: RateType",
+                currency,
+                month,
+                rate,
+                created_by,
+                updated_by,
+                created_at,
+                updated_at
+            FROM exchange_rates
+            WHERE ($1::exchange_rate_type IS NULL OR rate_type = $1)
+              AND ($2::text IS NULL OR currency = $2)
+            ORDER BY rate_type ASC, currency ASC, month ASC
+            "#,
+            rate_type as Option<RateType>,
+            currency
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rates)
+    }
+
+    async fn upsert(&self, params: UpsertExchangeRateParam) -> Result<ExchangeRate, AppError> {
+        let rate = sqlx::query_as!(
+            ExchangeRate,
+            r#"
+            INSERT INTO exchange_rates (
+                rate_type,
+                currency,
+                month,
+                rate,
+                created_by,
+                updated_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $5)
+            ON CONFLICT (rate_type, currency, month)
+            DO UPDATE SET
+                rate = EXCLUDED.rate,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING
+                id,
+                rate_type as "rate_type: RateType",
+                currency,
+                month,
+                rate,
+                created_by,
+                updated_by,
+                created_at,
+                updated_at
+            "#,
+            params.rate_type as RateType,
+            params.currency,
+            params.month,
+            params.rate,
+            params.user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(rate)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM exchange_rates
+            WHERE id = $1
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!(
+                "Exchange rate {} not found",
+                id
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn find_rate(
+        &self,
+        scenario: Scenario,
+        currency: &str,
+        date: NaiveDate,
+    ) -> Result<Option<Decimal>, AppError> {
+        let rate = sqlx::query_scalar!(
+            r#"
+            SELECT exchange_rate($1, $2, $3, $4)
+            "#,
+            scenario as Scenario,
+            currency,
+            self.reporting_currency,
+            date
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(rate)
+    }
+
+    async fn find_scenario_rate_types(&self) -> Result<Vec<ScenarioRateType>, AppError> {
+        let rate_types = sqlx::query_as!(
+            ScenarioRateType,
+            r#"
+            SELECT
+                scenario as "scenario: Scenario",
+                rate_type as "rate_type: RateType",
+                updated_by,
+                updated_at
+            FROM scenario_rate_types
+            ORDER BY scenario ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rate_types)
+    }
+
+    async fn update_scenario_rate_type(
+        &self,
+        scenario: Scenario,
+        rate_type: RateType,
+        user_id: Uuid,
+    ) -> Result<ScenarioRateType, AppError> {
+        let rate_type = sqlx::query_as!(
+            ScenarioRateType,
+            r#"
+            INSERT INTO scenario_rate_types (scenario, rate_type, updated_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (scenario)
+            DO UPDATE SET
+                rate_type = EXCLUDED.rate_type,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING
+                scenario as "scenario: Scenario",
+                rate_type as "rate_type: RateType",
+                updated_by,
+                updated_at
+            "#,
+            scenario as Scenario,
+            rate_type as RateType,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(rate_type)
+    }
+}
@@ -0,0 +1,232 @@
+use sqlx::PgPool;
+
+use crate::{
+    domains::fiscal::{
+        ChangePeriodStatusParam, FiscalMonth, FiscalPeriod, FiscalPeriodEvent,
+        FiscalPeriodRepository, PeriodStatus,
+    },
+    error::AppError,
+};
+
+#[derive(Debug, Clone)]
+pub struct FiscalPeriodRepositoryImpl {
+    pool: PgPool,
+}
+
+impl FiscalPeriodRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl FiscalPeriodRepository for FiscalPeriodRepositoryImpl {
+    async fn ensure_months(&self, months: Vec<FiscalMonth>) -> Result<(), AppError> {
+        let fiscal_years: Vec<i32> = months.iter().map(|m| m.fiscal_year).collect();
+        let periods: Vec<i32> = months.iter().map(|m| m.period as i32).collect();
+        let start_dates: Vec<_> = months.iter().map(|m| m.start_date).collect();
+        let end_dates: Vec<_> = months.iter().map(|m| m.end_date).collect();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO fiscal_periods (fiscal_year, period, start_date, end_date)
+            SELECT * FROM UNNEST($1::int[], $2::int[], $3::date[], $4::date[])
+            ON CONFLICT DO NOTHING
+            "#,
+            &fiscal_years,
+            &periods,
+            &start_dates,
+            &end_dates
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to register fiscal periods: {:?}", e);
+            AppError::from(e)
+        })?;
+
+        Ok(())
+    }
+
+    async fn find_by_year(&self, fiscal_year: i32) -> Result<Vec<FiscalPeriod>, AppError> {
+        let periods = sqlx::query_as!(
+            FiscalPeriod,
+            r#"
+            SELECT
+                fiscal_year,
+                period,
+                start_date,
+                end_date,
+                status as "status: PeriodStatus",
+                closed_by,
+                closed_at,
+                created_at,
+                updated_at
+            FROM fiscal_periods
+            WHERE fiscal_year = $1
+            ORDER BY period ASC
+            "#,
+            fiscal_year
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(periods)
+    }
+
+    async fn find_by_period(
+        &self,
+        fiscal_year: i32,
+        period: i32,
+    ) -> Result<Option<FiscalPeriod>, AppError> {
+        let period = sqlx::query_as!(
+            FiscalPeriod,
+            r#"
+            SELECT
+                fiscal_year,
+                period,
+                start_date,
+                end_date,
+                status as "status: PeriodStatus",
+                closed_by,
+                closed_at,
+                created_at,
+                updated_at
+            FROM fiscal_periods
+            WHERE fiscal_year = $1 AND period = $2
+            "#,
+            fiscal_year,
+            period
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(period)
+    }
+
+    async fn find_events(
+        &self,
+        fiscal_year: i32,
+        period: i32,
+    ) -> Result<Vec<FiscalPeriodEvent>, AppError> {
+        let events = sqlx::query_as!(
+            FiscalPeriodEvent,
+            r#"
+            SELECT
+                id,
+                fiscal_year,
+                period,
+                status as "status: PeriodStatus",
+                reason,
+                acted_by,
+                acted_at
+            FROM fiscal_period_events
+            WHERE fiscal_year = $1 AND period = $2
+            ORDER BY acted_at DESC
+            "#,
+            fiscal_year,
+            period
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn change_status(
+        &self,
+        params: ChangePeriodStatusParam,
+    ) -> Result<FiscalPeriod, AppError> {
+        let month = params.month;
+        let period_no = month.period as i32;
+
+        let mut tx = self.pool.begin().await?;
+
+        // 書き込み中のPL明細の確定を待ってから状態を変えるよう、月次期間を行ロックする
+        sqlx::query!(
+            r#"
+            INSERT INTO fiscal_periods (fiscal_year, period, start_date, end_date)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT DO NOTHING
+            "#,
+            month.fiscal_year,
+            period_no,
+            month.start_date,
+            month.end_date
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let current = sqlx::query_scalar!(
+            r#"
+            SELECT status as "status: PeriodStatus"
+            FROM fiscal_periods
+            WHERE fiscal_year = $1 AND period = $2
+            FOR UPDATE
+            "#,
+            month.fiscal_year,
+            period_no
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if current == params.status {
+            return Err(AppError::BadRequest(format!(
+                "Fiscal period FY{} M{:02} is already {:?}",
+                month.fiscal_year, period_no, params.status
+            )));
+        }
+
+        // 締め済みなら締めた人・日時を、締め解除なら空にする
+        let period = sqlx::query_as!(
+            FiscalPeriod,
+            r#"
+            UPDATE fiscal_periods
+            SET
+                status = $1,
+                closed_by = CASE WHEN $1 = 'Closed'::period_status THEN $2::uuid ELSE NULL END,
+                closed_at = CASE WHEN $1 = 'Closed'::period_status THEN CURRENT_TIMESTAMP ELSE NULL END,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE fiscal_year = $3 AND period = $4
+            RETURNING
+                fiscal_year,
+                period,
+                start_date,
+                end_date,
+                status as "status: PeriodStatus",
+                closed_by,
+                closed_at,
+                created_at,
+                updated_at
+            "#,
+            params.status as PeriodStatus,
+            params.acted_by,
+            month.fiscal_year,
+            period_no
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to change fiscal period status: {:?}", e);
+            AppError::from(e)
+        })?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO fiscal_period_events (fiscal_year, period, status, reason, acted_by)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            month.fiscal_year,
+            period_no,
+            params.status as PeriodStatus,
+            params.reason,
+            params.acted_by
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(period)
+    }
+}
@@ -0,0 +1,230 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    domains::{
+        invitation::{CreateInvitationParam, Invitation, InvitationRepository},
+        user::{CreateUserParam, User},
+    },
+    error::AppError,
+};
+
+#[derive(Debug, Clone)]
+pub struct InvitationRepositoryImpl {
+    pool: PgPool,
+}
+
+impl InvitationRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl InvitationRepository for InvitationRepositoryImpl {
+    async fn create(&self, params: CreateInvitationParam) -> Result<Invitation, AppError> {
+        let invitation = sqlx::query_as!(
+            Invitation,
+            r#"
+            INSERT INTO user_invitations (
+                email,
+                role,
+                token_hash,
+                expires_at,
+                invited_by
+            )
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING
+                id,
+                email,
+                role,
+                expires_at,
+                invited_by,
+                created_at,
+                accepted_by,
+                accepted_at,
+                revoked_by,
+                revoked_at
+            "#,
+            params.email,
+            params.role.as_str(),
+            params.token_hash,
+            params.expires_at,
+            params.invited_by
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(invitation)
+    }
+
+    async fn find_all(&self) -> Result<Vec<Invitation>, AppError> {
+        let invitations = sqlx::query_as!(
+            Invitation,
+            r#"
+            SELECT
+                id,
+                email,
+                role,
+                expires_at,
+                invited_by,
+                created_at,
+                accepted_by,
+                accepted_at,
+                revoked_by,
+                revoked_at
+            FROM user_invitations
+            ORDER BY created_at DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(invitations)
+    }
+
+    async fn revoke(&self, id: Uuid, revoked_by: Uuid) -> Result<Invitation, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let current = sqlx::query!(
+            r#"
+            SELECT accepted_at, revoked_at FROM user_invitations WHERE id = $1 FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound(format!("Invitation {} not found", id)))?;
+
+        if current.accepted_at.is_some() {
+            return Err(AppError::Validation(format!(
+                "Invitation {} has already been accepted",
+                id
+            )));
+        }
+        if current.revoked_at.is_some() {
+            return Err(AppError::Validation(format!(
+                "Invitation {} has already been revoked",
+                id
+            )));
+        }
+
+        let invitation = sqlx::query_as!(
+            Invitation,
+            r#"
+            UPDATE user_invitations
+            SET
+                revoked_by = $2,
+                revoked_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            RETURNING
+                id,
+                email,
+                role,
+                expires_at,
+                invited_by,
+                created_at,
+                accepted_by,
+                accepted_at,
+                revoked_by,
+                revoked_at
+            "#,
+            id,
+            revoked_by
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(invitation)
+    }
+
+    async fn accept(&self, token_hash: &str, params: CreateUserParam) -> Result<User, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        // 同じ招待で同時に登録されないよう行ロックをとる
+        let invitation = sqlx::query!(
+            r#"
+            SELECT id, email, role, expires_at, accepted_at, revoked_at
+            FROM user_invitations
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+            token_hash
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .filter(|i| i.accepted_at.is_none() && i.revoked_at.is_none() && i.expires_at > Utc::now())
+        .ok_or(AppError::Validation(
+            "Invitation is invalid or has expired".to_string(),
+        ))?;
+
+        if !invitation.email.eq_ignore_ascii_case(params.email.trim()) {
+            return Err(AppError::Validation(
+                "Email does not match the invitation".to_string(),
+            ));
+        }
+
+        let password_hash = params.hash_password()?;
+
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            INSERT INTO users (
+                employee_id,
+                username,
+                name,
+                email,
+                password_hash,
+                role
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+            params.employee_id,
+            params.username,
+            params.name,
+            invitation.email,
+            password_hash,
+            invitation.role
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(duplicate_user)?;
+
+        sqlx::query!(
+            r#"
+            UPDATE user_invitations
+            SET
+                accepted_by = $2,
+                accepted_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            "#,
+            invitation.id,
+            user.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(user)
+    }
+}
+
+/// 登録済みのユーザーと重複する項目があれば、どの項目かを示すエラーにする
+fn duplicate_user(e: sqlx::Error) -> AppError {
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            let field = match db.constraint() {
+                Some("users_employee_id_key") => "employee ID",
+                Some("users_username_key") => "username",
+                _ => "email",
+            };
+            AppError::Validation(format!("User with the same {} already exists", field))
+        }
+        _ => AppError::from(e),
+    }
+}
@@ -2,7 +2,7 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
-    domains::job::{CreateJobParam, Job, JobRepository, UpdateJobParam},
+    domains::job::{CreateJobParam, Job, JobAssignee, JobRepository, UpdateJobParam},
     error::AppError,
 };
 
@@ -62,12 +62,20 @@ impl JobRepository for JobRepositoryImpl {
         Ok(job)
     }
 
-    async fn find_all(&self) -> Result<Vec<Job>, AppError> {
+    async fn find_all(&self, member_id: Option<Uuid>) -> Result<Vec<Job>, AppError> {
         let jobs = sqlx::query_as!(
             Job,
             r#"
-            SELECT * FROM jobs ORDER BY created_at DESC
+            SELECT * FROM jobs j
+            WHERE $1::uuid IS NULL
+               OR j.owner_id = $1
+               OR EXISTS (
+                   SELECT 1 FROM job_assignees a
+                   WHERE a.job_id = j.id AND a.user_id = $1
+               )
+            ORDER BY created_at DESC
             "#,
+            member_id
         )
         .fetch_all(&self.pool)
         .await?;
@@ -141,4 +149,59 @@ impl JobRepository for JobRepositoryImpl {
 
         Ok(())
     }
+    async fn find_assignees(&self, job_id: Uuid) -> Result<Vec<JobAssignee>, AppError> {
+        let assignees = sqlx::query_as!(
+            JobAssignee,
+            r#"
+            SELECT a.user_id, u.name, a.assigned_by, a.assigned_at
+            FROM job_assignees a
+            JOIN users u ON u.id = a.user_id
+            WHERE a.job_id = $1
+            ORDER BY a.assigned_at ASC, u.name ASC
+            "#,
+            job_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(assignees)
+    }
+
+    async fn replace_assignees(
+        &self,
+        job_id: Uuid,
+        user_ids: Vec<Uuid>,
+        assigned_by: Uuid,
+    ) -> Result<Vec<JobAssignee>, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM job_assignees
+            WHERE job_id = $1 AND NOT (user_id = ANY($2))
+            "#,
+            job_id,
+            &user_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO job_assignees (job_id, user_id, assigned_by)
+            SELECT $1, u.user_id, $3
+            FROM UNNEST($2::uuid[]) AS u(user_id)
+            ON CONFLICT (job_id, user_id) DO NOTHING
+            "#,
+            job_id,
+            &user_ids,
+            assigned_by
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.find_assignees(job_id).await
+    }
 }
@@ -0,0 +1,159 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::{
+    domains::login_attempt::{
+        CreateLoginEventParam, LockoutPolicy, LoginAttemptRepository, LoginEvent, LoginEventFilter,
+        LoginOutcome,
+    },
+    error::AppError,
+};
+
+#[derive(Debug, Clone)]
+pub struct LoginAttemptRepositoryImpl {
+    pool: PgPool,
+}
+
+impl LoginAttemptRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl LoginAttemptRepository for LoginAttemptRepositoryImpl {
+    async fn record_event(&self, params: CreateLoginEventParam) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO login_events (
+                email,
+                user_id,
+                outcome,
+                ip_address,
+                user_agent
+            )
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            params.email,
+            params.user_id,
+            params.outcome as LoginOutcome,
+            params.ip_address,
+            params.user_agent
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_events(&self, filter: LoginEventFilter) -> Result<Vec<LoginEvent>, AppError> {
+        let events = sqlx::query_as!(
+            LoginEvent,
+            r#"
+            SELECT
+                id,
+                email,
+                user_id,
+                outcome as "outcome: LoginOutcome",
+                ip_address,
+                user_agent,
+                created_at
+            FROM login_events
+            WHERE ($1::uuid IS NULL OR user_id = $1)
+              AND ($2::varchar IS NULL OR email = $2)
+              AND ($3::login_outcome IS NULL OR outcome = $3)
+            ORDER BY created_at DESC
+            LIMIT $4
+            "#,
+            filter.user_id,
+            filter.email,
+            filter.outcome as Option<LoginOutcome>,
+            filter.limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn locked_until(&self, email: &str) -> Result<Option<DateTime<Utc>>, AppError> {
+        let locked_until = sqlx::query_scalar!(
+            r#"
+            SELECT locked_until as "locked_until!"
+            FROM login_failures
+            WHERE email = $1 AND locked_until > CURRENT_TIMESTAMP
+            "#,
+            email
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(locked_until)
+    }
+
+    async fn record_failure(
+        &self,
+        email: &str,
+        policy: LockoutPolicy,
+    ) -> Result<Option<DateTime<Utc>>, AppError> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        // 前回の失敗から lockout 以上経っていれば数え直す
+        let failed_count = sqlx::query_scalar!(
+            r#"
+            INSERT INTO login_failures (email, failed_count, last_failed_at)
+            VALUES ($1, 1, $2)
+            ON CONFLICT (email) DO UPDATE SET
+                failed_count = CASE
+                    WHEN login_failures.last_failed_at < $3 THEN 1
+                    ELSE login_failures.failed_count + 1
+                END,
+                last_failed_at = $2
+            RETURNING failed_count
+            "#,
+            email,
+            now,
+            now - policy.lockout
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let locked_until = if failed_count >= policy.max_failures {
+            let until = now + policy.lockout;
+            sqlx::query!(
+                r#"
+                UPDATE login_failures
+                SET
+                    failed_count = 0,
+                    locked_until = $2
+                WHERE email = $1
+                "#,
+                email,
+                until
+            )
+            .execute(&mut *tx)
+            .await?;
+            Some(until)
+        } else {
+            None
+        };
+
+        tx.commit().await?;
+
+        Ok(locked_until)
+    }
+
+    async fn clear_failures(&self, email: &str) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM login_failures WHERE email = $1
+            "#,
+            email
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{domains::password_reset::PasswordResetRepository, error::AppError};
+
+#[derive(Debug, Clone)]
+pub struct PasswordResetRepositoryImpl {
+    pool: PgPool,
+}
+
+impl PasswordResetRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl PasswordResetRepository for PasswordResetRepositoryImpl {
+    async fn create(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM password_reset_tokens WHERE user_id = $1 AND used_at IS NULL
+            "#,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO password_reset_tokens (token_hash, user_id, expires_at)
+            VALUES ($1, $2, $3)
+            "#,
+            token_hash,
+            user_id,
+            expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn consume(&self, token_hash: &str, password_hash: &str) -> Result<Uuid, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        // 無効化されたユーザーはパスワードを再設定できない
+        let token = sqlx::query!(
+            r#"
+            SELECT t.user_id, t.expires_at, t.used_at, u.is_active
+            FROM password_reset_tokens t
+            JOIN users u ON u.id = t.user_id
+            WHERE t.token_hash = $1
+            FOR UPDATE OF t
+            "#,
+            token_hash
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .filter(|t| t.used_at.is_none() && t.expires_at > Utc::now() && t.is_active)
+        .ok_or(AppError::Validation(
+            "Reset token is invalid or has expired".to_string(),
+        ))?;
+
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET
+                password_hash = $2,
+                updated_by = $1,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            "#,
+            token.user_id,
+            password_hash
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE password_reset_tokens SET used_at = CURRENT_TIMESTAMP WHERE token_hash = $1
+            "#,
+            token_hash
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(token.user_id)
+    }
+}
@@ -1,24 +1,50 @@
 use chrono::NaiveDate;
 use rust_decimal::Decimal;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
 use crate::{
-    domains::pl_entry::{PlEntry, PlEntryRepository, Scenario, UpsertPlEntryParam},
+    domains::{
+        budget::{
+            BudgetLine, BudgetPolicy, build_consumption, exec_plan_adjustments, find_overruns,
+        },
+        fiscal::{PeriodRange, PeriodStatus},
+        pl_entry::{
+            PlEntry, PlEntryDetail, PlEntryRepository, SaveJobEntriesParam,
+            SaveProjectEntriesParam, Scenario, UpsertPlEntryParam,
+        },
+        pl_snapshot::CreatePlSnapshotParam,
+        report::{MonthlyAmount, PlScope},
+    },
     error::AppError,
+    repositories::pl_snapshot::{insert_snapshot, lock_project},
 };
 
 #[derive(Debug, Clone)]
 pub struct PlEntryRepositoryImpl {
     pool: PgPool,
+    reporting_currency: String,
 }
 
 impl PlEntryRepositoryImpl {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, reporting_currency: String) -> Self {
+        Self {
+            pool,
+            reporting_currency,
+        }
     }
 }
 
+/// 報告通貨に換算した月次合計（換算レートが未登録の通貨を含む）
+struct ConvertedAmount {
+    account_item_id: Uuid,
+    account_item_name: String,
+    display_order: i32,
+    month: NaiveDate,
+    amount: Decimal,
+    missing_currencies: Option<Vec<String>>,
+}
+
 #[async_trait::async_trait]
 impl PlEntryRepository for PlEntryRepositoryImpl {
     async fn find_by_project(
@@ -37,6 +63,7 @@ impl PlEntryRepository for PlEntryRepositoryImpl {
                 date,
                 account_item_id,
                 amount,
+                currency,
                 description,
                 created_by,
                 updated_by,
@@ -59,31 +86,318 @@ impl PlEntryRepository for PlEntryRepositoryImpl {
         Ok(entries)
     }
 
-    async fn bulk_upsert(
+    async fn find_details_by_project(
         &self,
         project_id: Uuid,
         scenario: Scenario,
-        entries: Vec<UpsertPlEntryParam>,
-        user_id: Uuid,
-    ) -> Result<(), AppError> {
+        range: Option<PeriodRange>,
+    ) -> Result<Vec<PlEntryDetail>, AppError> {
+        let entries = sqlx::query_as!(
+            PlEntryDetail,
+            r#"
+            SELECT
+                e.id,
+                e.project_id,
+                e.job_id,
+                e.scenario as "scenario: Scenario",
+                e.date,
+                e.account_item_id,
+                a.name as account_item_name,
+                e.amount,
+                e.currency,
+                ROUND(e.amount * exchange_rate(e.scenario, e.currency, $5, e.date), 4) as reporting_amount,
+                e.description,
+                e.created_by,
+                e.updated_by,
+                e.created_at,
+                e.updated_at
+            FROM pl_entries e
+            JOIN account_items a ON a.id = e.account_item_id
+            WHERE e.project_id = $1 AND e.scenario = $2
+              AND e.job_id IS NULL AND e.allocation_run_id IS NULL
+              AND ($3::date IS NULL OR e.date >= $3)
+              AND ($4::date IS NULL OR e.date <= $4)
+            ORDER BY a.display_order ASC, e.date ASC
+            "#,
+            project_id,
+            scenario as Scenario,
+            range.map(|r| r.start),
+            range.map(|r| r.end),
+            self.reporting_currency
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to fetch pl entry details: {:?}", e);
+            AppError::from(e)
+        })?;
+
+        Ok(entries)
+    }
+
+    async fn find_details_by_job(
+        &self,
+        job_id: Uuid,
+        scenario: Scenario,
+        range: Option<PeriodRange>,
+    ) -> Result<Vec<PlEntryDetail>, AppError> {
+        let entries = sqlx::query_as!(
+            PlEntryDetail,
+            r#"
+            SELECT
+                e.id,
+                e.project_id,
+                e.job_id,
+                e.scenario as "scenario: Scenario",
+                e.date,
+                e.account_item_id,
+                a.name as account_item_name,
+                e.amount,
+                e.currency,
+                ROUND(e.amount * exchange_rate(e.scenario, e.currency, $5, e.date), 4) as reporting_amount,
+                e.description,
+                e.created_by,
+                e.updated_by,
+                e.created_at,
+                e.updated_at
+            FROM pl_entries e
+            JOIN account_items a ON a.id = e.account_item_id
+            WHERE e.job_id = $1 AND e.scenario = $2
+              AND ($3::date IS NULL OR e.date >= $3)
+              AND ($4::date IS NULL OR e.date <= $4)
+            ORDER BY a.display_order ASC, e.date ASC
+            "#,
+            job_id,
+            scenario as Scenario,
+            range.map(|r| r.start),
+            range.map(|r| r.end),
+            self.reporting_currency
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to fetch job pl entry details: {:?}", e);
+            AppError::from(e)
+        })?;
+
+        Ok(entries)
+    }
+
+    async fn find_monthly_totals(
+        &self,
+        scope: PlScope,
+        scenario: Scenario,
+        range: Option<PeriodRange>,
+    ) -> Result<Vec<MonthlyAmount>, AppError> {
+        let mut conn = self.pool.acquire().await?;
+
+        monthly_totals(&mut conn, scope, scenario, range, &self.reporting_currency).await
+    }
+
+    async fn find_forecast_totals(
+        &self,
+        scope: PlScope,
+        range: Option<PeriodRange>,
+    ) -> Result<Vec<MonthlyAmount>, AppError> {
+        let totals = sqlx::query_as!(
+            ConvertedAmount,
+            r#"
+            SELECT
+                e.account_item_id,
+                a.name as account_item_name,
+                a.display_order,
+                date_trunc('month', e.date)::date as "month!",
+                COALESCE(ROUND(SUM(e.amount * x.rate), 4), 0) as "amount!",
+                array_agg(DISTINCT e.currency::text) FILTER (WHERE x.rate IS NULL) as missing_currencies
+            FROM pl_entries e
+            JOIN account_items a ON a.id = e.account_item_id
+            JOIN projects p ON p.id = e.project_id
+            LEFT JOIN themes t ON t.id = p.theme_id
+            LEFT JOIN jobs j ON j.id = e.job_id
+            CROSS JOIN LATERAL (
+                SELECT exchange_rate(e.scenario, e.currency, $10, e.date) as rate
+            ) x
+            CROSS JOIN LATERAL (
+                SELECT EXISTS (
+                    SELECT 1 FROM fiscal_periods fp
+                    WHERE fp.status = 'Closed'
+                      AND e.date BETWEEN fp.start_date AND fp.end_date
+                ) as closed
+            ) c
+            -- Job 単位の明細しか対象にならない集計では JobPlan を計画として扱う
+            WHERE (
+                (c.closed AND e.scenario = 'Actual')
+                OR (
+                    NOT c.closed
+                    AND e.job_id IS NULL
+                    AND NOT $9
+                    AND e.scenario = CASE
+                        WHEN EXISTS (
+                            SELECT 1 FROM pl_entries r
+                            WHERE r.project_id = e.project_id
+                              AND r.scenario = 'RevisedPlan'
+                              AND r.job_id IS NULL
+                              AND r.allocation_run_id IS NULL
+                        ) THEN 'RevisedPlan'::scenario_type
+                        ELSE 'MasterPlan'::scenario_type
+                    END
+                )
+                OR (NOT c.closed AND e.job_id IS NOT NULL AND $9 AND e.scenario = 'JobPlan')
+            )
+              AND ($1::uuid IS NULL OR e.project_id = $1)
+              AND ($2::uuid IS NULL OR e.job_id = $2)
+              AND ($3::uuid IS NULL OR p.theme_id = $3)
+              AND ($4::uuid IS NULL OR t.segment_id = $4)
+              AND ($5::uuid IS NULL OR j.service_id = $5)
+              AND ($6::date IS NULL OR e.date >= $6)
+              AND ($7::date IS NULL OR e.date <= $7)
+              AND ($8::uuid IS NULL OR (e.project_id = $8 AND e.job_id IS NOT NULL))
+            GROUP BY e.account_item_id, a.name, a.display_order, 4
+            ORDER BY a.display_order ASC, 4 ASC
+            "#,
+            scope.project_id(),
+            scope.job_id(),
+            scope.theme_id(),
+            scope.segment_id(),
+            scope.service_id(),
+            range.map(|r| r.start),
+            range.map(|r| r.end),
+            scope.jobs_of_project_id(),
+            scope.is_job_based(),
+            self.reporting_currency
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to fetch forecast totals: {:?}", e);
+            AppError::from(e)
+        })?;
+
+        into_monthly_amounts(totals)
+    }
+
+    async fn save_project_entries(&self, params: SaveProjectEntriesParam) -> Result<i32, AppError> {
+        let dates: Vec<NaiveDate> = params.entries.iter().map(|e| e.date).collect();
+
+        let mut tx = self.pool.begin().await?;
+
+        lock_project(&mut tx, params.project_id).await?;
+        ensure_writable(&mut tx, params.project_id, params.scenario, &dates).await?;
+        if !params.entries.is_empty() {
+            upsert_project_entries(
+                &mut tx,
+                params.project_id,
+                params.scenario,
+                &params.entries,
+                &self.reporting_currency,
+                params.user_id,
+            )
+            .await?;
+        }
+
+        let version = insert_snapshot(
+            &mut tx,
+            &CreatePlSnapshotParam {
+                project_id: params.project_id,
+                scenario: params.scenario,
+                comment: params.comment,
+                created_by: params.user_id,
+            },
+        )
+        .await?;
+
+        if params.scenario.affects_exec_plan_adjust() {
+            sync_exec_plan_adjust(
+                &mut tx,
+                params.project_id,
+                &self.reporting_currency,
+                params.user_id,
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(version)
+    }
+
+    async fn sync_exec_plan_adjust(&self, project_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sync_exec_plan_adjust(&mut tx, project_id, &self.reporting_currency, user_id).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn bulk_upsert_job(
+        &self,
+        params: SaveJobEntriesParam,
+    ) -> Result<Vec<BudgetLine>, AppError> {
+        let SaveJobEntriesParam {
+            project_id,
+            job_id,
+            scenario,
+            entries,
+            budget_check,
+            comment,
+            user_id,
+        } = params;
+
         if entries.is_empty() {
-            return Ok(());
+            return Ok(Vec::new());
         }
 
         let account_item_ids: Vec<Uuid> = entries.iter().map(|e| e.account_item_id).collect();
         let dates: Vec<NaiveDate> = entries.iter().map(|e| e.date).collect();
         let amounts: Vec<Decimal> = entries.iter().map(|e| e.amount).collect();
+        let currencies: Vec<Option<String>> = entries.iter().map(|e| e.currency.clone()).collect();
         let descriptions: Vec<Option<String>> =
             entries.iter().map(|e| e.description.clone()).collect();
 
+        let mut tx = self.pool.begin().await?;
+
+        // 予算残の比較から書き込みまでの間に、同じProjectの計画やJobPlanが変わらないようにする
+        lock_project(&mut tx, project_id).await?;
+        ensure_writable(&mut tx, project_id, scenario, &dates).await?;
+
+        let mut overruns = Vec::new();
+        if let Some(check) = budget_check {
+            let budget = project_budget(&mut tx, project_id, &self.reporting_currency).await?;
+            let consumed = monthly_totals(
+                &mut tx,
+                PlScope::JobsOfProject(project_id),
+                Scenario::JobPlan,
+                None,
+                &self.reporting_currency,
+            )
+            .await?;
+            let current = monthly_totals(
+                &mut tx,
+                PlScope::Job(job_id),
+                Scenario::JobPlan,
+                None,
+                &self.reporting_currency,
+            )
+            .await?;
+
+            overruns = find_overruns(&budget, &consumed, &current, &check.converted, &check.items);
+            if !overruns.is_empty() && check.policy == BudgetPolicy::Reject {
+                return Err(AppError::BudgetExceeded(overruns));
+            }
+        }
+
         sqlx::query!(
             r#"
             INSERT INTO pl_entries (
                 project_id,
+                job_id,
                 scenario,
                 account_item_id,
                 date,
                 amount,
+                currency,
                 description,
                 created_by,
                 updated_by,
@@ -93,42 +407,422 @@ impl PlEntryRepository for PlEntryRepositoryImpl {
             SELECT
                 $1,
                 $2,
+                $3,
                 u.account_item_id,
                 u.date,
                 u.amount,
+                COALESCE(u.currency, $10),
                 u.description,
-                $6, -- created_by
-                $6, -- updated_by
+                $7, -- created_by
+                $7, -- updated_by
                 NOW(),
                 NOW()
             FROM UNNEST(
-                $3::uuid[],
-                $4::date[],
-                $5::numeric[],
-                $7::text[]
-            ) AS u(account_item_id, date, amount, description)
-            ON CONFLICT (project_id, scenario, account_item_id, date) WHERE job_id IS NULL
+                $4::uuid[],
+                $5::date[],
+                $6::numeric[],
+                $9::text[],
+                $8::text[]
+            ) AS u(account_item_id, date, amount, currency, description)
+            ON CONFLICT (job_id, scenario, account_item_id, date) WHERE job_id IS NOT NULL
             DO UPDATE SET
                 amount = EXCLUDED.amount,
+                currency = EXCLUDED.currency,
                 description = EXCLUDED.description,
                 updated_by = EXCLUDED.updated_by,
                 updated_at = CURRENT_TIMESTAMP
             "#,
             project_id,
+            job_id,
             scenario as Scenario,
             &account_item_ids,
             &dates,
             &amounts as &[Decimal],
             user_id,
-            &descriptions as &[Option<String>]
+            &descriptions as &[Option<String>],
+            &currencies as &[Option<String>],
+            self.reporting_currency
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| {
-            tracing::error!("Failed to bulk upsert to entries: {:?}", e);
+            tracing::error!("Failed to bulk upsert job entries: {:?}", e);
             AppError::from(e)
         })?;
 
-        Ok(())
+        insert_snapshot(
+            &mut tx,
+            &CreatePlSnapshotParam {
+                project_id,
+                scenario,
+                comment,
+                created_by: user_id,
+            },
+        )
+        .await?;
+
+        if scenario.affects_exec_plan_adjust() {
+            sync_exec_plan_adjust(&mut tx, project_id, &self.reporting_currency, user_id).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(overruns)
+    }
+}
+
+/// 範囲内のPL明細を報告通貨に換算し、勘定科目・月ごとに合計する
+///
+/// 書き込みと同じトランザクション内で集計できるよう、接続を受け取る
+pub(crate) async fn monthly_totals(
+    conn: &mut PgConnection,
+    scope: PlScope,
+    scenario: Scenario,
+    range: Option<PeriodRange>,
+    reporting_currency: &str,
+) -> Result<Vec<MonthlyAmount>, AppError> {
+    let totals = sqlx::query_as!(
+        ConvertedAmount,
+        r#"
+        SELECT
+            e.account_item_id,
+            a.name as account_item_name,
+            a.display_order,
+            date_trunc('month', e.date)::date as "month!",
+            COALESCE(ROUND(SUM(e.amount * x.rate), 4), 0) as "amount!",
+            array_agg(DISTINCT e.currency::text) FILTER (WHERE x.rate IS NULL) as missing_currencies
+        FROM pl_entries e
+        JOIN account_items a ON a.id = e.account_item_id
+        JOIN projects p ON p.id = e.project_id
+        LEFT JOIN themes t ON t.id = p.theme_id
+        LEFT JOIN jobs j ON j.id = e.job_id
+        CROSS JOIN LATERAL (
+            SELECT exchange_rate(e.scenario, e.currency, $10, e.date) as rate
+        ) x
+        WHERE e.scenario = $1
+          AND ($2::uuid IS NULL OR e.project_id = $2)
+          AND ($3::uuid IS NULL OR e.job_id = $3)
+          AND ($4::uuid IS NULL OR p.theme_id = $4)
+          AND ($5::uuid IS NULL OR t.segment_id = $5)
+          AND ($6::uuid IS NULL OR j.service_id = $6)
+          AND ($7::date IS NULL OR e.date >= $7)
+          AND ($8::date IS NULL OR e.date <= $8)
+          AND ($9::uuid IS NULL OR (e.project_id = $9 AND e.job_id IS NOT NULL))
+        GROUP BY e.account_item_id, a.name, a.display_order, 4
+        ORDER BY a.display_order ASC, 4 ASC
+        "#,
+        scenario as Scenario,
+        scope.project_id(),
+        scope.job_id(),
+        scope.theme_id(),
+        scope.segment_id(),
+        scope.service_id(),
+        range.map(|r| r.start),
+        range.map(|r| r.end),
+        scope.jobs_of_project_id(),
+        reporting_currency
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to fetch monthly totals: {:?}", e);
+        AppError::from(e)
+    })?;
+
+    into_monthly_amounts(totals)
+}
+
+/// 換算レートが未登録の通貨があればエラーとする
+fn into_monthly_amounts(rows: Vec<ConvertedAmount>) -> Result<Vec<MonthlyAmount>, AppError> {
+    if let Some((row, currencies)) = rows.iter().find_map(|row| {
+        row.missing_currencies
+            .as_ref()
+            .filter(|currencies| !currencies.is_empty())
+            .map(|currencies| (row, currencies))
+    }) {
+        return Err(AppError::MissingExchangeRate(format!(
+            "No exchange rate for {} in {}",
+            currencies.join(", "),
+            row.month.format("%Y-%m")
+        )));
     }
+
+    Ok(rows
+        .into_iter()
+        .map(|row| MonthlyAmount {
+            account_item_id: row.account_item_id,
+            account_item_name: row.account_item_name,
+            display_order: row.display_order,
+            month: row.month,
+            amount: row.amount,
+        })
+        .collect())
+}
+
+/// 書き込み対象の日付がロック済みシナリオや締め済みの月に含まれていないか検証する
+///
+/// PL明細を更新する経路では必ず同一トランザクション内で呼び出すこと
+pub(crate) async fn ensure_writable(
+    conn: &mut PgConnection,
+    project_id: Uuid,
+    scenario: Scenario,
+    dates: &[NaiveDate],
+) -> Result<(), AppError> {
+    // ロックの作成・解除がこの書き込みと並行して確定しないよう、プロジェクトと該当するロックを共有ロックする
+    sqlx::query!(
+        r#"
+        SELECT id FROM projects WHERE id = $1 FOR SHARE
+        "#,
+        project_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let locks = sqlx::query_scalar!(
+        r#"
+        SELECT l.id
+        FROM pl_scenario_locks l
+        WHERE l.project_id = $1
+          AND l.scenario = $2
+          AND l.unlocked_at IS NULL
+          AND EXISTS (
+              SELECT 1 FROM UNNEST($3::date[]) AS d(date)
+              WHERE d.date BETWEEN l.period_start AND l.period_end
+          )
+        FOR SHARE OF l
+        "#,
+        project_id,
+        scenario as Scenario,
+        dates
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if !locks.is_empty() {
+        return Err(AppError::ScenarioLocked(format!(
+            "{:?} of project {} is locked",
+            scenario, project_id
+        )));
+    }
+
+    if scenario != Scenario::Actual {
+        return Ok(());
+    }
+
+    // 締め済みの月の実績は変更できない
+    // 締め・締め解除がこの書き込みと並行して確定しないよう、該当する月次期間を共有ロックする
+    let periods = sqlx::query!(
+        r#"
+        SELECT fp.start_date, fp.status as "status: PeriodStatus"
+        FROM fiscal_periods fp
+        WHERE EXISTS (
+            SELECT 1 FROM UNNEST($1::date[]) AS d(date)
+            WHERE d.date BETWEEN fp.start_date AND fp.end_date
+        )
+        ORDER BY fp.start_date ASC
+        FOR SHARE
+        "#,
+        dates
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if let Some(period) = periods.iter().find(|p| p.status == PeriodStatus::Closed) {
+        return Err(AppError::PeriodClosed(format!(
+            "Actual entries for the period starting {} are closed",
+            period.start_date
+        )));
+    }
+
+    Ok(())
+}
+
+/// Project単位の明細を指定された内容に置き換える（含まれない明細は削除する）
+async fn replace_project_entries(
+    conn: &mut PgConnection,
+    project_id: Uuid,
+    scenario: Scenario,
+    entries: &[UpsertPlEntryParam],
+    reporting_currency: &str,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    // 削除される明細の期間も書き込み可能か検証する
+    let mut dates = sqlx::query_scalar!(
+        r#"
+        SELECT date FROM pl_entries
+        WHERE project_id = $1 AND scenario = $2
+          AND job_id IS NULL AND allocation_run_id IS NULL
+        "#,
+        project_id,
+        scenario as Scenario
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    dates.extend(entries.iter().map(|e| e.date));
+
+    ensure_writable(conn, project_id, scenario, &dates).await?;
+
+    let account_item_ids: Vec<Uuid> = entries.iter().map(|e| e.account_item_id).collect();
+    let entry_dates: Vec<NaiveDate> = entries.iter().map(|e| e.date).collect();
+
+    sqlx::query!(
+        r#"
+        DELETE FROM pl_entries e
+        WHERE e.project_id = $1 AND e.scenario = $2
+          AND e.job_id IS NULL AND e.allocation_run_id IS NULL
+          AND NOT EXISTS (
+              SELECT 1 FROM UNNEST($3::uuid[], $4::date[]) AS u(account_item_id, date)
+              WHERE u.account_item_id = e.account_item_id AND u.date = e.date
+          )
+        "#,
+        project_id,
+        scenario as Scenario,
+        &account_item_ids,
+        &entry_dates
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    upsert_project_entries(
+        conn,
+        project_id,
+        scenario,
+        entries,
+        reporting_currency,
+        user_id,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Projectの予算とする計画（InitialPlan があればそれを、なければ MasterPlan）の月次合計
+async fn project_budget(
+    conn: &mut PgConnection,
+    project_id: Uuid,
+    reporting_currency: &str,
+) -> Result<Vec<MonthlyAmount>, AppError> {
+    let scope = PlScope::Project(project_id);
+    let plan = monthly_totals(conn, scope, Scenario::InitialPlan, None, reporting_currency).await?;
+    if !plan.is_empty() {
+        return Ok(plan);
+    }
+
+    monthly_totals(conn, scope, Scenario::MasterPlan, None, reporting_currency).await
+}
+
+/// ExecPlanAdjust をProjectの計画と配下のJobのJobPlanの差額で置き換える
+///
+/// 計画は InitialPlan があればそれを、なければ MasterPlan を使う。
+/// 差額が変わらない明細は更新しないため、何度実行しても同じ結果になる
+pub(crate) async fn sync_exec_plan_adjust(
+    conn: &mut PgConnection,
+    project_id: Uuid,
+    reporting_currency: &str,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    let plan = project_budget(conn, project_id, reporting_currency).await?;
+    let job_plans = monthly_totals(
+        conn,
+        PlScope::JobsOfProject(project_id),
+        Scenario::JobPlan,
+        None,
+        reporting_currency,
+    )
+    .await?;
+
+    let entries = exec_plan_adjustments(&build_consumption(&plan, &job_plans));
+
+    replace_project_entries(
+        conn,
+        project_id,
+        Scenario::ExecPlanAdjust,
+        &entries,
+        reporting_currency,
+        user_id,
+    )
+    .await
+}
+
+/// Project単位の明細を登録・更新する。金額・通貨・摘要が変わらない明細は更新しない
+///
+/// 通貨の指定がない明細は報告通貨で登録する
+async fn upsert_project_entries(
+    conn: &mut PgConnection,
+    project_id: Uuid,
+    scenario: Scenario,
+    entries: &[UpsertPlEntryParam],
+    reporting_currency: &str,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    let account_item_ids: Vec<Uuid> = entries.iter().map(|e| e.account_item_id).collect();
+    let dates: Vec<NaiveDate> = entries.iter().map(|e| e.date).collect();
+    let amounts: Vec<Decimal> = entries.iter().map(|e| e.amount).collect();
+    let currencies: Vec<Option<String>> = entries.iter().map(|e| e.currency.clone()).collect();
+    let descriptions: Vec<Option<String>> = entries.iter().map(|e| e.description.clone()).collect();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO pl_entries (
+            project_id,
+            scenario,
+            account_item_id,
+            date,
+            amount,
+            currency,
+            description,
+            created_by,
+            updated_by,
+            created_at,
+            updated_at
+        )
+        SELECT
+            $1,
+            $2,
+            u.account_item_id,
+            u.date,
+            u.amount,
+            COALESCE(u.currency, $9),
+            u.description,
+            $6, -- created_by
+            $6, -- updated_by
+            NOW(),
+            NOW()
+        FROM UNNEST(
+            $3::uuid[],
+            $4::date[],
+            $5::numeric[],
+            $8::text[],
+            $7::text[]
+        ) AS u(account_item_id, date, amount, currency, description)
+        ON CONFLICT (project_id, scenario, account_item_id, date)
+            WHERE job_id IS NULL AND allocation_run_id IS NULL
+        DO UPDATE SET
+            amount = EXCLUDED.amount,
+            currency = EXCLUDED.currency,
+            description = EXCLUDED.description,
+            updated_by = EXCLUDED.updated_by,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE pl_entries.amount IS DISTINCT FROM EXCLUDED.amount
+           OR pl_entries.currency IS DISTINCT FROM EXCLUDED.currency
+           OR pl_entries.description IS DISTINCT FROM EXCLUDED.description
+        "#,
+        project_id,
+        scenario as Scenario,
+        &account_item_ids,
+        &dates,
+        &amounts as &[Decimal],
+        user_id,
+        &descriptions as &[Option<String>],
+        &currencies as &[Option<String>],
+        reporting_currency
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to bulk upsert to entries: {:?}", e);
+        AppError::from(e)
+    })?;
+
+    Ok(())
 }
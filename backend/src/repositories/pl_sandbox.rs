@@ -0,0 +1,248 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    domains::{
+        fiscal::PeriodRange,
+        pl_entry::Scenario,
+        pl_sandbox::{CreatePlSandboxParam, PlSandbox, PlSandboxRepository},
+        report::MonthlyAmount,
+    },
+    error::AppError,
+};
+
+#[derive(Debug, Clone)]
+pub struct PlSandboxRepositoryImpl {
+    pool: PgPool,
+}
+
+impl PlSandboxRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl PlSandboxRepository for PlSandboxRepositoryImpl {
+    async fn create(&self, params: CreatePlSandboxParam) -> Result<PlSandbox, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let sandbox = sqlx::query_as!(
+            PlSandbox,
+            r#"
+            INSERT INTO pl_sandboxes (
+                name,
+                description,
+                project_id,
+                theme_id,
+                source_scenario,
+                owner_id
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING
+                id,
+                name,
+                description,
+                project_id,
+                theme_id,
+                source_scenario as "source_scenario: Scenario",
+                owner_id,
+                created_at,
+                updated_at
+            "#,
+            params.name,
+            params.description,
+            params.project_id,
+            params.theme_id,
+            params.source_scenario as Scenario,
+            params.owner_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        insert_entries(&mut tx, sandbox.id, &params.entries).await?;
+
+        tx.commit().await?;
+
+        Ok(sandbox)
+    }
+
+    async fn find_by_owner(&self, owner_id: Uuid) -> Result<Vec<PlSandbox>, AppError> {
+        let sandboxes = sqlx::query_as!(
+            PlSandbox,
+            r#"
+            SELECT
+                id,
+                name,
+                description,
+                project_id,
+                theme_id,
+                source_scenario as "source_scenario: Scenario",
+                owner_id,
+                created_at,
+                updated_at
+            FROM pl_sandboxes
+            WHERE owner_id = $1
+            ORDER BY updated_at DESC
+            "#,
+            owner_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sandboxes)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<PlSandbox>, AppError> {
+        let sandbox = sqlx::query_as!(
+            PlSandbox,
+            r#"
+            SELECT
+                id,
+                name,
+                description,
+                project_id,
+                theme_id,
+                source_scenario as "source_scenario: Scenario",
+                owner_id,
+                created_at,
+                updated_at
+            FROM pl_sandboxes
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(sandbox)
+    }
+
+    async fn find_totals(
+        &self,
+        id: Uuid,
+        range: Option<PeriodRange>,
+    ) -> Result<Vec<MonthlyAmount>, AppError> {
+        let totals = sqlx::query_as!(
+            MonthlyAmount,
+            r#"
+            SELECT
+                e.account_item_id,
+                a.name as account_item_name,
+                a.display_order,
+                e.month,
+                e.amount
+            FROM pl_sandbox_entries e
+            JOIN account_items a ON a.id = e.account_item_id
+            WHERE e.sandbox_id = $1
+              AND ($2::date IS NULL OR e.month >= $2)
+              AND ($3::date IS NULL OR e.month <= $3)
+            ORDER BY a.display_order ASC, e.month ASC
+            "#,
+            id,
+            range.map(|r| r.start),
+            range.map(|r| r.end)
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(totals)
+    }
+
+    async fn replace_entries(
+        &self,
+        id: Uuid,
+        entries: Vec<MonthlyAmount>,
+    ) -> Result<PlSandbox, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let sandbox = sqlx::query_as!(
+            PlSandbox,
+            r#"
+            UPDATE pl_sandboxes
+            SET updated_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            RETURNING
+                id,
+                name,
+                description,
+                project_id,
+                theme_id,
+                source_scenario as "source_scenario: Scenario",
+                owner_id,
+                created_at,
+                updated_at
+            "#,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound(format!("Sandbox {} not found", id)))?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM pl_sandbox_entries
+            WHERE sandbox_id = $1
+            "#,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        insert_entries(&mut tx, id, &entries).await?;
+
+        tx.commit().await?;
+
+        Ok(sandbox)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM pl_sandboxes
+            WHERE id = $1
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Sandbox {} not found", id)));
+        }
+
+        Ok(())
+    }
+}
+
+async fn insert_entries(
+    conn: &mut PgConnection,
+    sandbox_id: Uuid,
+    entries: &[MonthlyAmount],
+) -> Result<(), AppError> {
+    let account_item_ids: Vec<Uuid> = entries.iter().map(|e| e.account_item_id).collect();
+    let months: Vec<NaiveDate> = entries.iter().map(|e| e.month).collect();
+    let amounts: Vec<Decimal> = entries.iter().map(|e| e.amount).collect();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO pl_sandbox_entries (sandbox_id, account_item_id, month, amount)
+        SELECT $1, u.account_item_id, u.month, u.amount
+        FROM UNNEST(
+            $2::uuid[],
+            $3::date[],
+            $4::numeric[]
+        ) AS u(account_item_id, month, amount)
+        "#,
+        sandbox_id,
+        &account_item_ids,
+        &months,
+        &amounts as &[Decimal]
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
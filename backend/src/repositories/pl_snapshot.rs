@@ -0,0 +1,196 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    domains::{
+        pl_entry::Scenario,
+        pl_snapshot::{CreatePlSnapshotParam, PlSnapshot, PlSnapshotEntry, PlSnapshotRepository},
+    },
+    error::AppError,
+};
+
+#[derive(Debug, Clone)]
+pub struct PlSnapshotRepositoryImpl {
+    pool: PgPool,
+    reporting_currency: String,
+}
+
+impl PlSnapshotRepositoryImpl {
+    pub fn new(pool: PgPool, reporting_currency: String) -> Self {
+        Self {
+            pool,
+            reporting_currency,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PlSnapshotRepository for PlSnapshotRepositoryImpl {
+    async fn find_by_project(
+        &self,
+        project_id: Uuid,
+        scenario: Scenario,
+    ) -> Result<Vec<PlSnapshot>, AppError> {
+        let snapshots = sqlx::query_as!(
+            PlSnapshot,
+            r#"
+            SELECT
+                s.id,
+                s.project_id,
+                s.scenario as "scenario: Scenario",
+                s.version,
+                s.comment,
+                s.created_by,
+                u.name as created_by_name,
+                s.created_at
+            FROM pl_snapshots s
+            JOIN users u ON u.id = s.created_by
+            WHERE s.project_id = $1 AND s.scenario = $2
+            ORDER BY s.version DESC
+            "#,
+            project_id,
+            scenario as Scenario
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(snapshots)
+    }
+
+    async fn find_by_version(
+        &self,
+        project_id: Uuid,
+        scenario: Scenario,
+        version: i32,
+    ) -> Result<Option<PlSnapshot>, AppError> {
+        let snapshot = sqlx::query_as!(
+            PlSnapshot,
+            r#"
+            SELECT
+                s.id,
+                s.project_id,
+                s.scenario as "scenario: Scenario",
+                s.version,
+                s.comment,
+                s.created_by,
+                u.name as created_by_name,
+                s.created_at
+            FROM pl_snapshots s
+            JOIN users u ON u.id = s.created_by
+            WHERE s.project_id = $1 AND s.scenario = $2 AND s.version = $3
+            "#,
+            project_id,
+            scenario as Scenario,
+            version
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(snapshot)
+    }
+
+    async fn find_entries(&self, snapshot_id: Uuid) -> Result<Vec<PlSnapshotEntry>, AppError> {
+        let entries = sqlx::query_as!(
+            PlSnapshotEntry,
+            r#"
+            SELECT
+                e.job_id,
+                e.account_item_id,
+                a.name as account_item_name,
+                a.display_order,
+                e.date,
+                e.amount,
+                e.currency,
+                ROUND(e.amount * exchange_rate(s.scenario, e.currency, $2, e.date), 4) as reporting_amount,
+                e.description
+            FROM pl_snapshot_entries e
+            JOIN pl_snapshots s ON s.id = e.snapshot_id
+            JOIN account_items a ON a.id = e.account_item_id
+            WHERE e.snapshot_id = $1
+            ORDER BY a.display_order ASC, e.date ASC, e.job_id ASC
+            "#,
+            snapshot_id,
+            self.reporting_currency
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+}
+
+/// 同じProjectへの同時保存で版番号が重複しないよう、Projectを行ロックする
+///
+/// 明細の書き込みより先に呼び出すこと（書き込み時の共有ロックからの昇格はデッドロックになる）
+pub(crate) async fn lock_project(
+    conn: &mut PgConnection,
+    project_id: Uuid,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        SELECT id FROM projects
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        project_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(AppError::NotFound(format!(
+        "Project {} not found",
+        project_id
+    )))?;
+
+    Ok(())
+}
+
+/// Projectの現在の明細を次の版として記録し、版番号を返す
+///
+/// Job単位のシナリオは配下の全Jobの明細を、それ以外はProject単位の明細を記録する。
+/// 先に lock_project で同じトランザクション内からProjectをロックしておくこと
+pub(crate) async fn insert_snapshot(
+    conn: &mut PgConnection,
+    params: &CreatePlSnapshotParam,
+) -> Result<i32, AppError> {
+    let snapshot = sqlx::query!(
+        r#"
+        INSERT INTO pl_snapshots (project_id, scenario, version, comment, created_by)
+        SELECT $1, $2, COALESCE(MAX(version), 0) + 1, $3, $4
+        FROM pl_snapshots
+        WHERE project_id = $1 AND scenario = $2
+        RETURNING id, version
+        "#,
+        params.project_id,
+        params.scenario as Scenario,
+        params.comment,
+        params.created_by
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO pl_snapshot_entries (
+            snapshot_id,
+            job_id,
+            account_item_id,
+            date,
+            amount,
+            currency,
+            description
+        )
+        SELECT $1, job_id, account_item_id, date, amount, currency, description
+        FROM pl_entries
+        WHERE project_id = $2 AND scenario = $3
+          AND (job_id IS NOT NULL) = $4 AND allocation_run_id IS NULL
+        "#,
+        snapshot.id,
+        params.project_id,
+        params.scenario as Scenario,
+        params.scenario.is_job_level()
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(snapshot.version)
+}
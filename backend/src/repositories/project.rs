@@ -82,7 +82,7 @@ impl ProjectRepository for ProjectRepositoryImpl {
         Ok(project)
     }
 
-    async fn find_all(&self) -> Result<Vec<Project>, AppError> {
+    async fn find_all(&self, owner_id: Option<Uuid>) -> Result<Vec<Project>, AppError> {
         let projects = sqlx::query_as!(
             Project,
             r#"
@@ -104,8 +104,10 @@ impl ProjectRepository for ProjectRepositoryImpl {
                 created_at,
                 updated_at
             FROM projects
+            WHERE ($1::uuid IS NULL OR owner_id = $1)
             ORDER BY created_at DESC
             "#,
+            owner_id
         )
         .fetch_all(&self.pool)
         .await
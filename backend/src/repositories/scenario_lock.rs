@@ -0,0 +1,191 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    domains::{
+        pl_entry::Scenario,
+        scenario_lock::{
+            CreateScenarioLockParam, ScenarioLock, ScenarioLockRepository, UnlockScenarioParam,
+        },
+    },
+    error::AppError,
+};
+
+#[derive(Debug, Clone)]
+pub struct ScenarioLockRepositoryImpl {
+    pool: PgPool,
+}
+
+impl ScenarioLockRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl ScenarioLockRepository for ScenarioLockRepositoryImpl {
+    async fn create(&self, params: CreateScenarioLockParam) -> Result<ScenarioLock, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        // 書き込み中の明細があればそのコミットを待ってからロックする（ensure_writable と対になる）
+        sqlx::query!(
+            r#"
+            SELECT id FROM projects WHERE id = $1 FOR NO KEY UPDATE
+            "#,
+            params.project_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Project {} not found",
+            params.project_id
+        )))?;
+
+        // 期間が重なる有効なロックがあれば二重ロックしない
+        let overlapping = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM pl_scenario_locks
+                WHERE project_id = $1
+                  AND scenario = $2
+                  AND unlocked_at IS NULL
+                  AND period_start <= $4
+                  AND period_end >= $3
+            ) as "exists!"
+            "#,
+            params.project_id,
+            params.scenario as Scenario,
+            params.period_start,
+            params.period_end
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if overlapping {
+            return Err(AppError::ScenarioLocked(format!(
+                "{:?} of project {} is already locked in the given period",
+                params.scenario, params.project_id
+            )));
+        }
+
+        let lock = sqlx::query_as!(
+            ScenarioLock,
+            r#"
+            INSERT INTO pl_scenario_locks (
+                project_id,
+                scenario,
+                period_start,
+                period_end,
+                locked_by
+            )
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING
+                id,
+                project_id,
+                scenario as "scenario: Scenario",
+                period_start,
+                period_end,
+                locked_by,
+                locked_at,
+                unlocked_by,
+                unlocked_at,
+                unlock_reason
+            "#,
+            params.project_id,
+            params.scenario as Scenario,
+            params.period_start,
+            params.period_end,
+            params.locked_by
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db)
+                if db.constraint() == Some("pl_scenario_locks_no_overlap") =>
+            {
+                AppError::ScenarioLocked(format!(
+                    "{:?} of project {} is already locked in the given period",
+                    params.scenario, params.project_id
+                ))
+            }
+            e => {
+                tracing::error!("Failed to create scenario lock: {:?}", e);
+                AppError::from(e)
+            }
+        })?;
+
+        tx.commit().await?;
+
+        Ok(lock)
+    }
+
+    async fn find_by_project(&self, project_id: Uuid) -> Result<Vec<ScenarioLock>, AppError> {
+        let locks = sqlx::query_as!(
+            ScenarioLock,
+            r#"
+            SELECT
+                id,
+                project_id,
+                scenario as "scenario: Scenario",
+                period_start,
+                period_end,
+                locked_by,
+                locked_at,
+                unlocked_by,
+                unlocked_at,
+                unlock_reason
+            FROM pl_scenario_locks
+            WHERE project_id = $1
+            ORDER BY locked_at DESC
+            "#,
+            project_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(locks)
+    }
+
+    async fn unlock(
+        &self,
+        project_id: Uuid,
+        id: Uuid,
+        params: UnlockScenarioParam,
+    ) -> Result<ScenarioLock, AppError> {
+        let lock = sqlx::query_as!(
+            ScenarioLock,
+            r#"
+            UPDATE pl_scenario_locks
+            SET
+                unlocked_by = $1,
+                unlocked_at = CURRENT_TIMESTAMP,
+                unlock_reason = $2
+            WHERE id = $3 AND project_id = $4 AND unlocked_at IS NULL
+            RETURNING
+                id,
+                project_id,
+                scenario as "scenario: Scenario",
+                period_start,
+                period_end,
+                locked_by,
+                locked_at,
+                unlocked_by,
+                unlocked_at,
+                unlock_reason
+            "#,
+            params.unlocked_by,
+            params.reason,
+            id,
+            project_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to unlock scenario: {:?}", e);
+            AppError::from(e)
+        })?
+        .ok_or(AppError::NotFound(format!("Active lock {} not found", id)))?;
+
+        Ok(lock)
+    }
+}
@@ -1,4 +1,5 @@
 use sqlx::{PgPool, types::Json};
+use uuid::Uuid;
 
 use crate::{
     domains::segment::{CreateSegmentParam, Segment, SegmentRepository, SegmentUiConfig},
@@ -83,4 +84,29 @@ impl SegmentRepository for SegmentRepositoryImpl {
 
         Ok(segments)
     }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Segment>, AppError> {
+        let segment = sqlx::query_as!(
+            Segment,
+            r#"
+            SELECT
+                id,
+                slug,
+                name,
+                description,
+                ui_config as "ui_config: Json<SegmentUiConfig>",
+                created_by,
+                updated_by,
+                created_at,
+                updated_at
+            FROM segments
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(segment)
+    }
 }
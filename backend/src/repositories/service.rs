@@ -50,12 +50,15 @@ impl ServiceRepository for ServiceRepositoryImpl {
         Ok(service)
     }
 
-    async fn find_all(&self) -> Result<Vec<Service>, AppError> {
+    async fn find_all(&self, owner_id: Option<Uuid>) -> Result<Vec<Service>, AppError> {
         let services = sqlx::query_as!(
             Service,
             r#"
-            SELECT * FROM services ORDER BY created_at DESC
+            SELECT * FROM services
+            WHERE ($1::uuid IS NULL OR owner_id = $1)
+            ORDER BY created_at DESC
             "#,
+            owner_id
         )
         .fetch_all(&self.pool)
         .await?;
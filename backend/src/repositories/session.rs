@@ -0,0 +1,252 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use moka::sync::Cache;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    domains::session::{Session, SessionRepository},
+    error::AppError,
+};
+
+/// キャッシュするセッションの状態
+#[derive(Debug, Clone, Copy)]
+struct SessionStatus {
+    user_id: Uuid,
+    active: bool,
+}
+
+/// リクエストごとにDBを参照しないよう、セッションとユーザーの状態を一定時間キャッシュする
+///
+/// 取り消し・無効化はこのリポジトリを通してキャッシュからも削除するため即時に反映される
+/// （DBを直接更新した場合や複数台構成では最大 status_ttl だけ遅れる）
+#[derive(Clone)]
+pub struct SessionRepositoryImpl {
+    pool: PgPool,
+    status_cache: Cache<Uuid, SessionStatus>,
+}
+
+impl SessionRepositoryImpl {
+    pub fn new(pool: PgPool, status_ttl: Duration) -> Self {
+        let status_cache = Cache::builder()
+            .max_capacity(10_000)
+            .time_to_live(status_ttl)
+            .support_invalidation_closures()
+            .build();
+
+        Self { pool, status_cache }
+    }
+}
+
+#[async_trait::async_trait]
+impl SessionRepository for SessionRepositoryImpl {
+    async fn create(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Session, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let session = sqlx::query_as!(
+            Session,
+            r#"
+            INSERT INTO user_sessions (user_id)
+            VALUES ($1)
+            RETURNING id, user_id, created_at, revoked_at
+            "#,
+            user_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO refresh_tokens (token_hash, session_id, expires_at)
+            VALUES ($1, $2, $3)
+            "#,
+            token_hash,
+            session.id,
+            expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(session)
+    }
+
+    async fn rotate(
+        &self,
+        token_hash: &str,
+        new_token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Session, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let current = sqlx::query!(
+            r#"
+            SELECT
+                t.expires_at,
+                t.used_at,
+                s.id as session_id,
+                s.user_id,
+                s.created_at,
+                s.revoked_at
+            FROM refresh_tokens t
+            JOIN user_sessions s ON s.id = t.session_id
+            WHERE t.token_hash = $1
+            FOR UPDATE OF t, s
+            "#,
+            token_hash
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::AuthError)?;
+
+        if current.revoked_at.is_some() || current.expires_at <= Utc::now() {
+            return Err(AppError::AuthError);
+        }
+
+        if current.used_at.is_some() {
+            sqlx::query!(
+                r#"
+                UPDATE user_sessions SET revoked_at = CURRENT_TIMESTAMP WHERE id = $1
+                "#,
+                current.session_id
+            )
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            self.status_cache.invalidate(&current.session_id);
+
+            tracing::warn!(
+                "Refresh token reused for session {} of user {}; session revoked",
+                current.session_id,
+                current.user_id
+            );
+            return Err(AppError::AuthError);
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE refresh_tokens SET used_at = CURRENT_TIMESTAMP WHERE token_hash = $1
+            "#,
+            token_hash
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO refresh_tokens (token_hash, session_id, expires_at)
+            VALUES ($1, $2, $3)
+            "#,
+            new_token_hash,
+            current.session_id,
+            expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Session {
+            id: current.session_id,
+            user_id: current.user_id,
+            created_at: current.created_at,
+            revoked_at: current.revoked_at,
+        })
+    }
+
+    async fn is_active(&self, id: Uuid) -> Result<bool, AppError> {
+        if let Some(status) = self.status_cache.get(&id) {
+            return Ok(status.active);
+        }
+
+        let Some(row) = sqlx::query!(
+            r#"
+            SELECT
+                s.user_id,
+                (s.revoked_at IS NULL AND u.is_active) as "active!"
+            FROM user_sessions s
+            JOIN users u ON u.id = s.user_id
+            WHERE s.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            return Ok(false);
+        };
+
+        self.status_cache.insert(
+            id,
+            SessionStatus {
+                user_id: row.user_id,
+                active: row.active,
+            },
+        );
+
+        Ok(row.active)
+    }
+
+    async fn revoke(&self, id: Uuid) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE user_sessions
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE id = $1 AND revoked_at IS NULL
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        self.status_cache.invalidate(&id);
+
+        Ok(())
+    }
+
+    async fn revoke_all(&self, user_id: Uuid) -> Result<u64, AppError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE user_sessions
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE user_id = $1 AND revoked_at IS NULL
+            "#,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        self.status_cache
+            .invalidate_entries_if(move |_, status| status.user_id == user_id)
+            .map_err(|e| anyhow::anyhow!("Session cache invalidation failed: {}", e))?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn revoke_others(&self, user_id: Uuid, session_id: Uuid) -> Result<u64, AppError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE user_sessions
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE user_id = $1 AND id <> $2 AND revoked_at IS NULL
+            "#,
+            user_id,
+            session_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        self.status_cache
+            .invalidate_entries_if(move |id, status| status.user_id == user_id && *id != session_id)
+            .map_err(|e| anyhow::anyhow!("Session cache invalidation failed: {}", e))?;
+
+        Ok(result.rows_affected())
+    }
+}
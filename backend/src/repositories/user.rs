@@ -2,10 +2,13 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
-    domains::user::{CreateUserParam, User, UserRepository},
+    domains::user::{CreateUserParam, User, UserRepository, UserRole},
     error::AppError,
 };
 
+/// 最初の管理者の登録を直列化するアドバイザリロックのキー
+const INITIAL_ADMIN_LOCK_KEY: i64 = 0x6768_6f73_7400_0001;
+
 /// UserRepositoryの実装構造体
 #[derive(Debug, Clone)]
 pub struct UserRepositoryImpl {
@@ -20,9 +23,9 @@ impl UserRepositoryImpl {
 
 #[async_trait::async_trait]
 impl UserRepository for UserRepositoryImpl {
-    async fn create(&self, params: CreateUserParam) -> Result<User, AppError> {
+    async fn create(&self, params: CreateUserParam, role: UserRole) -> Result<User, AppError> {
         let password_hash = params.hash_password()?;
-        let role_str = params.role.unwrap_or_default().as_str();
+        let role_str = role.as_str();
 
         let user = sqlx::query_as!(
             User,
@@ -50,6 +53,52 @@ impl UserRepository for UserRepositoryImpl {
 
         Ok(user)
     }
+    async fn create_initial_admin(
+        &self,
+        params: CreateUserParam,
+    ) -> Result<Option<User>, AppError> {
+        let password_hash = params.hash_password()?;
+        let mut tx = self.pool.begin().await?;
+
+        // 複数台が同時に起動しても1人しか登録しないよう、トランザクション単位のアドバイザリロックで直列化する
+        sqlx::query!(
+            r#"
+            SELECT pg_advisory_xact_lock($1)
+            "#,
+            INITIAL_ADMIN_LOCK_KEY
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            INSERT INTO users (
+                employee_id,
+                username,
+                name,
+                email,
+                password_hash,
+                role
+            )
+            SELECT $1, $2, $3, $4, $5, $6
+            WHERE NOT EXISTS (SELECT 1 FROM users)
+            RETURNING *
+            "#,
+            params.employee_id,
+            params.username,
+            params.name,
+            params.email,
+            password_hash,
+            UserRole::Admin.as_str()
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(user)
+    }
     async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, AppError> {
         let user = sqlx::query_as!(
             User,
@@ -67,13 +116,95 @@ impl UserRepository for UserRepositoryImpl {
         let user = sqlx::query_as!(
             User,
             r#"
-            SELECT * FROM users WHERE email = $1
+            SELECT * FROM users WHERE lower(email) = lower($1)
             "#,
             email
         )
         .fetch_optional(&self.pool)
         .await?;
 
+        Ok(user)
+    }
+    async fn set_active(
+        &self,
+        id: Uuid,
+        is_active: bool,
+        updated_by: Uuid,
+    ) -> Result<User, AppError> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users
+            SET
+                is_active = $2,
+                updated_by = $3,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            RETURNING *
+            "#,
+            id,
+            is_active,
+            updated_by
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AppError::NotFound(format!("User {} not found", id)))?;
+
+        Ok(user)
+    }
+    async fn update_password(
+        &self,
+        id: Uuid,
+        password_hash: &str,
+        updated_by: Uuid,
+    ) -> Result<(), AppError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE users
+            SET
+                password_hash = $2,
+                updated_by = $3,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            "#,
+            id,
+            password_hash,
+            updated_by
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("User {} not found", id)));
+        }
+
+        Ok(())
+    }
+    async fn update_role(
+        &self,
+        id: Uuid,
+        role: UserRole,
+        updated_by: Uuid,
+    ) -> Result<User, AppError> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users
+            SET
+                role = $2,
+                updated_by = $3,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = $1
+            RETURNING *
+            "#,
+            id,
+            role.as_str(),
+            updated_by
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AppError::NotFound(format!("User {} not found", id)))?;
+
         Ok(user)
     }
 }
@@ -0,0 +1,198 @@
+//! DBを使うテストの共通処理（`#[sqlx::test]` が用意したDBに登録する）
+
+use std::sync::Arc;
+
+use chrono::Duration;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    domains::{
+        budget::BudgetPolicy, fiscal::FiscalCalendar, login_attempt::LockoutPolicy, user::UserRole,
+    },
+    extractors::AuthUser,
+    handlers::auth::Claims,
+    mailer::FileMailer,
+    rate_limit::IpRateLimiter,
+    repositories::{
+        account_item::AccountItemRepositoryImpl, allocation::AllocationRepositoryImpl,
+        exchange_rate::ExchangeRateRepositoryImpl, fiscal::FiscalPeriodRepositoryImpl,
+        invitation::InvitationRepositoryImpl, job::JobRepositoryImpl,
+        login_attempt::LoginAttemptRepositoryImpl, password_reset::PasswordResetRepositoryImpl,
+        pl_entry::PlEntryRepositoryImpl, pl_sandbox::PlSandboxRepositoryImpl,
+        pl_snapshot::PlSnapshotRepositoryImpl, project::ProjectRepositoryImpl,
+        scenario_lock::ScenarioLockRepositoryImpl, segment::SegmentRepositoryImpl,
+        service::ServiceRepositoryImpl, session::SessionRepositoryImpl, theme::ThemeRepositoryImpl,
+        user::UserRepositoryImpl,
+    },
+};
+
+pub const REPORTING_CURRENCY: &str = "JPY";
+
+/// 既定の設定で組み立てた AppState
+pub fn app_state(pool: PgPool) -> AppState {
+    let currency = REPORTING_CURRENCY.to_string();
+
+    AppState {
+        user_repository: Arc::new(UserRepositoryImpl::new(pool.clone())),
+        theme_repository: Arc::new(ThemeRepositoryImpl::new(pool.clone())),
+        project_repository: Arc::new(ProjectRepositoryImpl::new(pool.clone())),
+        segment_repository: Arc::new(SegmentRepositoryImpl::new(pool.clone())),
+        service_repository: Arc::new(ServiceRepositoryImpl::new(pool.clone())),
+        job_repository: Arc::new(JobRepositoryImpl::new(pool.clone())),
+        account_item_repository: Arc::new(AccountItemRepositoryImpl::new(pool.clone())),
+        pl_entry_repository: Arc::new(PlEntryRepositoryImpl::new(pool.clone(), currency.clone())),
+        pl_sandbox_repository: Arc::new(PlSandboxRepositoryImpl::new(pool.clone())),
+        pl_snapshot_repository: Arc::new(PlSnapshotRepositoryImpl::new(
+            pool.clone(),
+            currency.clone(),
+        )),
+        scenario_lock_repository: Arc::new(ScenarioLockRepositoryImpl::new(pool.clone())),
+        fiscal_period_repository: Arc::new(FiscalPeriodRepositoryImpl::new(pool.clone())),
+        exchange_rate_repository: Arc::new(ExchangeRateRepositoryImpl::new(
+            pool.clone(),
+            currency.clone(),
+        )),
+        allocation_repository: Arc::new(AllocationRepositoryImpl::new(
+            pool.clone(),
+            currency.clone(),
+        )),
+        session_repository: Arc::new(SessionRepositoryImpl::new(
+            pool.clone(),
+            std::time::Duration::from_secs(30),
+        )),
+        invitation_repository: Arc::new(InvitationRepositoryImpl::new(pool.clone())),
+        password_reset_repository: Arc::new(PasswordResetRepositoryImpl::new(pool.clone())),
+        login_attempt_repository: Arc::new(LoginAttemptRepositoryImpl::new(pool)),
+        mailer: Arc::new(FileMailer::new(None).expect("file mailer without a directory")),
+        fiscal_calendar: FiscalCalendar::new(4).expect("valid start month"),
+        job_budget_policy: BudgetPolicy::Warn,
+        reporting_currency: currency,
+        jwt_secret: "test-secret".to_string(),
+        access_token_ttl: Duration::minutes(15),
+        refresh_token_ttl: Duration::days(14),
+        invitation_ttl: Duration::days(7),
+        password_reset_ttl: Duration::minutes(60),
+        app_url: "http://localhost:5173".to_string(),
+        lockout_policy: LockoutPolicy {
+            max_failures: 5,
+            lockout: Duration::minutes(15),
+        },
+        auth_rate_limiter: IpRateLimiter::new(1000, std::time::Duration::from_secs(60)),
+        trust_proxy_headers: false,
+    }
+}
+
+/// トークンを検証済みのユーザーとして扱う
+pub fn auth_user(user_id: Uuid, role: UserRole) -> AuthUser {
+    AuthUser {
+        claims: Claims {
+            sub: user_id.to_string(),
+            role: role.as_str().to_string(),
+            iat: 0,
+            exp: usize::MAX,
+            name: "Test".to_string(),
+            sid: Uuid::new_v4().to_string(),
+        },
+    }
+}
+
+pub async fn insert_user(pool: &PgPool, role: UserRole) -> Uuid {
+    let id = Uuid::new_v4();
+    let key = id.simple().to_string();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO users (id, employee_id, username, name, email, password_hash, role)
+        VALUES ($1, $2, $3, $4, $5, 'unused', $6)
+        "#,
+        id,
+        &key[..5],
+        &key[..20],
+        "Test",
+        format!("{}@example.com", key),
+        role.as_str()
+    )
+    .execute(pool)
+    .await
+    .expect("insert user");
+
+    id
+}
+
+pub async fn insert_project(pool: &PgPool, owner_id: Uuid) -> Uuid {
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO projects (name, owner_id, created_by)
+        VALUES ('Project', $1, $1)
+        RETURNING id
+        "#,
+        owner_id
+    )
+    .fetch_one(pool)
+    .await
+    .expect("insert project")
+}
+
+pub async fn insert_service(pool: &PgPool) -> Uuid {
+    let slug = Uuid::new_v4().simple().to_string();
+
+    sqlx::query_scalar!(
+        r#"
+        WITH segment AS (
+            INSERT INTO segments (slug, name) VALUES ($1, 'Segment') RETURNING id
+        )
+        INSERT INTO services (slug, name, segment_id)
+        SELECT $1, 'Service', id FROM segment
+        RETURNING id
+        "#,
+        slug
+    )
+    .fetch_one(pool)
+    .await
+    .expect("insert service")
+}
+
+pub async fn insert_job(
+    pool: &PgPool,
+    service_id: Uuid,
+    project_id: Option<Uuid>,
+    owner_id: Uuid,
+) -> Uuid {
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO jobs (service_id, project_id, title, owner_id, created_by)
+        VALUES ($1, $2, 'Job', $3, $3)
+        RETURNING id
+        "#,
+        service_id,
+        project_id,
+        owner_id
+    )
+    .fetch_one(pool)
+    .await
+    .expect("insert job")
+}
+
+pub async fn insert_assignee(pool: &PgPool, job_id: Uuid, user_id: Uuid) {
+    sqlx::query!(
+        r#"
+        INSERT INTO job_assignees (job_id, user_id, assigned_by)
+        VALUES ($1, $2, $2)
+        "#,
+        job_id,
+        user_id
+    )
+    .execute(pool)
+    .await
+    .expect("insert assignee");
+}
+
+/// マイグレーションで登録済みの勘定科目（表示順の先頭）
+pub async fn account_item_id(pool: &PgPool) -> Uuid {
+    sqlx::query_scalar!("SELECT id FROM account_items ORDER BY display_order LIMIT 1")
+        .fetch_one(pool)
+        .await
+        .expect("seeded account item")
+}